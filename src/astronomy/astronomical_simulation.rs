@@ -1,54 +1,60 @@
 use crate::*;
 use rayon::prelude::*;
-use std::collections::HashMap;
-
-const RADIUS_MULTIPLIER: f64 = 100.0;
-const REDRAW_AMOUNT: u16 = 2500;
-
-
-/* ----- TEXTURES ----- */
-const STAR_TEXTURE: &str = "textures/star_grayscale.jpg";
-const EARTH_TEXTURE: &str = "textures/earth.jpeg";
-const TERRESTRIAL_TEXTURE: &str = "textures/terrestrial.jpg";
-
-
-/* ----- BACKGROUND IMAGE ----- */
-const BACKGROUND_IMAGE: &str = "images/astronomy_bg_small.jpg";
 
 
 pub trait Celestial {
     fn point_body(&self) -> &PointBody;
     fn point_body_mut(&mut self) -> &mut PointBody;
-    fn get_radius(&self) -> Scalar;
+    fn get_radius(&self) -> Length;
     fn get_name(&self) -> String;
     fn is_star(&self) -> bool;
     fn is_planet(&self) -> bool;
     fn planet_type(&self) -> Option<PlanetType>;
     fn get_color(&self) -> [f64; 4];
+
+    /// Low-cost tracers are drawn as points, without labels and textures
+    fn is_tracer(&self) -> bool { false }
+
+    /// The power radiated by the celestial
+    fn get_luminosity(&self) -> Power { Power::new(ZERO) }
+
+    /// The name of the body the celestial's manoeuvres are measured around
+    fn get_reference(&self) -> Option<String> { None }
+
+    /// Update the non-gravitational state of the celestial (e.g. engine burns)
+    /// over the step of `dt` seconds starting at `time`
+    fn update(&mut self, _time: Scalar, _dt: Scalar, _reference: Option<&PointBody>) {}
+
+    /// The concrete type of the celestial, which is needed to write it to a checkpoint
+    fn concrete(&self) -> Option<ConcreteCelestial<'_>> { None }
 }
 
 
 pub struct AstronomicalSimulation {
     dt: Scalar,
     time: Scalar,
+    epoch: Option<f64>,
     celestials: Vec<Box<dyn Celestial + Sync>>,
     name: String,
 
     time_stopped: bool,
     move_speed: f32,
 
-    window: Window,
-    textures: HashMap<String, u32>,
-    drag: [f64; 2],
-    button_held_down: bool,
-    mouse_pos: [f64; 2],
-    rotation_sensitivity: f32,
-    
-    font: Font,
-
-    calculation_amount: u16,
-
     multi_processor: bool,
+    integrator: Integrator,
+    cached_accelerations: Option<Vec<Vector>>,
+
+    events: EventDetector,
+    potentials: Vec<HaloPotential>,
+    map_size: Scalar,
+    save_path: String,
+
+    steps: u64,
+    checkpoint_interval: u64,
+    checkpoint_path: String,
+    trajectories: Vec<TrajectoryOutput>,
+    ephemerides: Vec<OemOutput>,
+    rails: Option<Rails>,
 }
 
 
@@ -60,266 +66,537 @@ impl AstronomicalSimulation {
         move_speed: f32,
         multi_processor: bool
     ) -> Self {
-        let mut s: Self = Self {
+        Self {
             dt,
             celestials,
             name,
             move_speed,
             multi_processor,
             ..Self::default()
-        };
-        
-        s.load_textures();
-        s        
+        }
     }
     
-    fn load_textures(&mut self) {
-        self.textures.insert(
-            String::from("star"), Window::load_texture(STAR_TEXTURE) 
-        );
-        self.textures.insert(
-            String::from("terrestrial"), Window::load_texture(TERRESTRIAL_TEXTURE)
-        );
-        self.textures.insert(
-            String::from("earth"), Window::load_texture(EARTH_TEXTURE)
-        );
-    }
-
     pub fn add_celestial(&mut self, celestial: impl Celestial + Sync + 'static) {
+        self.cached_accelerations = None;
         self.celestials.push(Box::new(celestial))
     }
 
+    /// Add a static potential (e.g. a dark-matter halo), which acts on every body except its anchor
+    pub fn add_potential(&mut self, potential: HaloPotential) {
+        self.cached_accelerations = None;
+        self.potentials.push(potential)
+    }
+
+    /// Propagate the element sets to the given Julian day (UTC), which should be the current time of the simulation,
+    /// and add the satellites as test particles around the `Planet` named `Earth`.
+    /// Returns the number of added satellites; nothing is added if one of them fails.
+    pub fn add_satellites(&mut self, tles: &[Tle], julian_day: f64) -> Result<usize, TleError> {
+        let earth: PointBody = self.celestials.iter()
+            .find(|celestial| celestial.get_name() == "Earth" && matches!(celestial.concrete(), Some(ConcreteCelestial::Planet(_))))
+            .ok_or_else(|| TleError::MissingCenter(String::from("Earth")))?
+            .point_body()
+            .clone();
+        let satellites: Vec<Planet> = tles.iter()
+            .map(|tle| tle.satellite(&earth, julian_day))
+            .collect::<Result<Vec<Planet>, TleError>>()?;
+        let count: usize = satellites.len();
+        for satellite in satellites {
+            self.add_celestial(satellite);
+        }
+        Ok(count)
+    }
+
+    /// Add the minor planets and comets of a catalogue around the `Star` named `Sun`,
+    /// at the given Julian day (TT), or at the current time of the simulation if `None`.
+    /// The bodies are test particles, or low-mass planets if `test_particles` is `false`.
+    /// Returns the number of added bodies.
+    pub fn add_minor_bodies(&mut self,
+                            catalogue: &MpcCatalogue,
+                            julian_day: Option<f64>,
+                            test_particles: bool) -> Result<usize, MpcError> {
+        let julian_day: f64 = julian_day.or_else(|| self.get_julian_day()).ok_or(MpcError::MissingEpoch)?;
+        let sun: PointBody = self.celestials.iter()
+            .find(|celestial| celestial.get_name() == "Sun" && matches!(celestial.concrete(), Some(ConcreteCelestial::Star(_))))
+            .ok_or_else(|| MpcError::MissingCenter(String::from("Sun")))?
+            .point_body()
+            .clone();
+        let bodies: Vec<Planet> = catalogue.bodies(&sun, julian_day, test_particles);
+        let count: usize = bodies.len();
+        for body in bodies {
+            self.add_celestial(body);
+        }
+        Ok(count)
+    }
+
     /* ----- TIME ----- */
     pub fn stop_time(&mut self) { self.time_stopped = true; }
     pub fn resume_time(&mut self) { self.time_stopped = false; }
     pub fn toggle_time(&mut self) { self.time_stopped = !self.time_stopped; }
-    
-    /* ----- CELESTIALS ----- */
-    pub fn get_celestial_from_index(&self, n: usize) -> &Box<dyn Celestial + Sync> {
-        self.celestials.iter().nth(n).unwrap()
+    pub fn is_time_stopped(&self) -> bool { self.time_stopped }
+    pub fn get_time(&self) -> Scalar { self.time }
+    pub fn get_dt(&self) -> Scalar { self.dt }
+    pub fn set_time(&mut self, time: Scalar) { self.time = time; }
+    pub fn get_name(&self) -> String { self.name.clone() }
+
+    /// The Julian day of the time `0` of the simulation, if the date of the simulation is known
+    pub fn get_epoch(&self) -> Option<f64> { self.epoch }
+    pub fn set_epoch(&mut self, epoch: Option<f64>) { self.epoch = epoch; }
+
+    /// The Julian day of the current time, if the date of the simulation is known
+    pub fn get_julian_day(&self) -> Option<f64> {
+        self.epoch.map(|epoch| epoch + (self.time / DAY.value).to_f64())
     }
 
-    /* ----- GRAPHICS ----- */
-    pub fn draw_objects(&self) {
-        let map_size: Scalar = AU;  // TODO: Calculate map size
-        for celestial in self.celestials.iter() {
-            // Calculate the coordinates in the <-1; +1> range
-            let coordinates: [f32; 3] = [
-                (celestial.point_body().coordinates.x / map_size).value as f32,
-                (celestial.point_body().coordinates.y / map_size).value as f32,
-                (celestial.point_body().coordinates.z / map_size).value as f32
-            ];
-            
-            let colors: [f64; 4] = celestial.get_color();
-            let color: [f32; 3] = [colors[0] as f32, colors[1] as f32, colors[2] as f32];
-            
-            self.window.draw_text(
-                coordinates[0],
-                coordinates[1],
-                coordinates[2],
-                celestial.get_name(),
-                self.font.clone(),
-                24.0,
-                [1.0, 1.0, 1.0]
-            );
-            
-            if celestial.is_star() {
-                Window::add_light_source(color, coordinates);
-                Window::bind_texture(
-                    unsafe { *self.textures.get("star").unwrap_unchecked() }
-                )
-            } else if celestial.is_planet() {
-                Window::cancel_emission();
-                Window::react_to_light(color);
-                Window::bind_texture(
-                    unsafe { 
-                        *self.textures.get(
-                            if celestial.get_name().to_lowercase().as_str() == "earth" { "earth" }
-                            else { "terrestrial" }
-                        ).unwrap_unchecked()
-                    }
-                )
+    /// Set the time step. A negative `dt` runs the simulation backwards in time.
+    pub fn set_dt(&mut self, dt: Scalar) { self.dt = dt; }
+
+    /// Flip the direction of time (`dt` -> `-dt`)
+    pub fn reverse_time(&mut self) { self.dt = -self.dt; }
+    pub fn is_time_reversed(&self) -> bool { self.dt < ZERO }
+
+    /// Calculate the accelerations on all the threads of the global thread pool
+    pub fn set_multi_processor(&mut self, multi_processor: bool) { self.multi_processor = multi_processor; }
+    pub fn is_multi_processor(&self) -> bool { self.multi_processor }
+
+    pub fn get_integrator(&self) -> Integrator { self.integrator }
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+        self.cached_accelerations = None;
+    }
+
+    /// Advance the simulation by `dt` seconds
+    pub fn step(&mut self) {
+        self.time += self.dt;
+        self.calculate();
+
+        self.steps += 1;
+        for trajectory in self.trajectories.iter_mut() {
+            trajectory.record(self.steps, self.time, &self.celestials);
+        }
+        for ephemeris in self.ephemerides.iter_mut() {
+            ephemeris.record(self.steps, self.time, &self.celestials);
+        }
+        if self.checkpoint_interval != 0 && self.steps.is_multiple_of(self.checkpoint_interval) {
+            self.checkpoint();
+        }
+    }
+
+    /// The number of steps taken since the start of the run
+    pub fn get_steps(&self) -> u64 { self.steps }
+
+    /// Take `steps` steps without a viewer (stopping the time does not apply here)
+    pub fn run_for(&mut self, steps: u64) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// End the run: flush the trajectories and write a checkpoint (if they are turned on).
+    /// The errors are reported on the standard error.
+    pub fn finish(&mut self) {
+        if let Err(error) = self.flush_trajectories() {
+            eprintln!("Could not write the trajectory: {error}");
+        }
+        if self.checkpoint_interval != 0 {
+            self.checkpoint();
+        }
+    }
+
+    /// Integrate `steps` steps forward, then the same number of steps backward,
+    /// and measure how far the celestials are from where they started.
+    /// Afterwards the original state is restored, and no events are recorded during the test.\
+    /// Only the `Leapfrog` integrator is reversible, with `Euler` the errors grow with the number of steps.
+    pub fn test_reversibility(&mut self, steps: usize) -> ReversibilityReport {
+        let time: Scalar = self.time;
+        let dt: Scalar = self.dt;
+        let initial: Vec<PointBody> = self.celestials.iter()
+            .map(|celestial| celestial.point_body().clone())
+            .collect();
+        let events: EventDetector = std::mem::take(&mut self.events);
+        let steps_taken: u64 = self.steps;
+        let checkpoint_interval: u64 = std::mem::take(&mut self.checkpoint_interval);
+        let trajectories: Vec<TrajectoryOutput> = std::mem::take(&mut self.trajectories);
+        let ephemerides: Vec<OemOutput> = std::mem::take(&mut self.ephemerides);
+
+        for _ in 0..steps { self.step(); }
+        self.reverse_time();
+        for _ in 0..steps { self.step(); }
+
+        let mut report: ReversibilityReport = ReversibilityReport {
+            steps,
+            position_error: ZERO,
+            velocity_error: ZERO,
+            worst: None
+        };
+        for (celestial, body) in self.celestials.iter_mut().zip(initial) {
+            let current: &PointBody = celestial.point_body();
+            let position_error: Scalar = current.distance(&body.coordinates).value;
+            let velocity_error: Scalar = (current.velocity - body.velocity).magnitude();
+
+            if report.worst.is_none() || position_error > report.position_error {
+                report.position_error = position_error;
+                report.worst = Some(celestial.get_name());
             }
-            
-            let radius: f64 = (celestial.get_radius() / map_size).value * RADIUS_MULTIPLIER;
-            
-            Window::enable_light();
-            Window::enable_texture();
-            
-            Window::draw_sphere(coordinates, radius, color, 64);
+            report.velocity_error = report.velocity_error.max(velocity_error);
+
+            *celestial.point_body_mut() = body;
         }
+
+        self.time = time;
+        self.dt = dt;
+        self.events = events;
+        self.steps = steps_taken;
+        self.checkpoint_interval = checkpoint_interval;
+        self.trajectories = trajectories;
+        self.ephemerides = ephemerides;
+        self.cached_accelerations = None;
+        report
     }
+
+    /* ----- EVENTS ----- */
+    pub fn get_event_detector(&self) -> &EventDetector { &self.events }
+    pub fn get_event_detector_mut(&mut self) -> &mut EventDetector { &mut self.events }
+    pub fn add_event(&mut self, event: EventKind) { self.events.add_event(event); }
     
-    fn move_forward(&mut self, amount: f32) {
-        let rot_x: Degree = Degree::from_float(self.window.camera_rotation[1] as f64);
-        let rot_y: Degree = Degree::from_float(self.window.camera_rotation[0] as f64);
-        self.window.camera_location = [
-            self.window.camera_location[0] - amount * (rot_x.sin() * rot_y.cos()) as f32,
-            self.window.camera_location[1] + amount * rot_y.sin() as f32,
-            self.window.camera_location[2] + amount * (rot_x.cos() * rot_y.cos()) as f32
-        ];
+    /* ----- SAVING ----- */
+    /// Set the `.psa` file the simulation is saved to when pressing `F5`
+    pub fn set_save_path(&mut self, path: impl ToString) { self.save_path = path.to_string(); }
+
+    pub fn get_save_path(&self) -> &str { &self.save_path }
+
+    /// Save the current state to the save path.
+    /// The celestials which cannot be written to a scenario are left out, and their names are returned.
+    pub fn save(&self) -> std::io::Result<Vec<String>> {
+        save_simulation(self, &self.save_path)
     }
-    
-    fn move_backward(&mut self, amount: f32) {
-        self.move_forward(-amount);
+
+    /* ----- EPHEMERIS ----- */
+    /// Move some of the bodies along an ephemeris instead of integrating them.
+    /// The bodies are moved to their places immediately.
+    pub fn set_rails(&mut self, rails: Rails) -> Result<(), SpkError> {
+        rails.apply(self.time, &mut self.celestials)?;
+        self.rails = Some(rails);
+        self.cached_accelerations = None;
+        Ok(())
     }
 
-    fn move_left(&mut self, amount: f32) {
-        let rot_x: Degree = Degree::from_float(self.window.camera_rotation[1] as f64);
-        let rot_y: Degree = Degree::from_float(self.window.camera_rotation[0] as f64);
-        self.window.camera_location = [
-            self.window.camera_location[0] + amount * (rot_x.cos() * rot_y.cos()) as f32,
-            self.window.camera_location[1] + amount * rot_y.sin() as f32,
-            self.window.camera_location[2] + amount * (rot_x.sin() * rot_y.cos()) as f32
-        ];
+    /// Integrate every body again
+    pub fn remove_rails(&mut self) {
+        self.rails = None;
+        self.cached_accelerations = None;
     }
-    
-    fn move_right(&mut self, amount: f32) {
-        self.move_left(-amount);
+
+    pub fn get_rails(&self) -> Option<&Rails> { self.rails.as_ref() }
+
+    /// Move the bodies on rails to their places at the given time.
+    /// If the ephemeris does not cover the time, the bodies are integrated from then on.
+    fn apply_rails(&mut self, time: Scalar) {
+        let result: Result<(), SpkError> = match &self.rails {
+            Some(rails) => rails.apply(time, &mut self.celestials),
+            None => return
+        };
+        if let Err(error) = result {
+            eprintln!("The bodies on rails are integrated from now on: {error}");
+            self.rails = None;
+        }
     }
 
-    fn move_up(&mut self, amount: f32) {
-        self.window.camera_location = [
-            self.window.camera_location[0],
-            self.window.camera_location[1] - amount,
-            self.window.camera_location[2]
-        ];
+    /* ----- TRAJECTORIES ----- */
+    /// Stream the states of the bodies into a trajectory file.
+    /// The current state is written immediately, then the samples follow the cadence of the output.
+    pub fn add_trajectory_output(&mut self, mut output: TrajectoryOutput) {
+        output.sample(self.time, &self.celestials);
+        self.trajectories.push(output);
     }
-    
-    fn move_down(&mut self, amount: f32) {
-        self.move_up(-amount);
+
+    /// Export the trajectories of the bodies as a CCSDS Orbit Ephemeris Message.
+    /// The current state is sampled immediately, and the file is written with the trajectory outputs.
+    pub fn add_oem_output(&mut self, mut output: OemOutput) {
+        output.sample(self.time, &self.celestials);
+        self.ephemerides.push(output);
     }
-    
-    fn rotate_camera(&mut self, roll: f32, pitch: f32, yaw: f32) {
-        self.window.camera_rotation = [
-            self.window.camera_rotation[0] + roll,
-            self.window.camera_rotation[1] + pitch,
-            self.window.camera_rotation[2] + yaw
-        ]
-    }
-
-    fn handle_events(&mut self, event: WindowEvent) {
-        let (x, y) = self.window.get_cursor_pos();
-        if self.button_held_down {
-            let dx: f64 = x - self.mouse_pos[0];
-            let dy: f64 = y - self.mouse_pos[1];
-            self.drag = [dx, dy];
-            self.rotate_camera(self.drag[1] as f32 * self.rotation_sensitivity, self.drag[0] as f32 * self.rotation_sensitivity, 0.0);
+
+    /// Write the buffered samples of the trajectory outputs and the ephemerides into their files
+    pub fn flush_trajectories(&mut self) -> std::io::Result<()> {
+        for trajectory in self.trajectories.iter_mut() {
+            trajectory.flush()?;
         }
-        self.mouse_pos = [x, y];
+        for ephemeris in self.ephemerides.iter() {
+            ephemeris.write()?;
+        }
+        Ok(())
+    }
 
-        match event {
-            WindowEvent::Key(Key::W, _, Action::Press, _) | WindowEvent::Key(Key::W, _, Action::Repeat, _) => {
-                self.move_forward(self.move_speed);
-            },
-            WindowEvent::Key(Key::S, _, Action::Press, _) | WindowEvent::Key(Key::S, _, Action::Repeat, _) => {
-                self.move_backward(self.move_speed);
-            },
-            WindowEvent::Key(Key::D, _, Action::Press, _) | WindowEvent::Key(Key::D, _, Action::Repeat, _) => {
-                self.move_right(self.move_speed);
-            },
-            WindowEvent::Key(Key::A, _, Action::Press, _) | WindowEvent::Key(Key::A, _, Action::Repeat, _) => {
-                self.move_left(self.move_speed);
-            },
-            WindowEvent::Key(Key::LeftShift, _, Action::Press, _) => {
-                self.move_speed *= 5.0;
-            },
-            WindowEvent::Key(Key::LeftShift, _, Action::Release, _) => {
-                self.move_speed /= 5.0;
-            },
-            WindowEvent::Key(Key::Space, _, Action::Press, _) => {
-                self.time_stopped = !self.time_stopped;
-            },
-            WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
-                self.button_held_down = true;
-                self.drag = [0.0; 2];
-            },
-            WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
-                self.button_held_down = false;
-                self.drag = [0.0; 2];
+    /* ----- CHECKPOINTS ----- */
+    /// Write a checkpoint to `path` after every `interval` steps, and at the end of the run.
+    /// An interval of `0` turns the checkpoints off.
+    pub fn set_checkpoints(&mut self, interval: u64, path: impl ToString) {
+        self.checkpoint_interval = interval;
+        self.checkpoint_path = path.to_string();
+    }
+
+    /// Write a checkpoint to the checkpoint path
+    pub fn checkpoint(&self) {
+        if let Err(error) = self.write_checkpoint(&self.checkpoint_path) {
+            eprintln!("Could not write the checkpoint {}: {error}", self.checkpoint_path);
+        }
+    }
+
+    /// Write the full physics state to a binary checkpoint file:
+    /// the time, the time step, the integrator with its cached accelerations,
+    /// the static potentials, every celestial with its concrete type, the bodies on rails
+    /// (with the path of their kernel) and the epoch. The event detector and the view are not part of the checkpoint.
+    pub fn write_checkpoint(&self, path: impl AsRef<std::path::Path>) -> Result<(), CheckpointError> {
+        let mut writer: CheckpointWriter = CheckpointWriter::new();
+        writer.write_string(&self.name);
+        writer.write_scalar(self.time);
+        writer.write_scalar(self.dt);
+        writer.write_u64(self.steps);
+        writer.write_u8(match self.integrator {
+            Integrator::Euler => 0,
+            Integrator::Leapfrog => 1
+        });
+
+        writer.write_len(self.potentials.len());
+        for potential in self.potentials.iter() {
+            writer.write_string(&potential.anchor);
+            writer.write_scalar(potential.mass.value);
+            writer.write_scalar(potential.scale_radius.value);
+        }
+
+        writer.write_len(self.celestials.len());
+        for celestial in self.celestials.iter() {
+            writer.write_celestial(celestial.as_ref())?;
+        }
+
+        writer.write_bool(self.cached_accelerations.is_some());
+        if let Some(accelerations) = &self.cached_accelerations {
+            writer.write_len(accelerations.len());
+            for acceleration in accelerations.iter() {
+                writer.write_vector(acceleration);
             }
-            _ => ()
         }
+
+        writer.write_bool(self.rails.is_some());
+        if let Some(rails) = &self.rails {
+            writer.write_string(&rails.ephemeris.kernel.path.to_string_lossy());
+            writer.write_f64(rails.ephemeris.epoch);
+            writer.write_i32(rails.ephemeris.center);
+            writer.write_len(rails.bodies.len());
+            for (name, target) in rails.bodies.iter() {
+                writer.write_string(name);
+                writer.write_i32(*target);
+            }
+        }
+
+        writer.write_bool(self.epoch.is_some());
+        if let Some(epoch) = self.epoch {
+            writer.write_f64(epoch);
+        }
+
+        writer.save(path)
     }
-    
-    pub fn run(&mut self) {
-        let this: *mut Self = self as *mut Self;
-        
-        self.window.start_render_loop(move |_w, e| {
-            unsafe { 
-                if !(*this).time_stopped {
-                    (*this).time += (*this).dt;
-                    (*this).calculate();
-                }
-                
-                if (*this).calculation_amount < REDRAW_AMOUNT {
-                    (*this).calculation_amount += 1;
-                    return;
-                }
 
-                (*this).calculation_amount = 0;
-                
-                Window::load_identity_matrix();
-                Window::clear_screen();
+    /// Restore a simulation from a checkpoint file.
+    /// The restored simulation continues bit-identically to the one the checkpoint was written from.
+    pub fn read_checkpoint(path: impl AsRef<std::path::Path>) -> Result<Self, CheckpointError> {
+        let mut reader: CheckpointReader = CheckpointReader::open(path)?;
+        let name: String = reader.read_string()?;
+        let time: Scalar = reader.read_scalar()?;
+        let dt: Scalar = reader.read_scalar()?;
+        let steps: u64 = reader.read_u64()?;
+        let integrator: Integrator = match reader.read_u8()? {
+            0 => Integrator::Euler,
+            1 => Integrator::Leapfrog,
+            value => return Err(CheckpointError::Corrupt(format!("unknown integrator {value}")))
+        };
 
-                for (_, event) in flush_messages(e) {
-                    (*this).handle_events(event);
-                }
-                
-                (*this).window.move_camera();
-                
-                (*this).window.load_background_image();
-                (*this).draw_objects();
-
-                (*this).window.swap_buffers();
-                (*this).window.poll_events();
+        let mut potentials: Vec<HaloPotential> = Vec::new();
+        for _ in 0..reader.read_len()? {
+            let anchor: String = reader.read_string()?;
+            potentials.push(HaloPotential::new(anchor, Mass::new(reader.read_scalar()?), Length::new(reader.read_scalar()?)));
+        }
+
+        let mut celestials: Vec<Box<dyn Celestial + Sync>> = Vec::new();
+        for _ in 0..reader.read_len()? {
+            celestials.push(reader.read_celestial()?);
+        }
+
+        let cached_accelerations: Option<Vec<Vector>> = if reader.read_bool()? {
+            let mut accelerations: Vec<Vector> = Vec::new();
+            for _ in 0..reader.read_len()? {
+                accelerations.push(reader.read_vector()?);
+            }
+            Some(accelerations)
+        } else { None };
+
+        let rails: Option<Rails> = if reader.version >= 2 && reader.read_bool()? {
+            let kernel: String = reader.read_string()?;
+            let epoch: f64 = reader.read_f64()?;
+            let center: i32 = reader.read_i32()?;
+            let mut bodies: Vec<(String, i32)> = Vec::new();
+            for _ in 0..reader.read_len()? {
+                bodies.push((reader.read_string()?, reader.read_i32()?));
             }
+            let kernel: SpkKernel = SpkKernel::open(kernel).map_err(CheckpointError::Ephemeris)?;
+            Some(Rails::new(Ephemeris::new(std::sync::Arc::new(kernel), epoch, center), bodies))
+        } else { None };
+        let epoch: Option<f64> = if reader.version >= 3 && reader.read_bool()? {
+            Some(reader.read_f64()?)
+        } else { None };
+        reader.finish()?;
+
+        let mut simulation: Self = Self::new(dt, celestials, name, 0.02, false);
+        simulation.time = time;
+        simulation.steps = steps;
+        simulation.integrator = integrator;
+        simulation.potentials = potentials;
+        simulation.cached_accelerations = cached_accelerations;
+        simulation.rails = rails;
+        simulation.epoch = epoch;
+        Ok(simulation)
+    }
+
+    /* ----- CELESTIALS ----- */
+    pub fn get_celestial_from_index(&self, n: usize) -> &(dyn Celestial + Sync) {
+        self.celestials[n].as_ref()
+    }
+
+    pub fn get_celestials(&self) -> &[Box<dyn Celestial + Sync>] { &self.celestials }
+
+    /// Calculate the barycenter of the celestials
+    pub fn barycenter(&self) -> PointBody { barycenter(&self.celestials) }
+
+    /// Calculate the kinetic and the gravitational potential energy of the celestials
+    pub fn energy(&self) -> (Energy, Energy) { energy(&self.celestials) }
+
+    pub fn get_celestial_from_name(&self, name: &str) -> Option<&(dyn Celestial + Sync)> {
+        self.celestials.iter().find(|celestial| celestial.get_name() == name).map(|celestial| celestial.as_ref())
+    }
+
+    /* ----- VIEW ----- */
+    /// Set the distance, which is drawn as one unit on the screen
+    pub fn set_map_size(&mut self, map_size: Scalar) { self.map_size = map_size; }
+    pub fn get_map_size(&self) -> Scalar { self.map_size }
+
+    /// The speed the camera of the viewer starts with
+    pub fn set_move_speed(&mut self, move_speed: f32) { self.move_speed = move_speed; }
+    pub fn get_move_speed(&self) -> f32 { self.move_speed }
+
+    /// Open the viewer, and step and draw the simulation until its window is closed.
+    /// The trajectories are flushed and a checkpoint is written (if they are turned on) at the end.
+    #[cfg(feature = "graphics")]
+    pub fn run(&mut self) {
+        match Viewer::new(self) {
+            Ok(mut viewer) => viewer.run(self),
+            Err(error) => eprintln!("Could not open the viewer: {error}")
         }
-        );
+        self.finish();
     }
 
     /* ----- CALCULATIONS ----- */
-    fn calculate(&mut self) {
+    /// Calculate the gravitational acceleration of each celestial.
+    /// Test particles feel the gravity of the massive bodies, but do not attract anything,
+    /// so the cost is `O(N_massive * N_total)`.
+    fn accelerations(&self) -> Vec<Vector> {
         let celestials: &Vec<Box<dyn Celestial + Sync>> = &self.celestials;
-        
-        // Calculate the forces applied to each object
-        let mut forces: Vec<Vector> = Vec::with_capacity(self.celestials.len());
+        let massive: Vec<usize> = (0..celestials.len())
+            .filter(|i| !celestials[*i].point_body().test_particle)
+            .collect();
+
+        // The pulls are added with compensated summation, so the many small terms are not lost
+        let acceleration = |a: usize| -> Vector {
+            let mut acceleration: VectorSummation = VectorSummation::new();
+            let ca: &PointBody = celestials[a].point_body();
+
+            for b in massive.iter().copied() {
+                if a != b {
+                    let cb: &PointBody = celestials[b].point_body();
+                    acceleration.add(ca.gravitational_acceleration(cb));
+                }
+            }
+            acceleration.total()
+        };
 
+        let mut accelerations: Vec<Vector> = Vec::with_capacity(celestials.len());
         if self.multi_processor {
-            (0..celestials.len()).into_par_iter().map(|a| {
-                let mut force: Vector = NULL_VECTOR;
-                let ca: &PointBody = celestials[a].point_body();
-
-                for b in 0..celestials.len() {
-                    if a != b {
-                        let cb: &PointBody = celestials[b].point_body();
-                        force += ca.gravitational_force(cb);
-                    }
-                }
-                force
-            }).collect_into_vec(&mut forces);
+            (0..celestials.len()).into_par_iter().map(acceleration).collect_into_vec(&mut accelerations);
         } else {
-            for a in 0..celestials.len() {
-                let mut force: Vector = NULL_VECTOR;
-                let ca: &PointBody = celestials[a].point_body();
-
-                for b in 0..celestials.len() {
-                    if a != b {
-                        let cb: &PointBody = celestials[b].point_body();
-                        force += ca.gravitational_force(cb);
-                    }
+            accelerations.extend((0..celestials.len()).map(acceleration));
+        }
+
+        // Add the pull of the static potentials
+        for potential in self.potentials.iter() {
+            let anchor: Option<usize> = celestials.iter().position(|celestial| celestial.get_name() == potential.anchor);
+            let center: Point = match anchor {
+                Some(anchor) => celestials[anchor].point_body().coordinates,
+                None => continue
+            };
+            for (i, celestial) in celestials.iter().enumerate() {
+                if Some(i) == anchor { continue; }
+                accelerations[i] += potential.acceleration(&celestial.point_body().coordinates, &center);
+            }
+        }
+
+        accelerations
+    }
+
+    fn calculate(&mut self) {
+        let start: Scalar = self.time - self.dt;
+        let before: Option<EventState> = if self.events.is_empty() { None }
+            else { Some(EventState::capture(start, &self.celestials)) };
+
+        // Move the object based on the acceleration of gravity and its initial velocity
+        match self.integrator {
+            Integrator::Euler => {
+                let dt: Time = Time::new(self.dt);
+                let accelerations: Vec<Vector> = self.accelerations();
+                for (i, object) in self.celestials.iter_mut().enumerate() {
+                    let body: &mut PointBody = object.point_body_mut();
+                    body.advance(dt);
+                    body.accelerate(accelerations[i], dt);
+                }
+                self.apply_rails(self.time);
+            },
+            Integrator::Leapfrog => {
+                // The accelerations only depend on the positions, so the ones from the end of the last step are still valid
+                let accelerations: Vec<Vector> = match self.cached_accelerations.take() {
+                    Some(accelerations) if accelerations.len() == self.celestials.len() => accelerations,
+                    _ => self.accelerations()
+                };
+                let dt: Time = Time::new(self.dt);
+                let half: Time = dt / scalar!(2);
+
+                // Kick, drift
+                for (i, object) in self.celestials.iter_mut().enumerate() {
+                    let body: &mut PointBody = object.point_body_mut();
+                    body.accelerate(accelerations[i], half);
+                    body.advance(dt);
                 }
 
-                forces.push(force);
+                // Kick, with the bodies on rails at their places at the end of the step
+                self.apply_rails(self.time);
+                let accelerations: Vec<Vector> = self.accelerations();
+                for (i, object) in self.celestials.iter_mut().enumerate() {
+                    object.point_body_mut().accelerate(accelerations[i], half);
+                }
+                self.apply_rails(self.time);
+                self.cached_accelerations = Some(accelerations);
             }
         }
 
+        // Apply the non-gravitational changes (engine burns, ...)
+        for i in 0..self.celestials.len() {
+            let reference: Option<PointBody> = self.celestials[i].get_reference()
+                .and_then(|name| self.get_celestial_from_name(&name))
+                .map(|celestial| celestial.point_body().clone());
+            self.celestials[i].update(start, self.dt, reference.as_ref());
+        }
 
-        // Move the object based on the force applied to it and its initial velocity
-        for (i, object) in self.celestials.iter_mut().enumerate() {
-            let body: &mut PointBody = object.point_body_mut();
-            body.advance(self.dt);
-            body.velocity += body.acceleration(forces[i]) * self.dt;
+        // Look for the events that happened during the step
+        if let Some(before) = before {
+            let after: EventState = EventState::capture(self.time, &self.celestials);
+            self.events.detect(&before, &after);
         }
     }
 }
@@ -330,27 +607,24 @@ impl Default for AstronomicalSimulation {
         Self {
             dt: scalar!(10),
             time: scalar!(0),
+            epoch: None,
             celestials: Vec::new(),
             name: String::from("Astronomical Simulation"),
             time_stopped: false,
             move_speed: 0.2,
-            window: Window::new(
-                600,
-                600,
-                [0.0, 0.0, 0.0, 1.0],
-                "Astronomical Simulation",
-                45,
-                [0.0, 0.0, -5.0],
-                Some(BACKGROUND_IMAGE)
-            ).unwrap(),
-            textures: HashMap::new(),
-            drag: [0.0; 2],
-            mouse_pos: [0.0; 2],
-            button_held_down: false,
-            rotation_sensitivity: 0.05,
-            font: load_font!("fonts\\arial.ttf"),
-            calculation_amount: 0,
-            multi_processor: false
+            multi_processor: false,
+            integrator: Integrator::default(),
+            cached_accelerations: None,
+            events: EventDetector::default(),
+            potentials: Vec::new(),
+            map_size: AU.value,
+            save_path: String::from("simulations/save.psa"),
+            steps: 0,
+            checkpoint_interval: 0,
+            checkpoint_path: String::from("simulations/checkpoint.psc"),
+            trajectories: Vec::new(),
+            ephemerides: Vec::new(),
+            rails: None
         }
     }
 }
@@ -361,8 +635,8 @@ macro_rules! astronomical_simulation {
     (
         $celestials: expr
     ) => {
-        AstronomicalSimulation::new(
-            scalar!(1),
+        $crate::astronomy::AstronomicalSimulation::new(
+            $crate::scalar!(1),
             $celestials,
             String::from("Simulation"),
             0.02,
@@ -373,8 +647,8 @@ macro_rules! astronomical_simulation {
         $dt: expr,
         $celestials: expr
     ) => {
-        AstronomicalSimulation::new(
-            scalar!($dt),
+        $crate::astronomy::AstronomicalSimulation::new(
+            $crate::scalar!($dt),
             $celestials,
             String::from("Simulation"),
             0.02,
@@ -386,8 +660,8 @@ macro_rules! astronomical_simulation {
         $celestials: expr,
         $name: expr
     ) => {
-        AstronomicalSimulation::new(
-            scalar!($dt),
+        $crate::astronomy::AstronomicalSimulation::new(
+            $crate::scalar!($dt),
             $celestials,
             String::from($name),
             0.02,
@@ -400,8 +674,8 @@ macro_rules! astronomical_simulation {
         $name: expr,
         $move_speed: expr
     ) => {
-        AstronomicalSimulation::new(
-            scalar!($dt),
+        $crate::astronomy::AstronomicalSimulation::new(
+            $crate::scalar!($dt),
             $celestials,
             String::from($name),
             f32::from($move_speed),
@@ -415,12 +689,78 @@ macro_rules! astronomical_simulation {
         $move_speed: expr,
         $multi_processor: expr
     ) => {
-        AstronomicalSimulation::new(
-            scalar!($dt),
+        $crate::astronomy::AstronomicalSimulation::new(
+            $crate::scalar!($dt),
             $celestials,
             String::from($name),
             f32::from($move_speed),
             $multi_processor
         )
     };
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn solar_system() -> AstronomicalSimulation {
+        astronomical_simulation!(3600, vec![
+            Box::new(star!("Sun", 0, 0, 2e30, 7e8)),
+            Box::new(planet!("Earth", (0, 0, 29_780), (1.496e11, 0, 0), 5.97e24, 6.4e6)),
+            Box::new(planet!("Mars", (1000, 0, -24_000), (-2.28e11, 0, 1e10), 6.4e23, 3.4e6))
+        ])
+    }
+
+    /// Asserts that both simulations are in exactly the same state
+    fn assert_same_state(restarted: &AstronomicalSimulation, straight: &AstronomicalSimulation) {
+        assert_eq!(restarted.get_time(), straight.get_time());
+        assert_eq!(restarted.get_steps(), straight.get_steps());
+        assert_eq!(restarted.get_celestials().len(), straight.get_celestials().len());
+        for (restarted, straight) in restarted.get_celestials().iter().zip(straight.get_celestials()) {
+            let name: String = straight.get_name();
+            assert_eq!(restarted.get_name(), name);
+            let (restarted, straight): (&PointBody, &PointBody) = (restarted.point_body(), straight.point_body());
+            assert_eq!(restarted.coordinates, straight.coordinates, "{name}");
+            assert_eq!(restarted.velocity, straight.velocity, "{name}");
+            assert_eq!(restarted.mass, straight.mass, "{name}");
+            assert_eq!(restarted.position_compensation, straight.position_compensation, "{name}");
+            assert_eq!(restarted.velocity_compensation, straight.velocity_compensation, "{name}");
+        }
+    }
+
+    #[test]
+    fn checkpoints_restart_bit_identically() {
+        for integrator in [Integrator::Euler, Integrator::Leapfrog] {
+            let mut straight: AstronomicalSimulation = solar_system();
+            straight.set_integrator(integrator);
+            straight.run_for(500);
+
+            let mut first: AstronomicalSimulation = solar_system();
+            first.set_integrator(integrator);
+            first.run_for(200);
+            let path: std::path::PathBuf = std::env::temp_dir().join(format!("restart-{}-{integrator:?}.psc", std::process::id()));
+            first.write_checkpoint(&path).unwrap();
+            let mut restarted: AstronomicalSimulation = AstronomicalSimulation::read_checkpoint(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(restarted.get_integrator(), integrator);
+            restarted.run_for(300);
+
+            assert_same_state(&restarted, &straight);
+        }
+    }
+
+    #[test]
+    fn leapfrog_retraces_its_steps() {
+        let mut simulation: AstronomicalSimulation = solar_system();
+        simulation.set_integrator(Integrator::Leapfrog);
+        let report: ReversibilityReport = simulation.test_reversibility(24 * 365);
+
+        // Only the rounding errors remain: the last place of a coordinate of 10^11 m is 3 * 10^-5 m
+        assert!(report.position_error < scalar!(1e-4), "{report}");
+        assert!(report.velocity_error < scalar!(1e-11), "{report}");
+
+        let mut euler: AstronomicalSimulation = solar_system();
+        assert_eq!(euler.get_integrator(), Integrator::Euler);
+        assert!(euler.test_reversibility(24 * 365).position_error > scalar!(1e6));
+    }
+}
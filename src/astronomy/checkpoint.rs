@@ -0,0 +1,420 @@
+use crate::*;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+
+/// The first bytes of every checkpoint file
+const MAGIC: [u8; 8] = *b"PSIMCKPT";
+
+/// The version of the checkpoint format, it is increased on every change.
+/// Version 2 added the bodies on rails and version 3 the epoch, the files of the older versions can still be read.
+pub const CHECKPOINT_VERSION: u16 = 3;
+
+/// The way the scalars are stored: `0` - one `f64`, `1` - two `f64`s (double-double)
+#[cfg(not(feature = "double-double"))]
+const SCALAR_FORMAT: u8 = 0;
+#[cfg(feature = "double-double")]
+const SCALAR_FORMAT: u8 = 1;
+
+
+/// # Checkpoint Error
+/// The reasons a checkpoint can not be written or read
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(std::io::Error),
+    NotACheckpoint,
+    UnsupportedVersion(u16),
+    ScalarFormat(u8),
+    Corrupt(String),
+    Unsupported(String),
+    Ephemeris(SpkError)
+}
+
+
+impl CheckpointError {
+    fn corrupt(message: impl ToString) -> Self { Self::Corrupt(message.to_string()) }
+}
+
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::NotACheckpoint => write!(f, "not a checkpoint file"),
+            Self::UnsupportedVersion(version) => write!(
+                f, "unsupported checkpoint version {version} (this build reads versions up to {CHECKPOINT_VERSION})"
+            ),
+            Self::ScalarFormat(format) => write!(
+                f,
+                "the checkpoint was written with {} scalars, but this build uses {} scalars",
+                if *format == 1 { "double-double" } else { "f64" },
+                if SCALAR_FORMAT == 1 { "double-double" } else { "f64" }
+            ),
+            Self::Corrupt(message) => write!(f, "corrupt checkpoint: {message}"),
+            Self::Unsupported(name) => write!(f, "the celestial '{name}' can not be written to a checkpoint"),
+            Self::Ephemeris(error) => write!(f, "the ephemeris of the bodies on rails can not be read: {error}")
+        }
+    }
+}
+
+
+impl std::error::Error for CheckpointError {}
+
+
+impl From<std::io::Error> for CheckpointError {
+    fn from(error: std::io::Error) -> Self { Self::Io(error) }
+}
+
+
+/// # Concrete Celestial
+/// The concrete type of a celestial, which decides how it is written to a checkpoint
+pub enum ConcreteCelestial<'a> {
+    Star(&'a Star),
+    Planet(&'a Planet),
+    Spacecraft(&'a Spacecraft)
+}
+
+
+/// # Checkpoint Writer
+/// Encodes values into the little-endian binary format of the checkpoints.
+/// The file starts with a header (magic bytes, format version, scalar format),
+/// and ends with a 64-bit FNV-1a checksum of everything before it.
+pub struct CheckpointWriter {
+    bytes: Vec<u8>
+}
+
+
+impl CheckpointWriter {
+    pub fn new() -> Self {
+        let mut writer: Self = Self { bytes: Vec::new() };
+        writer.bytes.extend_from_slice(&MAGIC);
+        writer.write_u16(CHECKPOINT_VERSION);
+        writer.write_u8(SCALAR_FORMAT);
+        writer
+    }
+
+    pub fn write_u8(&mut self, value: u8) { self.bytes.push(value); }
+    pub fn write_u16(&mut self, value: u16) { self.bytes.extend_from_slice(&value.to_le_bytes()); }
+    pub fn write_u32(&mut self, value: u32) { self.bytes.extend_from_slice(&value.to_le_bytes()); }
+    pub fn write_u64(&mut self, value: u64) { self.bytes.extend_from_slice(&value.to_le_bytes()); }
+    pub fn write_i32(&mut self, value: i32) { self.bytes.extend_from_slice(&value.to_le_bytes()); }
+    pub fn write_f64(&mut self, value: f64) { self.bytes.extend_from_slice(&value.to_le_bytes()); }
+    pub fn write_bool(&mut self, value: bool) { self.write_u8(value as u8); }
+
+    /// Write the length of a sequence
+    pub fn write_len(&mut self, len: usize) { self.write_u32(len as u32); }
+
+    pub fn write_string(&mut self, value: &str) {
+        self.write_len(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    /// Write a scalar with all of its bits
+    #[cfg(not(feature = "double-double"))]
+    pub fn write_scalar(&mut self, value: Scalar) { self.write_f64(value.value); }
+    #[cfg(feature = "double-double")]
+    pub fn write_scalar(&mut self, value: Scalar) {
+        self.write_f64(value.value.hi);
+        self.write_f64(value.value.lo);
+    }
+
+    pub fn write_point(&mut self, point: &Point) {
+        self.write_scalar(point.x);
+        self.write_scalar(point.y);
+        self.write_scalar(point.z);
+    }
+
+    pub fn write_vector(&mut self, vector: &Vector) { self.write_point(&vector.point); }
+
+    /// Write every field of a point body, including the compensations of the summations
+    pub fn write_point_body(&mut self, body: &PointBody) {
+        self.write_scalar(body.mass.value);
+        self.write_vector(&body.velocity);
+        self.write_point(&body.coordinates);
+        self.write_scalar(body.charge);
+        self.write_bool(body.test_particle);
+        self.write_vector(&body.position_compensation);
+        self.write_vector(&body.velocity_compensation);
+    }
+
+    /// Write a celestial with its concrete type and parameters
+    pub fn write_celestial(&mut self, celestial: &(dyn Celestial + Sync)) -> Result<(), CheckpointError> {
+        let concrete: ConcreteCelestial = celestial.concrete()
+            .ok_or_else(|| CheckpointError::Unsupported(celestial.get_name()))?;
+
+        match concrete {
+            ConcreteCelestial::Star(star) => {
+                self.write_u8(0);
+                self.write_string(&star.name);
+                self.write_point_body(star.point_body());
+                self.write_scalar(star.radius.value);
+                self.write_scalar(star.radiation.wavelength);
+                self.write_scalar(star.radiation.frequency);
+                self.write_scalar(star.radiation.temperature);
+                self.write_bool(star.tracer);
+            },
+            ConcreteCelestial::Planet(planet) => {
+                self.write_u8(1);
+                self.write_string(&planet.name);
+                self.write_point_body(planet.point_body());
+                self.write_scalar(planet.radius.value);
+                self.write_u8(match planet.planet_type {
+                    PlanetType::Terrestrial => 0,
+                    PlanetType::GasGiant => 1,
+                    PlanetType::Satellite => 2
+                });
+            },
+            ConcreteCelestial::Spacecraft(spacecraft) => {
+                self.write_u8(2);
+                self.write_string(&spacecraft.name);
+                self.write_point_body(spacecraft.point_body());
+                self.write_scalar(spacecraft.radius.value);
+                self.write_scalar(spacecraft.dry_mass.value);
+                self.write_scalar(spacecraft.specific_impulse.value);
+                self.write_scalar(spacecraft.max_thrust.value);
+                self.write_bool(spacecraft.reference.is_some());
+                if let Some(reference) = &spacecraft.reference {
+                    self.write_string(reference);
+                }
+                self.write_len(spacecraft.burns.len());
+                for burn in spacecraft.burns.iter() {
+                    self.write_scalar(burn.start);
+                    self.write_scalar(burn.duration);
+                    match burn.direction {
+                        BurnDirection::Prograde => self.write_u8(0),
+                        BurnDirection::Retrograde => self.write_u8(1),
+                        BurnDirection::Normal => self.write_u8(2),
+                        BurnDirection::AntiNormal => self.write_u8(3),
+                        BurnDirection::RadialOut => self.write_u8(4),
+                        BurnDirection::RadialIn => self.write_u8(5),
+                        BurnDirection::Fixed(direction) => {
+                            self.write_u8(6);
+                            self.write_vector(&direction);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the checksum and write the checkpoint to a file.
+    /// The data is written to a temporary file first, so a crash while writing does not destroy the previous checkpoint.
+    pub fn save(mut self, path: impl AsRef<Path>) -> Result<(), CheckpointError> {
+        let checksum: u64 = fnv1a(&self.bytes);
+        self.write_u64(checksum);
+
+        let path: &Path = path.as_ref();
+        let mut temporary: std::ffi::OsString = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, &self.bytes)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+
+impl Default for CheckpointWriter {
+    fn default() -> Self { Self::new() }
+}
+
+
+/// # Checkpoint Reader
+/// Decodes the values written by a `CheckpointWriter`
+pub struct CheckpointReader {
+    pub version: u16,
+    bytes: Vec<u8>,
+    position: usize
+}
+
+
+impl CheckpointReader {
+    /// Read a checkpoint file, and check its header and checksum
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CheckpointError> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(mut bytes: Vec<u8>) -> Result<Self, CheckpointError> {
+        if bytes.len() < MAGIC.len() || bytes[..MAGIC.len()] != MAGIC {
+            return Err(CheckpointError::NotACheckpoint);
+        }
+        if bytes.len() < MAGIC.len() + 3 + 8 {
+            return Err(CheckpointError::corrupt("the file is truncated"));
+        }
+
+        let mut reader: Self = Self { version: 0, bytes: Vec::new(), position: MAGIC.len() };
+        let body: usize = bytes.len() - 8;
+        let checksum: u64 = u64::from_le_bytes(bytes[body..].try_into().unwrap());
+
+        // Check the version before the checksum, so the files of other versions get a clear error
+        let version: u16 = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+        if version == 0 || version > CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+        if fnv1a(&bytes[..body]) != checksum {
+            return Err(CheckpointError::corrupt("checksum mismatch"));
+        }
+
+        bytes.truncate(body);
+        reader.version = version;
+        reader.bytes = bytes;
+        reader.position += 2;
+
+        let format: u8 = reader.read_u8()?;
+        if format != SCALAR_FORMAT {
+            return Err(CheckpointError::ScalarFormat(format));
+        }
+        Ok(reader)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], CheckpointError> {
+        let end: usize = self.position + N;
+        if end > self.bytes.len() {
+            return Err(CheckpointError::corrupt("unexpected end of data"));
+        }
+        let bytes: [u8; N] = self.bytes[self.position..end].try_into().unwrap();
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, CheckpointError> { Ok(self.take::<1>()?[0]) }
+    pub fn read_u16(&mut self) -> Result<u16, CheckpointError> { Ok(u16::from_le_bytes(self.take()?)) }
+    pub fn read_u32(&mut self) -> Result<u32, CheckpointError> { Ok(u32::from_le_bytes(self.take()?)) }
+    pub fn read_u64(&mut self) -> Result<u64, CheckpointError> { Ok(u64::from_le_bytes(self.take()?)) }
+    pub fn read_i32(&mut self) -> Result<i32, CheckpointError> { Ok(i32::from_le_bytes(self.take()?)) }
+    pub fn read_f64(&mut self) -> Result<f64, CheckpointError> { Ok(f64::from_le_bytes(self.take()?)) }
+
+    pub fn read_bool(&mut self) -> Result<bool, CheckpointError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(CheckpointError::corrupt(format!("invalid boolean {value}")))
+        }
+    }
+
+    /// Read the length of a sequence
+    pub fn read_len(&mut self) -> Result<usize, CheckpointError> {
+        let len: usize = self.read_u32()? as usize;
+        if len > self.bytes.len() - self.position {
+            return Err(CheckpointError::corrupt("sequence longer than the file"));
+        }
+        Ok(len)
+    }
+
+    pub fn read_string(&mut self) -> Result<String, CheckpointError> {
+        let len: usize = self.read_len()?;
+        let bytes: Vec<u8> = self.bytes[self.position..self.position + len].to_vec();
+        self.position += len;
+        String::from_utf8(bytes).map_err(|_| CheckpointError::corrupt("invalid UTF-8 in a name"))
+    }
+
+    #[cfg(not(feature = "double-double"))]
+    pub fn read_scalar(&mut self) -> Result<Scalar, CheckpointError> {
+        Ok(Scalar { value: self.read_f64()? })
+    }
+    #[cfg(feature = "double-double")]
+    pub fn read_scalar(&mut self) -> Result<Scalar, CheckpointError> {
+        let hi: f64 = self.read_f64()?;
+        let lo: f64 = self.read_f64()?;
+        Ok(Scalar { value: DoubleDouble { hi, lo } })
+    }
+
+    pub fn read_point(&mut self) -> Result<Point, CheckpointError> {
+        Ok(Point::new(self.read_scalar()?, self.read_scalar()?, self.read_scalar()?))
+    }
+
+    pub fn read_vector(&mut self) -> Result<Vector, CheckpointError> {
+        Ok(Vector::from_point(self.read_point()?))
+    }
+
+    pub fn read_point_body(&mut self) -> Result<PointBody, CheckpointError> {
+        let mass: Mass = Mass::new(self.read_scalar()?);
+        let velocity: Vector = self.read_vector()?;
+        let coordinates: Point = self.read_point()?;
+        let charge: Scalar = self.read_scalar()?;
+        let mut body: PointBody = PointBody::new(mass, velocity, coordinates, charge);
+        body.test_particle = self.read_bool()?;
+        body.position_compensation = self.read_vector()?;
+        body.velocity_compensation = self.read_vector()?;
+        Ok(body)
+    }
+
+    /// Read a celestial written by `CheckpointWriter::write_celestial`
+    pub fn read_celestial(&mut self) -> Result<Box<dyn Celestial + Sync>, CheckpointError> {
+        let tag: u8 = self.read_u8()?;
+        let name: String = self.read_string()?;
+        let body: PointBody = self.read_point_body()?;
+
+        let mut celestial: Box<dyn Celestial + Sync> = match tag {
+            0 => {
+                let mut star: Star = Star::tracer(name, body.velocity, body.coordinates, body.get_mass());
+                star.radius = Length::new(self.read_scalar()?);
+                star.radiation.wavelength = self.read_scalar()?;
+                star.radiation.frequency = self.read_scalar()?;
+                star.radiation.temperature = self.read_scalar()?;
+                star.tracer = self.read_bool()?;
+                Box::new(star)
+            },
+            1 => {
+                let radius: Length = Length::new(self.read_scalar()?);
+                let planet_type: PlanetType = match self.read_u8()? {
+                    0 => PlanetType::Terrestrial,
+                    1 => PlanetType::GasGiant,
+                    2 => PlanetType::Satellite,
+                    value => return Err(CheckpointError::corrupt(format!("invalid planet type {value}")))
+                };
+                Box::new(Planet::new(
+                    name, body.velocity, body.coordinates, body.get_mass(), radius, planet_type
+                ))
+            },
+            2 => {
+                let radius: Length = Length::new(self.read_scalar()?);
+                let dry_mass: Mass = Mass::new(self.read_scalar()?);
+                let specific_impulse: Time = Time::new(self.read_scalar()?);
+                let max_thrust: Force = Force::new(self.read_scalar()?);
+                let mut spacecraft: Spacecraft = Spacecraft::new(
+                    name, body.velocity, body.coordinates, dry_mass, body.mass - dry_mass, specific_impulse, max_thrust
+                );
+                spacecraft.radius = radius;
+                if self.read_bool()? {
+                    spacecraft.reference = Some(self.read_string()?);
+                }
+                for _ in 0..self.read_len()? {
+                    let start: Scalar = self.read_scalar()?;
+                    let duration: Scalar = self.read_scalar()?;
+                    let direction: BurnDirection = match self.read_u8()? {
+                        0 => BurnDirection::Prograde,
+                        1 => BurnDirection::Retrograde,
+                        2 => BurnDirection::Normal,
+                        3 => BurnDirection::AntiNormal,
+                        4 => BurnDirection::RadialOut,
+                        5 => BurnDirection::RadialIn,
+                        6 => BurnDirection::Fixed(self.read_vector()?),
+                        value => return Err(CheckpointError::corrupt(format!("invalid burn direction {value}")))
+                    };
+                    spacecraft.add_burn(Burn::new(start, duration, direction));
+                }
+                Box::new(spacecraft)
+            },
+            value => return Err(CheckpointError::corrupt(format!("unknown celestial type {value}")))
+        };
+
+        // The constructors may round or recompute the fields, so the exact body is put back
+        *celestial.point_body_mut() = body;
+        Ok(celestial)
+    }
+
+    /// Check that every byte was read
+    pub fn finish(self) -> Result<(), CheckpointError> {
+        if self.position != self.bytes.len() {
+            return Err(CheckpointError::corrupt("unexpected data at the end"));
+        }
+        Ok(())
+    }
+}
+
+
+/// The 64-bit FNV-1a hash of the bytes
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash: u64, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
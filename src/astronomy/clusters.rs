@@ -0,0 +1,449 @@
+use crate::*;
+use std::f64::consts::PI as PI_F64;
+
+
+/// The radius (in scale radii) beyond which sampled positions are rejected
+const MAX_RADIUS: f64 = 50.0;
+/// The number of points used to find the maximum of a velocity distribution
+const VELOCITY_GRID: usize = 200;
+/// The radial step of the integration of King models (in King radii)
+const KING_STEP: f64 = 1e-3;
+
+
+/// A sampled position and velocity in model units
+type PhaseSpace = ([f64; 3], [f64; 3]);
+
+
+/// # Cluster Error
+/// An invalid setting of a cluster
+/// - `MassRange` - The mass function needs `0 < min < max` (in solar masses)
+/// - `CentralPotential` - The King model needs a positive, finite central potential `W0`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClusterError {
+    MassRange { min: f64, max: f64 },
+    CentralPotential(f64)
+}
+
+
+impl std::fmt::Display for ClusterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MassRange { min, max } => write!(f, "invalid mass range {min}-{max} (expected 0 < min < max)"),
+            Self::CentralPotential(w0) => write!(f, "invalid central potential W0 = {w0} (expected W0 > 0)")
+        }
+    }
+}
+
+
+impl std::error::Error for ClusterError {}
+
+
+/// # Initial Mass Function
+/// The distribution of the masses of the generated stars (masses in solar masses)
+/// - `Salpeter` - `dN/dm ∝ m^-2.35`
+/// - `Kroupa` - `dN/dm ∝ m^-α`, with `α = 0.3` below `0.08`, `1.3` below `0.5` and `2.3` above
+#[derive(Debug, Copy, Clone)]
+pub enum InitialMassFunction {
+    Salpeter { min: f64, max: f64 },
+    Kroupa { min: f64, max: f64 }
+}
+
+
+impl InitialMassFunction {
+    /// Check that the mass range is `0 < min < max`
+    pub fn validate(&self) -> Result<(), ClusterError> {
+        let (Self::Salpeter { min, max } | Self::Kroupa { min, max }) = *self;
+        if min > 0.0 && min < max { Ok(()) } else { Err(ClusterError::MassRange { min, max }) }
+    }
+
+    /// Sample a mass (in kg)
+    pub fn sample(&self, random: &mut Random) -> Result<Mass, ClusterError> {
+        self.validate()?;
+        let mass: f64 = match *self {
+            Self::Salpeter { min, max } => sample_power_law(random, min, max, -2.35),
+            Self::Kroupa { min, max } => {
+                // The segments of the broken power law, continuous at the breaks
+                let breaks: [(f64, f64, f64); 3] = [(0.0, 0.08, 0.3), (0.08, 0.5, 1.3), (0.5, f64::INFINITY, 2.3)];
+                let mut segments: Vec<(f64, f64, f64, f64)> = Vec::new();  // from, to, alpha, weight
+                let mut normalization: f64 = 1.0;
+                for (i, (lower, upper, alpha)) in breaks.iter().enumerate() {
+                    if i > 0 {
+                        // Keep the distribution continuous at the lower break
+                        normalization *= lower.powf(alpha - breaks[i - 1].2);
+                    }
+                    let from: f64 = min.max(*lower);
+                    let to: f64 = max.min(*upper);
+                    if to <= from { continue; }
+                    let k: f64 = 1.0 - alpha;
+                    let weight: f64 = normalization * (to.powf(k) - from.powf(k)) / k;
+                    segments.push((from, to, *alpha, weight));
+                }
+
+                let total: f64 = segments.iter().map(|segment| segment.3).sum();
+                let mut choice: f64 = random.uniform() * total;
+                let mut segment: (f64, f64, f64, f64) = segments[segments.len() - 1];
+                for s in segments.iter() {
+                    if choice < s.3 { segment = *s; break; }
+                    choice -= s.3;
+                }
+                sample_power_law(random, segment.0, segment.1, -segment.2)
+            }
+        };
+        Ok(SOLAR_MASS * scalar!(mass))
+    }
+}
+
+
+/// # Cluster Profile
+/// The distribution function the cluster is sampled from
+/// - `Plummer` - The Plummer sphere, `scale_radius` is the Plummer radius
+/// - `King` - The King model with the dimensionless central potential `W0`,
+///   `scale_radius` is the King (core) radius
+/// - `Hernquist` - The Hernquist model, `scale_radius` is its scale length
+#[derive(Debug, Copy, Clone)]
+pub enum ClusterProfile {
+    Plummer,
+    King { central_potential: f64 },
+    Hernquist
+}
+
+
+/// # Cluster Generator
+/// Generates a star cluster in virial equilibrium.
+/// ## Attributes
+/// `name: String` - The prefix of the names of the stars\
+/// `count: usize` - The number of stars\
+/// `profile: ClusterProfile` - The distribution function\
+/// `scale_radius: Length` - The scale radius of the profile\
+/// `mass_function: InitialMassFunction` - The distribution of the masses\
+/// `coordinates: Point`, `velocity: Vector` - The position and velocity of the barycenter\
+/// `seed: u64` - The seed of the random number generator
+/// ## Method
+/// Positions and velocities are sampled from the distribution function in model units,
+/// then scaled to the physical size and mass of the cluster.
+/// The velocities are finally rescaled, so that `2K + W = 0` holds exactly for the sampled masses.
+/// The radius of each star comes from the main sequence mass-radius relation.
+#[derive(Debug, Clone)]
+pub struct ClusterGenerator {
+    pub name: String,
+    pub count: usize,
+    pub profile: ClusterProfile,
+    pub scale_radius: Length,
+    pub mass_function: InitialMassFunction,
+    pub coordinates: Point,
+    pub velocity: Vector,
+    pub seed: u64
+}
+
+
+impl ClusterGenerator {
+    pub fn new(count: usize, profile: ClusterProfile, scale_radius: Length, seed: u64) -> Self {
+        Self {
+            name: String::from("Star"),
+            count,
+            profile,
+            scale_radius,
+            mass_function: InitialMassFunction::Kroupa { min: 0.08, max: 100.0 },
+            coordinates: ORIGO,
+            velocity: NULL_VECTOR,
+            seed
+        }
+    }
+
+    /// Generate the stars of the cluster
+    pub fn generate(&self) -> Result<Vec<Star>, ClusterError> {
+        self.mass_function.validate()?;
+        let mut random: Random = Random::new(self.seed);
+
+        // Sample the masses
+        let masses: Vec<Mass> = (0..self.count)
+            .map(|_| self.mass_function.sample(&mut random))
+            .collect::<Result<_, _>>()?;
+        let total_mass: Mass = masses.iter().fold(Mass::new(ZERO), |sum, mass| sum + *mass);
+
+        // Sample the phase space in model units
+        let (model_mass, phase_space): (f64, Vec<PhaseSpace>) = match self.profile {
+            ClusterProfile::Plummer => (1.0, (0..self.count).map(|_| sample_plummer(&mut random)).collect()),
+            ClusterProfile::Hernquist => (1.0, (0..self.count).map(|_| sample_hernquist(&mut random)).collect()),
+            ClusterProfile::King { central_potential } => {
+                let model: KingModel = KingModel::new(central_potential)?;
+                (model.total_mass(), (0..self.count).map(|_| model.sample(&mut random)).collect())
+            }
+        };
+
+        // Scale to physical units (G = 1, M = model_mass, r = 1 in model units)
+        let velocity_unit: Scalar = (constants::G * total_mass.value / (self.scale_radius * scalar!(model_mass)).value).sqrt();
+        let mut positions: Vec<Vector> = Vec::with_capacity(self.count);
+        let mut velocities: Vec<Vector> = Vec::with_capacity(self.count);
+        for (position, velocity) in phase_space.iter() {
+            positions.push(vector!(position[0], position[1], position[2]) * self.scale_radius.value);
+            velocities.push(vector!(velocity[0], velocity[1], velocity[2]) * velocity_unit);
+        }
+
+        // Move into the barycentric frame
+        let mut center: Vector = NULL_VECTOR;
+        let mut momentum: Vector = NULL_VECTOR;
+        for i in 0..self.count {
+            center += positions[i] * masses[i].value;
+            momentum += velocities[i] * masses[i].value;
+        }
+        center /= total_mass.value;
+        momentum /= total_mass.value;
+        for i in 0..self.count {
+            positions[i] -= center;
+            velocities[i] -= momentum;
+        }
+
+        // Virialise
+        let mut kinetic: Scalar = ZERO;
+        let mut potential: Scalar = ZERO;
+        for i in 0..self.count {
+            kinetic += scalar!(0.5) * masses[i].value * velocities[i].dot(&velocities[i]);
+            for j in (i + 1)..self.count {
+                potential -= constants::G * masses[i].value * masses[j].value / (positions[i] - positions[j]).magnitude();
+            }
+        }
+        let factor: Scalar = if kinetic > ZERO { (-potential / (scalar!(2) * kinetic)).sqrt() } else { scalar!(1) };
+
+        Ok((0..self.count).map(|i| {
+            Star::new(
+                format!("{} {}", self.name, i + 1),
+                self.velocity + velocities[i] * factor,
+                self.coordinates + positions[i].to_point(),
+                masses[i],
+                main_sequence_radius(masses[i])
+            )
+        }).collect())
+    }
+
+    /// Add the stars of the cluster to the simulation, returning the number of added stars
+    pub fn populate(&self, simulation: &mut AstronomicalSimulation) -> Result<usize, ClusterError> {
+        let stars: Vec<Star> = self.generate()?;
+        let count: usize = stars.len();
+        for star in stars {
+            simulation.add_celestial(star);
+        }
+        Ok(count)
+    }
+}
+
+
+/// Estimate the radius of a main sequence star from its mass:
+/// `R ∝ M^0.8` below one solar mass and `R ∝ M^0.57` above it
+pub fn main_sequence_radius(mass: Mass) -> Length {
+    let mass: Scalar = mass / SOLAR_MASS;
+    let exponent: f64 = if mass.to_f64() < 1.0 { 0.8 } else { 0.57 };
+    SOLAR_RADIUS * mass.pow(scalar!(exponent))
+}
+
+
+/// A random unit vector, uniformly distributed on the sphere
+pub(crate) fn random_direction(random: &mut Random) -> [f64; 3] {
+    let z: f64 = random.range(-1.0, 1.0);
+    let phi: f64 = random.range(0.0, 2.0 * PI_F64);
+    let s: f64 = (1.0 - z * z).sqrt();
+    [s * phi.cos(), s * phi.sin(), z]
+}
+
+
+fn scale(direction: [f64; 3], length: f64) -> [f64; 3] {
+    [direction[0] * length, direction[1] * length, direction[2] * length]
+}
+
+
+/// Sample a speed between `0` and `max` from the distribution `weight(v)` by rejection
+fn sample_speed(random: &mut Random, max: f64, weight: impl Fn(f64) -> f64) -> f64 {
+    let peak: f64 = (0..=VELOCITY_GRID)
+        .map(|i| weight(max * i as f64 / VELOCITY_GRID as f64))
+        .fold(0.0, f64::max) * 1.1;
+    if peak <= 0.0 { return 0.0; }
+
+    loop {
+        let v: f64 = random.range(0.0, max);
+        if random.range(0.0, peak) < weight(v) { return v; }
+    }
+}
+
+
+/// Sample a star of a Plummer sphere (G = M = a = 1), following Aarseth, Hénon & Wielen (1974)
+fn sample_plummer(random: &mut Random) -> PhaseSpace {
+    let radius: f64 = loop {
+        let x: f64 = random.uniform();
+        if x <= 0.0 { continue; }
+        let r: f64 = 1.0 / (x.powf(-2.0 / 3.0) - 1.0).sqrt();
+        if r < MAX_RADIUS { break r; }
+    };
+
+    // q = v / v_escape follows g(q) = q^2 (1 - q^2)^3.5
+    let q: f64 = loop {
+        let q: f64 = random.uniform();
+        if random.range(0.0, 0.1) < q * q * (1.0 - q * q).powf(3.5) { break q; }
+    };
+    let speed: f64 = q * 2f64.sqrt() * (1.0 + radius * radius).powf(-0.25);
+
+    (scale(random_direction(random), radius), scale(random_direction(random), speed))
+}
+
+
+/// Sample a star of a Hernquist model (G = M = a = 1) from its isotropic distribution function
+pub(crate) fn sample_hernquist(random: &mut Random) -> PhaseSpace {
+    let radius: f64 = loop {
+        let x: f64 = random.uniform().sqrt();
+        let r: f64 = x / (1.0 - x);
+        if r < MAX_RADIUS { break r; }
+    };
+    let potential: f64 = -1.0 / (1.0 + radius);
+
+    // f(E) of Hernquist (1990), with q = sqrt(-E)
+    let distribution = |v: f64| -> f64 {
+        let energy: f64 = 0.5 * v * v + potential;
+        if energy >= 0.0 { return 0.0; }
+        let q: f64 = (-energy).sqrt().min(1.0 - 1e-12);
+        let q2: f64 = q * q;
+        let f: f64 = (1.0 - q2).powf(-2.5) * (
+            3.0 * q.asin() + q * (1.0 - q2).sqrt() * (1.0 - 2.0 * q2) * (8.0 * q2 * q2 - 8.0 * q2 - 3.0)
+        );
+        v * v * f
+    };
+    let speed: f64 = sample_speed(random, (-2.0 * potential).sqrt(), distribution);
+
+    (scale(random_direction(random), radius), scale(random_direction(random), speed))
+}
+
+
+/// # King Model
+/// The numerical solution of a King model in units of the King radius and the velocity dispersion
+struct KingModel {
+    radii: Vec<f64>,
+    potentials: Vec<f64>,
+    masses: Vec<f64>
+}
+
+
+impl KingModel {
+    /// Integrate Poisson's equation from the centre to the tidal radius
+    fn new(central_potential: f64) -> Result<Self, ClusterError> {
+        if !(central_potential > 0.0 && central_potential.is_finite()) {
+            return Err(ClusterError::CentralPotential(central_potential));
+        }
+        let central_density: f64 = Self::density(central_potential);
+        // W'' = -2W'/r - 9 ρ(W) / ρ0
+        let derivative = |r: f64, w: f64, dw: f64| -> (f64, f64) {
+            let source: f64 = 9.0 * Self::density(w) / central_density;
+            // At the centre 2W'/r -> 2W'', so W'' = -3 ρ / ρ0
+            if r > 0.0 { (dw, -2.0 * dw / r - source) } else { (dw, -source / 3.0) }
+        };
+
+        let mut radii: Vec<f64> = vec![0.0];
+        let mut potentials: Vec<f64> = vec![central_potential];
+        let mut masses: Vec<f64> = vec![0.0];
+        let (mut r, mut w, mut dw, mut mass): (f64, f64, f64, f64) = (0.0, central_potential, 0.0, 0.0);
+
+        while w > 0.0 {
+            let h: f64 = KING_STEP;
+            let k1 = derivative(r, w, dw);
+            let k2 = derivative(r + h / 2.0, w + h / 2.0 * k1.0, dw + h / 2.0 * k1.1);
+            let k3 = derivative(r + h / 2.0, w + h / 2.0 * k2.0, dw + h / 2.0 * k2.1);
+            let k4 = derivative(r + h, w + h * k3.0, dw + h * k3.1);
+            w += h / 6.0 * (k1.0 + 2.0 * k2.0 + 2.0 * k3.0 + k4.0);
+            dw += h / 6.0 * (k1.1 + 2.0 * k2.1 + 2.0 * k3.1 + k4.1);
+
+            let middle: f64 = r + h / 2.0;
+            mass += 4.0 * PI_F64 * middle * middle * Self::density(w.max(0.0)) / central_density * h;
+            r += h;
+
+            radii.push(r);
+            potentials.push(w.max(0.0));
+            masses.push(mass);
+        }
+
+        Ok(Self { radii, potentials, masses })
+    }
+
+    /// The density belonging to the dimensionless potential `W` (without the constant factor)
+    fn density(w: f64) -> f64 {
+        if w <= 0.0 { return 0.0; }
+        w.exp() * erf(w.sqrt()) - (4.0 * w / PI_F64).sqrt() * (1.0 + 2.0 * w / 3.0)
+    }
+
+    /// The total mass in units where `G = 1`, the King radius is `1` and the velocity dispersion is `1`
+    /// (the central density is `9 / 4π` in these units)
+    fn total_mass(&self) -> f64 {
+        self.masses[self.masses.len() - 1] * 9.0 / (4.0 * PI_F64)
+    }
+
+    fn sample(&self, random: &mut Random) -> PhaseSpace {
+        // Invert the cumulative mass profile
+        let target: f64 = random.uniform() * self.masses[self.masses.len() - 1];
+        let index: usize = self.masses.partition_point(|mass| *mass < target).clamp(1, self.masses.len() - 1);
+        let fraction: f64 = (target - self.masses[index - 1]) / (self.masses[index] - self.masses[index - 1]);
+        let radius: f64 = self.radii[index - 1] + fraction * (self.radii[index] - self.radii[index - 1]);
+        let w: f64 = self.potentials[index - 1] + fraction * (self.potentials[index] - self.potentials[index - 1]);
+
+        // f(E) ∝ exp(W - v^2 / 2) - 1
+        let speed: f64 = sample_speed(random, (2.0 * w).sqrt(), |v| {
+            v * v * ((w - 0.5 * v * v).exp() - 1.0).max(0.0)
+        });
+
+        (scale(random_direction(random), radius), scale(random_direction(random), speed))
+    }
+}
+
+
+/// The error function, using the Chebyshev approximation of the complementary error function
+/// from Numerical Recipes (fractional error below `1.2e-7`)
+fn erf(x: f64) -> f64 {
+    let z: f64 = x.abs();
+    let t: f64 = 1.0 / (1.0 + 0.5 * z);
+    let complement: f64 = t * (
+        -z * z - 1.26551223 + t * (1.00002368 + t * (0.37409196 + t * (0.09678418 + t * (-0.18628806
+        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))))
+    ).exp();
+    if x >= 0.0 { 1.0 - complement } else { complement - 1.0 }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn invalid_settings_are_errors() {
+        for central_potential in [0.0, -1.0, f64::NAN] {
+            let generator: ClusterGenerator = ClusterGenerator::new(
+                10, ClusterProfile::King { central_potential }, Length::from(1e15), 1
+            );
+            assert!(matches!(generator.generate(), Err(ClusterError::CentralPotential(_))));
+        }
+
+        for (min, max) in [(1.0, 1.0), (2.0, 1.0), (0.0, 1.0)] {
+            for mass_function in [InitialMassFunction::Kroupa { min, max }, InitialMassFunction::Salpeter { min, max }] {
+                let mut generator: ClusterGenerator = ClusterGenerator::new(10, ClusterProfile::Plummer, Length::from(1e15), 1);
+                generator.mass_function = mass_function;
+                assert_eq!(generator.generate().err(), Some(ClusterError::MassRange { min, max }));
+            }
+        }
+    }
+
+    #[test]
+    fn valid_settings_generate_every_star() {
+        let scale: f64 = 1e15;
+        for profile in [ClusterProfile::Plummer, ClusterProfile::Hernquist, ClusterProfile::King { central_potential: 5.0 }] {
+            let generator: ClusterGenerator = ClusterGenerator::new(20, profile, Length::from(scale), 1);
+            let stars: Vec<Box<dyn Celestial + Sync>> = generator.generate().unwrap().into_iter()
+                .map(|star| Box::new(star) as Box<dyn Celestial + Sync>)
+                .collect();
+            assert_eq!(stars.len(), 20);
+
+            // The cluster is in virial equilibrium, 2T / |W| = 1
+            let (kinetic, potential) = energy(&stars);
+            assert!(((kinetic * scalar!(2) / potential.abs()).to_f64() - 1.0).abs() < 1e-9, "{profile:?}");
+
+            // The barycenter is at rest at the origin
+            let center: PointBody = barycenter(&stars);
+            let speed: Scalar = (scalar!(2) * kinetic.value / center.mass.value).sqrt();
+            assert!(Vector::from_point(center.coordinates).magnitude() < scalar!(scale * 1e-9), "{profile:?}");
+            assert!(center.velocity.magnitude() < speed * scalar!(1e-9), "{profile:?}");
+        }
+    }
+}
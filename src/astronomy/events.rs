@@ -0,0 +1,400 @@
+use crate::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+
+/// The maximum number of bisection steps when locating an event inside a step
+const MAX_BISECTIONS: u16 = 200;
+
+
+/// A user-defined event function
+pub type EventFunction = Box<dyn Fn(&EventState) -> f64>;
+
+/// A function, which gets called with every detected event
+pub type EventCallback = Box<dyn FnMut(&EventRecord)>;
+
+
+/// # Event State
+/// A snapshot of the celestials, which the event functions are evaluated on
+#[derive(Clone, Debug)]
+pub struct EventState {
+    pub time: Scalar,
+    pub names: Vec<String>,
+    pub bodies: Vec<PointBody>,
+    pub radii: Vec<Length>
+}
+
+
+impl EventState {
+    /// Take a snapshot of the given celestials at the given time
+    pub fn capture(time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) -> Self {
+        Self {
+            time,
+            names: celestials.iter().map(|celestial| celestial.get_name()).collect(),
+            bodies: celestials.iter().map(|celestial| celestial.point_body().clone()).collect(),
+            radii: celestials.iter().map(|celestial| celestial.get_radius()).collect()
+        }
+    }
+
+    /// Get the state of a body from its name
+    pub fn body(&self, name: &str) -> Option<&PointBody> {
+        self.names.iter().position(|n| n == name).map(|i| &self.bodies[i])
+    }
+
+    /// Get the radius of a body from its name
+    pub fn radius(&self, name: &str) -> Option<Length> {
+        self.names.iter().position(|n| n == name).map(|i| self.radii[i])
+    }
+
+    /// Interpolate between two states using cubic Hermite interpolation.
+    /// `fraction` is `0` at `self` and `1` at `other`.
+    pub fn interpolate(&self, other: &Self, fraction: Scalar) -> Self {
+        let s: Scalar = fraction;
+        let h: Scalar = other.time - self.time;
+
+        // Hermite basis functions and their derivatives
+        let h00: Scalar = scalar!(2) * s.powi(3) - scalar!(3) * s.powi(2) + scalar!(1);
+        let h10: Scalar = s.powi(3) - scalar!(2) * s.powi(2) + s;
+        let h01: Scalar = scalar!(3) * s.powi(2) - scalar!(2) * s.powi(3);
+        let h11: Scalar = s.powi(3) - s.powi(2);
+        let d00: Scalar = scalar!(6) * s.powi(2) - scalar!(6) * s;
+        let d10: Scalar = scalar!(3) * s.powi(2) - scalar!(4) * s + scalar!(1);
+        let d01: Scalar = scalar!(6) * s - scalar!(6) * s.powi(2);
+        let d11: Scalar = scalar!(3) * s.powi(2) - scalar!(2) * s;
+
+        let bodies: Vec<PointBody> = self.bodies.iter().zip(other.bodies.iter()).map(|(a, b)| {
+            let p0: Vector = Vector::from_point(a.coordinates);
+            let p1: Vector = Vector::from_point(b.coordinates);
+
+            let coordinates: Vector = p0 * h00 + a.velocity * (h10 * h) + p1 * h01 + b.velocity * (h11 * h);
+            let velocity: Vector = if h == ZERO {
+                a.velocity
+            } else {
+                (p0 * d00 + p1 * d01) / h + a.velocity * d10 + b.velocity * d11
+            };
+            let mass: Mass = a.mass + (b.mass - a.mass) * s;
+
+            PointBody::new(mass, velocity, coordinates.to_point(), a.charge)
+        }).collect();
+
+        Self {
+            time: self.time + h * s,
+            names: self.names.clone(),
+            bodies,
+            radii: self.radii.clone()
+        }
+    }
+}
+
+
+/// # Event Kind
+/// The things the event detector can look for:
+/// - `Periapsis` - `body` passes its closest point to `around`
+/// - `Apoapsis` - `body` passes its farthest point from `around`
+/// - `ClosestApproach` - `a` and `b` are at a local minimum of their distance
+/// - `Eclipse` - `body` enters or leaves the umbra cast by `occulter` from the light of `source`
+/// - `Custom` - a user-defined function crosses zero
+pub enum EventKind {
+    Periapsis { body: String, around: String },
+    Apoapsis { body: String, around: String },
+    ClosestApproach { a: String, b: String },
+    Eclipse { body: String, occulter: String, source: String },
+    Custom { name: String, function: EventFunction }
+}
+
+
+impl EventKind {
+    /// Evaluate the event function, whose zero-crossings mark the events.
+    /// Returns `None` if a required body does not exist.
+    pub fn evaluate(&self, state: &EventState) -> Option<f64> {
+        match self {
+            Self::Periapsis { body, around } | Self::Apoapsis { body, around } => {
+                Self::radial_velocity(state.body(body)?, state.body(around)?)
+            },
+            Self::ClosestApproach { a, b } => {
+                Self::radial_velocity(state.body(a)?, state.body(b)?)
+            },
+            Self::Eclipse { body, occulter, source } => {
+                let target: &PointBody = state.body(body)?;
+                let shade: &PointBody = state.body(occulter)?;
+                let light: &PointBody = state.body(source)?;
+                let shade_radius: Scalar = state.radius(occulter)?.value;
+                let light_radius: Scalar = state.radius(source)?.value;
+
+                // The axis of the shadow cone, pointing away from the light source
+                let axis: Vector = Vector::from_point(shade.coordinates - light.coordinates);
+                let separation: Scalar = axis.magnitude();
+                let axis: Vector = axis.unit();
+
+                let relative: Vector = Vector::from_point(target.coordinates - shade.coordinates);
+                let along: Scalar = relative.dot(&axis);
+
+                // The body is on the lit side of the occulter
+                if along <= ZERO {
+                    return Some(relative.magnitude().to_f64());
+                }
+
+                // Distance from the axis minus the radius of the umbra at that distance
+                let perpendicular: Scalar = (relative - axis * along).magnitude();
+                let umbra: Scalar = shade_radius - (light_radius - shade_radius) * along / separation;
+                Some((perpendicular - umbra).to_f64())
+            },
+            Self::Custom { function, .. } => Some(function(state))
+        }
+    }
+
+    /// The rate of change of the distance between two bodies (multiplied by the distance)
+    fn radial_velocity(body: &PointBody, around: &PointBody) -> Option<f64> {
+        let position: Vector = Vector::from_point(body.coordinates - around.coordinates);
+        let velocity: Vector = body.velocity - around.velocity;
+        Some(position.dot(&velocity).to_f64())
+    }
+
+    /// The crossing directions that count as an event
+    fn direction(&self) -> Crossing {
+        match self {
+            Self::Periapsis { .. } | Self::ClosestApproach { .. } => Crossing::Rising,
+            Self::Apoapsis { .. } => Crossing::Falling,
+            Self::Eclipse { .. } | Self::Custom { .. } => Crossing::Both
+        }
+    }
+
+    /// Describe an event that happened with the given crossing
+    fn describe(&self, rising: bool) -> String {
+        match self {
+            Self::Periapsis { body, around } => format!("{body} periapsis around {around}"),
+            Self::Apoapsis { body, around } => format!("{body} apoapsis around {around}"),
+            Self::ClosestApproach { a, b } => format!("closest approach of {a} and {b}"),
+            Self::Eclipse { body, occulter, .. } => {
+                if rising { format!("{body} leaves the shadow of {occulter}") }
+                else { format!("{body} enters the shadow of {occulter}") }
+            },
+            Self::Custom { name, .. } => {
+                if rising { format!("{name} (rising)") } else { format!("{name} (falling)") }
+            }
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Periapsis { .. } => "periapsis",
+            Self::Apoapsis { .. } => "apoapsis",
+            Self::ClosestApproach { .. } => "closest approach",
+            Self::Eclipse { .. } => "eclipse",
+            Self::Custom { .. } => "custom"
+        }
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Crossing {
+    Rising,
+    Falling,
+    Both
+}
+
+
+/// # Event Record
+/// An event that was detected during the simulation
+#[derive(Clone, Debug)]
+pub struct EventRecord {
+    pub time: Scalar,
+    pub kind: &'static str,
+    pub description: String,
+    pub state: EventState
+}
+
+
+impl std::fmt::Display for EventRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "t = {} s [{}] {}", self.time.to_f64(), self.kind, self.description)
+    }
+}
+
+
+/// # Event Detector
+/// Detects events between two steps of the simulation.
+/// When an event function changes sign during a step,
+/// the moment of the event is located by bisection on the interpolated states,
+/// until the interval is shorter than `precision`.\
+/// Only one crossing of each event is detected per step,
+/// so the step should be short compared to the time between events.
+pub struct EventDetector {
+    events: Vec<EventKind>,
+    pub precision: Scalar,
+    log: Vec<EventRecord>,
+    callbacks: Vec<EventCallback>,
+    log_file: Option<BufWriter<File>>
+}
+
+
+impl EventDetector {
+    pub fn new(precision: Scalar) -> Self {
+        Self {
+            events: Vec::new(),
+            precision,
+            log: Vec::new(),
+            callbacks: Vec::new(),
+            log_file: None
+        }
+    }
+
+    /// Add an event to look for
+    pub fn add_event(&mut self, event: EventKind) { self.events.push(event); }
+
+    /// Add a function, which gets called every time an event is detected
+    pub fn add_callback(&mut self, callback: impl FnMut(&EventRecord) + 'static) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// Write every detected event into the given file as well
+    pub fn log_to_file(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.log_file = Some(BufWriter::new(File::create(path)?));
+        Ok(())
+    }
+
+    /// Get the events detected so far
+    pub fn get_log(&self) -> &[EventRecord] { &self.log }
+
+    /// Remove the detected events from the log
+    pub fn clear_log(&mut self) { self.log.clear(); }
+
+    pub fn is_empty(&self) -> bool { self.events.is_empty() }
+
+    /// Look for the events between two states
+    pub fn detect(&mut self, before: &EventState, after: &EventState) {
+        let mut records: Vec<EventRecord> = Vec::new();
+        let forward: bool = after.time >= before.time;
+
+        for event in self.events.iter() {
+            let (start, end): (f64, f64) = match (event.evaluate(before), event.evaluate(after)) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue
+            };
+
+            // The directions are meant in increasing time, so on a backward step the earlier state is `after`
+            let (earlier, later): (f64, f64) = if forward { (start, end) } else { (end, start) };
+            let rising: bool = earlier < 0.0 && later >= 0.0;
+            let falling: bool = earlier > 0.0 && later <= 0.0;
+            let found: bool = match event.direction() {
+                Crossing::Rising => rising,
+                Crossing::Falling => falling,
+                Crossing::Both => rising || falling
+            };
+            if !found { continue; }
+
+            let state: EventState = self.locate(event, before, after, start);
+            records.push(
+                EventRecord {
+                    time: state.time,
+                    kind: event.label(),
+                    description: event.describe(rising),
+                    state
+                }
+            );
+        }
+
+        // Report the events in the order they happened
+        records.sort_by(|a, b| {
+            let order = a.time.partial_cmp(&b.time).unwrap_or(std::cmp::Ordering::Equal);
+            if forward { order } else { order.reverse() }
+        });
+
+        for record in records {
+            for callback in self.callbacks.iter_mut() {
+                callback(&record);
+            }
+            if let Some(file) = self.log_file.as_mut() {
+                // A failing log file should not stop the simulation
+                let _ = writeln!(file, "{record}").and_then(|_| file.flush());
+            }
+            self.log.push(record);
+        }
+    }
+
+    /// Locate the zero-crossing of the event function by bisection
+    fn locate(&self, event: &EventKind, before: &EventState, after: &EventState, start: f64) -> EventState {
+        let length: Scalar = (after.time - before.time).abs();
+        let mut lower: Scalar = scalar!(0);
+        let mut upper: Scalar = scalar!(1);
+
+        for _ in 0..MAX_BISECTIONS {
+            if (upper - lower) * length <= self.precision { break; }
+
+            let middle: Scalar = (lower + upper) / scalar!(2);
+            let value: f64 = event.evaluate(&before.interpolate(after, middle)).unwrap_or(start);
+
+            if (value < 0.0) == (start < 0.0) && value != 0.0 {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+        }
+
+        before.interpolate(after, upper)
+    }
+
+    /// Write the full event log into a file
+    pub fn write_log(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut file: BufWriter<File> = BufWriter::new(File::create(path)?);
+        for record in self.log.iter() {
+            writeln!(file, "{record}")?;
+        }
+        file.flush()
+    }
+}
+
+
+impl Default for EventDetector {
+    fn default() -> Self {
+        Self::new(scalar!(1e-3))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// A planet on an eccentric orbit, starting after its periapsis
+    fn orbit() -> AstronomicalSimulation {
+        let sun: Star = star!("Sun", 0, 0, 2e30, 7e8);
+        let planet: Planet = planet!("Planet", (3000, 0, 36_000), (1.5e11, 0, 0), 6e24, 6.4e6);
+        let mut simulation: AstronomicalSimulation = astronomical_simulation!(3600, vec![Box::new(sun), Box::new(planet)]);
+        simulation.set_integrator(Integrator::Leapfrog);
+        simulation.add_event(EventKind::Periapsis { body: String::from("Planet"), around: String::from("Sun") });
+        simulation.add_event(EventKind::Apoapsis { body: String::from("Planet"), around: String::from("Sun") });
+        simulation.add_event(EventKind::ClosestApproach { a: String::from("Planet"), b: String::from("Sun") });
+        simulation
+    }
+
+    #[test]
+    fn backward_runs_label_the_same_passages() {
+        let mut simulation: AstronomicalSimulation = orbit();
+        simulation.run_for(24 * 365 * 3);
+        let mut forward: Vec<(f64, &str)> = simulation.get_event_detector().get_log().iter()
+            .map(|record| (record.time.to_f64(), record.kind))
+            .collect();
+        assert!(forward.iter().any(|(_, kind)| *kind == "apoapsis"));
+        assert!(forward.iter().any(|(_, kind)| *kind == "closest approach"));
+
+        simulation.get_event_detector_mut().clear_log();
+        simulation.reverse_time();
+        simulation.run_for(24 * 365 * 3);
+        let mut backward: Vec<(f64, &str)> = simulation.get_event_detector().get_log().iter()
+            .map(|record| (record.time.to_f64(), record.kind))
+            .collect();
+
+        // The events at the same time can be reported in any order
+        forward.sort_by(|a, b| a.1.cmp(b.1).then(a.0.total_cmp(&b.0)));
+        backward.sort_by(|a, b| a.1.cmp(b.1).then(a.0.total_cmp(&b.0)));
+
+        assert_eq!(forward.len(), backward.len(), "{forward:?} {backward:?}");
+        for ((forward_time, forward_kind), (backward_time, backward_kind)) in forward.iter().zip(backward.iter()) {
+            assert_eq!(forward_kind, backward_kind);
+            assert!((forward_time - backward_time).abs() < 3600.0, "{forward_time} != {backward_time}");
+        }
+    }
+}
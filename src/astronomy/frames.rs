@@ -0,0 +1,119 @@
+use crate::*;
+
+
+/// # Obliquity of the Ecliptic
+/// The angle between the equator and the ecliptic at J2000 (in radians)\
+/// Value: `84381.448"`
+pub const OBLIQUITY_J2000: f64 = 84_381.448 / 3600.0 * std::f64::consts::PI / 180.0;
+
+
+/// Convert a vector from the ecliptic frame to the frame of the simulation.
+/// In the ecliptic frame `z` points to the north ecliptic pole,
+/// while in the simulation `y` is the height, so the ecliptic is the `x`-`z` plane:
+/// `(x, y, z)` -> `(x, z, -y)`
+pub fn ecliptic_to_simulation(vector: [f64; 3]) -> Vector {
+    vector!(vector[0], vector[2], -vector[1])
+}
+
+
+/// Convert a vector from the frame of the simulation to the ecliptic frame.
+/// The inverse of `ecliptic_to_simulation`.
+pub fn simulation_to_ecliptic(vector: &Vector) -> [f64; 3] {
+    [vector.point.x.to_f64(), -vector.point.z.to_f64(), vector.point.y.to_f64()]
+}
+
+
+/// Rotate a vector from the equatorial frame (ICRF / J2000) to the ecliptic frame
+pub fn equatorial_to_ecliptic(vector: [f64; 3]) -> [f64; 3] {
+    let (sin, cos): (f64, f64) = OBLIQUITY_J2000.sin_cos();
+    [
+        vector[0],
+        cos * vector[1] + sin * vector[2],
+        -sin * vector[1] + cos * vector[2]
+    ]
+}
+
+
+/// Rotate a vector from the ecliptic frame to the equatorial frame (ICRF / J2000)
+pub fn ecliptic_to_equatorial(vector: [f64; 3]) -> [f64; 3] {
+    let (sin, cos): (f64, f64) = OBLIQUITY_J2000.sin_cos();
+    [
+        vector[0],
+        cos * vector[1] - sin * vector[2],
+        sin * vector[1] + cos * vector[2]
+    ]
+}
+
+
+/// Rotate a vector from the TEME frame (True Equator, Mean Equinox) of the given Julian day
+/// to the equatorial frame (mean equator and equinox of J2000).
+/// The rotation uses the IAU-1976 precession and the largest terms of the IAU-1980 nutation,
+/// which is accurate to a few metres at the orbits of the Earth satellites.
+pub fn teme_to_equatorial(vector: [f64; 3], julian_day: f64) -> [f64; 3] {
+    const ARCSECOND: f64 = std::f64::consts::PI / 180.0 / 3600.0;
+    let t: f64 = (julian_day - 2_451_545.0) / 36_525.0;
+
+    // The fundamental arguments of the Moon and the Sun
+    let degrees = |constant: f64, linear: f64, quadratic: f64, cubic: f64| -> f64 {
+        (constant + ((cubic * t + quadratic) * t + linear) * t / 3600.0).to_radians()
+    };
+    let l: f64 = degrees(134.96298139, 1717915922.6330, 31.310, 0.064);
+    let l1: f64 = degrees(357.52772333, 129596581.2240, -0.577, -0.012);
+    let f: f64 = degrees(93.27191028, 1739527263.1370, -13.257, 0.011);
+    let d: f64 = degrees(297.85036306, 1602961601.3280, -6.891, 0.019);
+    let omega: f64 = degrees(125.04452222, -6962890.5390, 7.455, 0.008);
+
+    // (argument, Δψ, Δψ rate, Δε, Δε rate) in 0.0001"
+    let terms: [(f64, f64, f64, f64, f64); 9] = [
+        (omega, -171_996.0, -174.2, 92_025.0, 8.9),
+        (2.0 * (f - d + omega), -13_187.0, -1.6, 5_736.0, -3.1),
+        (2.0 * (f + omega), -2_274.0, -0.2, 977.0, -0.5),
+        (2.0 * omega, 2_062.0, 0.2, -895.0, 0.5),
+        (l1, 1_426.0, -3.4, 54.0, -0.1),
+        (l, 712.0, 0.1, -7.0, 0.0),
+        (l1 + 2.0 * (f - d + omega), -517.0, 1.2, 224.0, -0.6),
+        (2.0 * f + omega, -386.0, -0.4, 200.0, 0.0),
+        (l + 2.0 * (f + omega), -301.0, 0.0, 129.0, -0.1)
+    ];
+    let (mut delta_psi, mut delta_epsilon): (f64, f64) = (0.0, 0.0);
+    for (argument, psi, psi_rate, epsilon, epsilon_rate) in terms {
+        delta_psi += (psi + psi_rate * t) * argument.sin();
+        delta_epsilon += (epsilon + epsilon_rate * t) * argument.cos();
+    }
+    let delta_psi: f64 = delta_psi * 1e-4 * ARCSECOND;
+    let delta_epsilon: f64 = delta_epsilon * 1e-4 * ARCSECOND;
+    let mean_epsilon: f64 = (84_381.448 + ((0.001813 * t - 0.00059) * t - 46.8150) * t) * ARCSECOND;
+    let true_epsilon: f64 = mean_epsilon + delta_epsilon;
+
+    // TEME -> true of date: the equation of the equinoxes
+    let (sin, cos): (f64, f64) = (delta_psi * mean_epsilon.cos()).sin_cos();
+    let tod: [f64; 3] = [cos * vector[0] - sin * vector[1], sin * vector[0] + cos * vector[1], vector[2]];
+
+    // True of date -> mean of date: the nutation
+    let (sin_psi, cos_psi): (f64, f64) = delta_psi.sin_cos();
+    let (sin_mean, cos_mean): (f64, f64) = mean_epsilon.sin_cos();
+    let (sin_true, cos_true): (f64, f64) = true_epsilon.sin_cos();
+    let nutation: [[f64; 3]; 3] = [
+        [cos_psi, cos_true * sin_psi, sin_true * sin_psi],
+        [-cos_mean * sin_psi, cos_true * cos_mean * cos_psi + sin_true * sin_mean, sin_true * cos_mean * cos_psi - sin_mean * cos_true],
+        [-sin_mean * sin_psi, cos_true * sin_mean * cos_psi - sin_true * cos_mean, sin_true * sin_mean * cos_psi + cos_true * cos_mean]
+    ];
+
+    // Mean of date -> J2000: the precession
+    let zeta: f64 = ((0.017998 * t + 0.30188) * t + 2306.2181) * t * ARCSECOND;
+    let theta: f64 = ((-0.041833 * t - 0.42665) * t + 2004.3109) * t * ARCSECOND;
+    let z: f64 = ((0.018203 * t + 1.09468) * t + 2306.2181) * t * ARCSECOND;
+    let (sin_zeta, cos_zeta): (f64, f64) = zeta.sin_cos();
+    let (sin_theta, cos_theta): (f64, f64) = theta.sin_cos();
+    let (sin_z, cos_z): (f64, f64) = z.sin_cos();
+    let precession: [[f64; 3]; 3] = [
+        [cos_zeta * cos_theta * cos_z - sin_zeta * sin_z, cos_zeta * cos_theta * sin_z + sin_zeta * cos_z, cos_zeta * sin_theta],
+        [-sin_zeta * cos_theta * cos_z - cos_zeta * sin_z, -sin_zeta * cos_theta * sin_z + cos_zeta * cos_z, -sin_zeta * sin_theta],
+        [-sin_theta * cos_z, -sin_theta * sin_z, cos_theta]
+    ];
+
+    let multiply = |matrix: &[[f64; 3]; 3], vector: [f64; 3]| -> [f64; 3] {
+        [0, 1, 2].map(|row| (0..3).map(|column| matrix[row][column] * vector[column]).sum())
+    };
+    multiply(&precession, multiply(&nutation, tod))
+}
@@ -0,0 +1,312 @@
+use crate::*;
+use std::f64::consts::PI as PI_F64;
+
+
+/// # Halo Potential
+/// A static Hernquist potential, which moves together with its anchor body:
+/// $$\Phi(r) = -\frac{GM}{r + a}$$
+/// Every body except the anchor feels its pull.
+/// ## Attributes
+/// `anchor: String` - The name of the body at the centre of the halo\
+/// `mass: Mass` - The total mass of the halo\
+/// `scale_radius: Length` - The scale length `a` of the halo
+#[derive(Debug, Clone)]
+pub struct HaloPotential {
+    pub anchor: String,
+    pub mass: Mass,
+    pub scale_radius: Length
+}
+
+
+impl HaloPotential {
+    pub fn new(anchor: impl ToString, mass: Mass, scale_radius: Length) -> Self {
+        Self { anchor: anchor.to_string(), mass, scale_radius }
+    }
+
+    /// The acceleration caused by the halo at `position`, if its centre is at `center`
+    pub fn acceleration(&self, position: &Point, center: &Point) -> Vector {
+        let offset: Vector = Vector::from_point(*position - *center);
+        let distance: Scalar = offset.magnitude();
+        if distance == ZERO { return NULL_VECTOR; }
+        -offset.unit() * (constants::G * self.mass.value / (distance + self.scale_radius.value).powi(2))
+    }
+}
+
+
+/// # Galaxy
+/// The bodies and the halo of a generated galaxy
+pub struct Galaxy {
+    pub nucleus: Star,
+    pub stars: Vec<Star>,
+    pub halo: Option<HaloPotential>
+}
+
+
+/// # Galaxy Model
+/// A disk galaxy made of a Hernquist bulge, an exponential disk and a dark-matter halo.
+/// ## Attributes
+/// `name: String` - The name of the galaxy, used as the prefix of the names of its bodies\
+/// `bulge_mass: Mass`, `bulge_scale: Length`, `bulge_count: usize` - The Hernquist bulge\
+/// `disk_mass: Mass`, `disk_scale_length: Length`, `disk_scale_height: Length`, `disk_count: usize` -
+/// The disk, with `Σ(R) ∝ exp(-R / R_d)` and a `sech^2(z / z_0)` vertical profile\
+/// `halo_mass: Mass`, `halo_scale: Length`, `halo_count: usize` - The Hernquist halo\
+/// `static_halo: bool` - Add the halo as a static `HaloPotential` (otherwise it is sampled with `halo_count` tracer stars, if there are any)\
+/// `nucleus_mass: Mass` - The mass of the central body (e.g. the black hole), which anchors the halo\
+/// `velocity_dispersion: f64` - The random velocities of the disk, relative to the circular velocity\
+/// `spin_axis: Vector` - The direction of the angular momentum of the disk\
+/// `coordinates: Point`, `velocity: Vector` - The position and velocity of the centre of the galaxy\
+/// `seed: u64` - The seed of the random number generator
+/// ## Tracers
+/// The stars are tracer `Star`s: each one stands for a part of the mass of its component.
+#[derive(Debug, Clone)]
+pub struct GalaxyModel {
+    pub name: String,
+    pub bulge_mass: Mass,
+    pub bulge_scale: Length,
+    pub bulge_count: usize,
+    pub disk_mass: Mass,
+    pub disk_scale_length: Length,
+    pub disk_scale_height: Length,
+    pub disk_count: usize,
+    pub halo_mass: Mass,
+    pub halo_scale: Length,
+    pub halo_count: usize,
+    pub static_halo: bool,
+    pub nucleus_mass: Mass,
+    pub velocity_dispersion: f64,
+    pub spin_axis: Vector,
+    pub coordinates: Point,
+    pub velocity: Vector,
+    pub seed: u64
+}
+
+
+impl GalaxyModel {
+    /// A galaxy resembling the Milky Way, with `count` tracer stars
+    pub fn milky_way(name: impl ToString, count: usize, seed: u64) -> Self {
+        let kiloparsec: Length = PARSEC * scalar!(1000);
+        Self {
+            name: name.to_string(),
+            bulge_mass: SOLAR_MASS * scalar!(1e10),
+            bulge_scale: kiloparsec * scalar!(0.6),
+            bulge_count: count / 5,
+            disk_mass: SOLAR_MASS * scalar!(5e10),
+            disk_scale_length: kiloparsec * scalar!(2.6),
+            disk_scale_height: kiloparsec * scalar!(0.3),
+            disk_count: count - count / 5,
+            halo_mass: SOLAR_MASS * scalar!(1e12),
+            halo_scale: kiloparsec * scalar!(30),
+            halo_count: count,
+            static_halo: true,
+            nucleus_mass: SOLAR_MASS * scalar!(4e6),
+            velocity_dispersion: 0.1,
+            spin_axis: vector!(0, 1, 0),
+            coordinates: ORIGO,
+            velocity: NULL_VECTOR,
+            seed
+        }
+    }
+
+    /// The total mass of the galaxy
+    pub fn total_mass(&self) -> Mass {
+        self.bulge_mass + self.disk_mass + self.halo_mass + self.nucleus_mass
+    }
+
+    /// The name of the central body
+    pub fn nucleus_name(&self) -> String { format!("{} nucleus", self.name) }
+
+    /// The circular velocity in the plane of the disk at the radius `r`
+    pub fn circular_velocity(&self, r: Length) -> Velocity {
+        if r.value == ZERO { return Velocity::new(ZERO); }
+        let x: f64 = (r / self.disk_scale_length).to_f64();
+
+        // The mass enclosed by the radius `r`
+        let disk: Mass = self.disk_mass * scalar!(1.0 - (1.0 + x) * (-x).exp());
+        let bulge: Mass = self.bulge_mass * (r / (r + self.bulge_scale)).powi(2);
+        let halo: Mass = self.halo_mass * (r / (r + self.halo_scale)).powi(2);
+
+        Velocity::new((constants::G * (disk + bulge + halo + self.nucleus_mass).value / r.value).sqrt())
+    }
+
+    /// Generate the bodies of the galaxy
+    pub fn generate(&self) -> Galaxy {
+        let mut random: Random = Random::new(self.seed);
+        let static_halo: bool = self.static_halo || self.halo_count == 0;
+        let halo_count: usize = if static_halo { 0 } else { self.halo_count };
+        let mut stars: Vec<Star> = Vec::with_capacity(self.bulge_count + self.disk_count + halo_count);
+
+        // Bulge
+        let bulge_velocity: Scalar = (constants::G * self.bulge_mass.value / self.bulge_scale.value).sqrt();
+        for i in 0..self.bulge_count {
+            let (position, velocity) = sample_hernquist(&mut random);
+            stars.push(
+                Star::tracer(
+                    format!("{} bulge {}", self.name, i + 1),
+                    self.velocity + self.orient(vector!(velocity[0], velocity[1], velocity[2])) * bulge_velocity,
+                    self.coordinates + self.orient(vector!(position[0], position[1], position[2]) * self.bulge_scale.value).to_point(),
+                    self.bulge_mass / scalar!(self.bulge_count as f64)
+                )
+            );
+        }
+
+        // Disk
+        for i in 0..self.disk_count {
+            let radius: Length = self.disk_scale_length * scalar!(sample_exponential_disk(&mut random));
+            let height: Length = self.disk_scale_height * scalar!(random.range(-1.0, 1.0).clamp(-0.999_999, 0.999_999).atanh());
+            let angle: f64 = random.range(0.0, 2.0 * PI_F64);
+            let (sin, cos): (f64, f64) = angle.sin_cos();
+
+            // The disk rotates in the positive sense around its spin axis
+            let circular: Velocity = self.circular_velocity(radius);
+            let dispersion: f64 = (circular * scalar!(self.velocity_dispersion)).to_f64();
+            let position: Vector = Vector::new((radius * scalar!(cos)).value, height.value, (radius * scalar!(sin)).value);
+            let velocity: Vector = vector!(sin, 0, -cos) * circular.value + vector!(
+                random.normal(0.0, dispersion),
+                random.normal(0.0, dispersion / 2.0),
+                random.normal(0.0, dispersion)
+            );
+
+            stars.push(
+                Star::tracer(
+                    format!("{} disk {}", self.name, i + 1),
+                    self.velocity + self.orient(velocity),
+                    self.coordinates + self.orient(position).to_point(),
+                    self.disk_mass / scalar!(self.disk_count as f64)
+                )
+            );
+        }
+
+        // Live halo
+        let halo_velocity: Scalar = (constants::G * self.halo_mass.value / self.halo_scale.value).sqrt();
+        for i in 0..halo_count {
+            let (position, velocity) = sample_hernquist(&mut random);
+            stars.push(
+                Star::tracer(
+                    format!("{} halo {}", self.name, i + 1),
+                    self.velocity + self.orient(vector!(velocity[0], velocity[1], velocity[2])) * halo_velocity,
+                    self.coordinates + self.orient(vector!(position[0], position[1], position[2]) * self.halo_scale.value).to_point(),
+                    self.halo_mass / scalar!(halo_count as f64)
+                )
+            );
+        }
+
+        Galaxy {
+            nucleus: Star::tracer(self.nucleus_name(), self.velocity, self.coordinates, self.nucleus_mass),
+            stars,
+            halo: if static_halo {
+                Some(HaloPotential::new(self.nucleus_name(), self.halo_mass, self.halo_scale))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Add the galaxy (and its halo) to the simulation, returning the number of added bodies
+    pub fn populate(&self, simulation: &mut AstronomicalSimulation) -> usize {
+        let galaxy: Galaxy = self.generate();
+        let count: usize = galaxy.stars.len() + 1;
+
+        simulation.add_celestial(galaxy.nucleus);
+        for star in galaxy.stars {
+            simulation.add_celestial(star);
+        }
+        if let Some(halo) = galaxy.halo {
+            simulation.add_potential(halo);
+        }
+        count
+    }
+
+    /// Rotate a vector from the frame of the disk (where the spin axis is `y`) to the simulation frame
+    fn orient(&self, vector: Vector) -> Vector {
+        let up: Vector = vector!(0, 1, 0);
+        let axis: Vector = self.spin_axis.unit();
+        let rotation_axis: Vector = up * axis;
+        let sin: Scalar = rotation_axis.magnitude();
+        let cos: Scalar = up.dot(&axis);
+
+        if sin == ZERO {
+            return if cos < ZERO { -vector } else { vector };
+        }
+
+        // Rodrigues' rotation formula
+        let k: Vector = rotation_axis.unit();
+        vector * cos + (k * vector) * sin + k * (k.dot(&vector) * (scalar!(1) - cos))
+    }
+}
+
+
+/// Place two galaxies on a parabolic collision course, as in Toomre & Toomre (1972).
+/// The orbit lies in the reference plane of the simulation,
+/// and the galaxies are placed around their common barycenter at `coordinates`.
+/// ## Params
+/// - `pericenter: Length` - The closest approach of the centres
+/// - `separation: Length` - The initial distance of the centres
+pub fn parabolic_encounter(first: &mut GalaxyModel,
+                           second: &mut GalaxyModel,
+                           pericenter: Length,
+                           separation: Length,
+                           coordinates: Point) {
+    let first_mass: Mass = first.total_mass();
+    let second_mass: Mass = second.total_mass();
+    let total: Mass = first_mass + second_mass;
+
+    // Relative orbit of the second galaxy around the first: r = p / (1 + cos(ν)), with p = 2q
+    let semi_latus_rectum: Length = pericenter * scalar!(2);
+    let true_anomaly: f64 = -((semi_latus_rectum / separation).to_f64() - 1.0).clamp(-1.0, 1.0).acos();
+    let (position, velocity) = OrbitalElements::state_from_true_anomaly(
+        semi_latus_rectum.value, 1.0, 0.0, 0.0, 0.0, true_anomaly, constants::G * total.value
+    );
+
+    first.coordinates = coordinates + (-position * (second_mass / total)).to_point();
+    first.velocity = -velocity * (second_mass / total);
+    second.coordinates = coordinates + (position * (first_mass / total)).to_point();
+    second.velocity = velocity * (first_mass / total);
+}
+
+
+/// Sample the radius (in scale lengths) of an exponential disk,
+/// by inverting the enclosed mass `1 - (1 + x) exp(-x)` with Newton's method
+fn sample_exponential_disk(random: &mut Random) -> f64 {
+    let u: f64 = random.uniform();
+    let mut x: f64 = 1.0;
+    for _ in 0..50 {
+        let value: f64 = 1.0 - (1.0 + x) * (-x).exp() - u;
+        let derivative: f64 = x * (-x).exp();
+        if derivative <= 0.0 { break; }
+        let step: f64 = value / derivative;
+        x = (x - step).max(1e-9);
+        if step.abs() < 1e-12 { break; }
+    }
+    x
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn live_halo_keeps_the_mass_of_the_galaxy() {
+        let mut model: GalaxyModel = GalaxyModel::milky_way("Galaxy", 100, 7);
+        model.static_halo = false;
+        model.halo_count = 50;
+
+        let galaxy: Galaxy = model.generate();
+        assert!(galaxy.halo.is_none());
+        assert_eq!(galaxy.stars.len(), 150);
+        assert_eq!(galaxy.stars.iter().filter(|star| star.get_name().contains("halo")).count(), 50);
+
+        let mass: Mass = galaxy.stars.iter()
+            .fold(galaxy.nucleus.point_body().mass, |total, star| total + star.point_body().mass);
+        let error: f64 = ((mass - model.total_mass()) / model.total_mass()).to_f64().abs();
+        assert!(error < 1e-12, "the bodies weigh {mass}, the model {}", model.total_mass());
+    }
+
+    #[test]
+    fn static_halo_adds_a_potential() {
+        let model: GalaxyModel = GalaxyModel::milky_way("Galaxy", 100, 7);
+        let galaxy: Galaxy = model.generate();
+        assert_eq!(galaxy.stars.len(), 100);
+        assert_eq!(galaxy.halo.map(|halo| halo.mass), Some(model.halo_mass));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::*;
+use std::f64::consts::PI as PI_F64;
+
+
+/// # Generator Error
+/// Invalid settings of a `DiskGenerator`
+/// - `RadialRange` - The semi-major axes need `0 < inner < outer` (in metres)
+/// - `SizeRange` - The radii of the bodies need `0 < min <= max` (in metres)
+/// - `MissingCenter` - The simulation has no body with the name of the centre
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratorError {
+    RadialRange { inner: f64, outer: f64 },
+    SizeRange { min: f64, max: f64 },
+    MissingCenter(String)
+}
+
+
+impl std::fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RadialRange { inner, outer } => write!(f, "invalid radial range {inner}-{outer} m (expected 0 < inner < outer)"),
+            Self::SizeRange { min, max } => write!(f, "invalid size range {min}-{max} m (expected 0 < min <= max)"),
+            Self::MissingCenter(name) => write!(f, "there is no body called {name}")
+        }
+    }
+}
+
+
+impl std::error::Error for GeneratorError {}
+
+
+/// # Disk Generator
+/// Generates a population of small bodies on near-Keplerian orbits around a central body:
+/// asteroid belts, protoplanetary disks and planetary rings.
+/// ## Attributes
+/// `name: String` - The prefix of the names of the generated bodies\
+/// `center: String` - The name of the central body\
+/// `count: usize` - The number of generated bodies\
+/// `inner_radius: Length`, `outer_radius: Length` - The radial range of the semi-major axes\
+/// `surface_density_exponent: f64` - `p` in the surface density profile `Σ(r) ∝ r^-p`\
+/// `eccentricity_dispersion: f64` - The scale of the Rayleigh distributed eccentricities\
+/// `inclination_dispersion: f64` - The scale of the Rayleigh distributed inclinations (in radians)\
+/// `min_size: Length`, `max_size: Length` - The range of the radii of the bodies\
+/// `size_exponent: f64` - `q` in the size distribution `dN/dR ∝ R^-q`\
+/// `density: Scalar` - The density of the bodies (for calculating their masses)\
+/// `planet_type: PlanetType` - The type of the generated bodies\
+/// `test_particles: bool` - Generate test particles, which do not attract other bodies\
+/// `seed: u64` - The seed of the random number generator
+/// ## Orbits
+/// The orbits are measured relative to the reference plane of the simulation (`x`-`z`).
+/// The angles of the nodes, the periapses and the mean anomalies are uniformly distributed.
+#[derive(Debug, Clone)]
+pub struct DiskGenerator {
+    pub name: String,
+    pub center: String,
+    pub count: usize,
+    pub inner_radius: Length,
+    pub outer_radius: Length,
+    pub surface_density_exponent: f64,
+    pub eccentricity_dispersion: f64,
+    pub inclination_dispersion: f64,
+    pub min_size: Length,
+    pub max_size: Length,
+    pub size_exponent: f64,
+    pub density: Scalar,
+    pub planet_type: PlanetType,
+    pub test_particles: bool,
+    pub seed: u64
+}
+
+
+impl DiskGenerator {
+    /// An asteroid belt between `2.1 AU` and `3.3 AU`,
+    /// with rocky bodies between `1 km` and `100 km`
+    pub fn asteroid_belt(center: impl ToString, count: usize, seed: u64) -> Self {
+        Self {
+            name: String::from("Asteroid"),
+            center: center.to_string(),
+            count,
+            inner_radius: AU * scalar!(2.1),
+            outer_radius: AU * scalar!(3.3),
+            surface_density_exponent: 1.0,
+            eccentricity_dispersion: 0.1,
+            inclination_dispersion: 0.1,
+            min_size: Length::from(1e3),
+            max_size: Length::from(1e5),
+            size_exponent: 3.5,
+            density: scalar!(2000),
+            planet_type: PlanetType::Terrestrial,
+            test_particles: false,
+            seed
+        }
+    }
+
+    /// A protoplanetary disk of planetesimals between `0.1 AU` and `30 AU`,
+    /// following the surface density of the minimum-mass solar nebula (`Σ ∝ r^-1.5`)
+    pub fn protoplanetary_disk(center: impl ToString, count: usize, seed: u64) -> Self {
+        Self {
+            name: String::from("Planetesimal"),
+            center: center.to_string(),
+            count,
+            inner_radius: AU * scalar!(0.1),
+            outer_radius: AU * scalar!(30),
+            surface_density_exponent: 1.5,
+            eccentricity_dispersion: 0.01,
+            inclination_dispersion: 0.005,
+            min_size: Length::from(1e3),
+            max_size: Length::from(1e6),
+            size_exponent: 3.5,
+            density: scalar!(2000),
+            planet_type: PlanetType::Terrestrial,
+            test_particles: false,
+            seed
+        }
+    }
+
+    /// A dynamically cold ring of icy particles between `inner_radius` and `outer_radius`
+    pub fn planetary_ring(center: impl ToString,
+                          inner_radius: Length,
+                          outer_radius: Length,
+                          count: usize,
+                          seed: u64) -> Self {
+        Self {
+            name: String::from("Ring particle"),
+            center: center.to_string(),
+            count,
+            inner_radius,
+            outer_radius,
+            surface_density_exponent: 0.0,
+            eccentricity_dispersion: 1e-4,
+            inclination_dispersion: 1e-5,
+            min_size: Length::from(0.01),
+            max_size: Length::from(10),
+            size_exponent: 3.0,
+            density: scalar!(900),
+            planet_type: PlanetType::Satellite,
+            test_particles: true,
+            seed
+        }
+    }
+
+    /// Check that the radial range is `0 < inner < outer` and the size range is `0 < min <= max`
+    pub fn validate(&self) -> Result<(), GeneratorError> {
+        let (inner, outer): (f64, f64) = (self.inner_radius.to_f64(), self.outer_radius.to_f64());
+        if !(inner > 0.0 && inner < outer) {
+            return Err(GeneratorError::RadialRange { inner, outer });
+        }
+        let (min, max): (f64, f64) = (self.min_size.to_f64(), self.max_size.to_f64());
+        if !(min > 0.0 && min <= max) {
+            return Err(GeneratorError::SizeRange { min, max });
+        }
+        Ok(())
+    }
+
+    /// Generate the bodies around the given central body
+    pub fn generate(&self, center: &PointBody) -> Result<Vec<Planet>, GeneratorError> {
+        self.validate()?;
+        let mut random: Random = Random::new(self.seed);
+        let mut bodies: Vec<Planet> = Vec::with_capacity(self.count);
+
+        for i in 0..self.count {
+            // The surface density Σ(r) ∝ r^-p means dN/dr ∝ r^(1 - p)
+            let semi_major_axis: f64 = sample_power_law(
+                &mut random,
+                self.inner_radius.to_f64(),
+                self.outer_radius.to_f64(),
+                1.0 - self.surface_density_exponent
+            );
+            let radius: f64 = sample_power_law(
+                &mut random,
+                self.min_size.to_f64(),
+                self.max_size.to_f64(),
+                -self.size_exponent
+            );
+            let mass: Mass = Mass::new(self.density * scalar!(4.0 / 3.0 * PI_F64 * radius.powi(3)));
+
+            let elements: OrbitalElements = OrbitalElements::new(
+                scalar!(semi_major_axis),
+                random.rayleigh(self.eccentricity_dispersion).min(0.99),
+                random.rayleigh(self.inclination_dispersion).min(PI_F64),
+                random.range(0.0, 2.0 * PI_F64),
+                random.range(0.0, 2.0 * PI_F64),
+                random.range(0.0, 2.0 * PI_F64)
+            );
+            let (position, velocity) = elements.to_state(constants::G * (center.mass + mass).value);
+
+            let mut body: Planet = Planet::new(
+                format!("{} {}", self.name, i + 1),
+                center.velocity + velocity,
+                center.coordinates + position.to_point(),
+                mass,
+                Length::from(radius),
+                self.planet_type
+            );
+            body.set_test_particle(self.test_particles);
+            bodies.push(body);
+        }
+
+        Ok(bodies)
+    }
+
+    /// Add the generated bodies to the simulation.
+    /// Returns the number of added bodies.
+    pub fn populate(&self, simulation: &mut AstronomicalSimulation) -> Result<usize, GeneratorError> {
+        let center: PointBody = simulation.get_celestial_from_name(&self.center)
+            .ok_or_else(|| GeneratorError::MissingCenter(self.center.clone()))?
+            .point_body()
+            .clone();
+        let bodies: Vec<Planet> = self.generate(&center)?;
+        let count: usize = bodies.len();
+        for body in bodies {
+            simulation.add_celestial(body);
+        }
+        Ok(count)
+    }
+}
+
+
+/// Sample `x` from the distribution `dN/dx ∝ x^exponent` between `min` and `max`
+pub fn sample_power_law(random: &mut Random, min: f64, max: f64, exponent: f64) -> f64 {
+    let u: f64 = random.uniform();
+    let k: f64 = exponent + 1.0;
+    if k.abs() < 1e-12 {
+        min * (max / min).powf(u)
+    } else {
+        (min.powf(k) + u * (max.powf(k) - min.powf(k))).powf(1.0 / k)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn same_seed_generates_the_same_belt() {
+        let sun: Star = star!("Sun", 0, 0, 2e30, 7e8);
+        let belt = |seed: u64| -> Vec<(Point, Vector, Mass)> {
+            DiskGenerator::asteroid_belt("Sun", 50, seed).generate(sun.point_body()).unwrap().iter()
+                .map(|body| (body.coordinates(), body.velocity(), body.mass()))
+                .collect()
+        };
+        assert_eq!(belt(7), belt(7));
+        assert_ne!(belt(7), belt(8));
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        let sun: Star = star!("Sun", 0, 0, 2e30, 7e8);
+        let belt: DiskGenerator = DiskGenerator::asteroid_belt("Sun", 10, 1);
+        for (inner, outer) in [(3.0, 2.0), (2.0, 2.0), (0.0, 2.0), (-1.0, 2.0)] {
+            let generator: DiskGenerator = DiskGenerator {
+                inner_radius: AU * scalar!(inner), outer_radius: AU * scalar!(outer), ..belt.clone()
+            };
+            assert!(matches!(generator.generate(sun.point_body()), Err(GeneratorError::RadialRange { .. })));
+        }
+        for (min, max) in [(0.0, 1e3), (-1.0, 1e3), (1e4, 1e3)] {
+            let generator: DiskGenerator = DiskGenerator {
+                min_size: Length::from(min), max_size: Length::from(max), ..belt.clone()
+            };
+            assert_eq!(generator.generate(sun.point_body()).err(), Some(GeneratorError::SizeRange { min, max }));
+        }
+
+        let mut simulation: AstronomicalSimulation = astronomical_simulation!(3600, vec![Box::new(sun)]);
+        assert_eq!(belt.populate(&mut simulation), Ok(10));
+        assert_eq!(
+            DiskGenerator::asteroid_belt("Earth", 10, 1).populate(&mut simulation),
+            Err(GeneratorError::MissingCenter(String::from("Earth")))
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use crate::*;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+
+/// The tolerance used when looking up an epoch (in days, about 0.1 s)
+const EPOCH_TOLERANCE: f64 = 1e-6;
+
+
+/// # Horizons Error
+/// An error while reading a Horizons vector table
+/// - `Io` - The file could not be read
+/// - `Format` - The file is invalid, at the given line (starting from 1)
+/// - `MissingEpoch` - The table has no state at the requested epoch (Julian day)
+#[derive(Debug)]
+pub enum HorizonsError {
+    Io(std::io::Error),
+    Format { line: usize, message: String },
+    MissingEpoch(f64)
+}
+
+
+impl HorizonsError {
+    fn format(line: usize, message: impl ToString) -> Self {
+        Self::Format { line, message: message.to_string() }
+    }
+}
+
+
+impl Display for HorizonsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Format { line, message } => write!(f, "line {line}: {message}"),
+            Self::MissingEpoch(epoch) => write!(f, "no state at JD {epoch}")
+        }
+    }
+}
+
+
+impl std::error::Error for HorizonsError {}
+
+
+impl From<std::io::Error> for HorizonsError {
+    fn from(error: std::io::Error) -> Self { Self::Io(error) }
+}
+
+
+/// # Horizons Units
+/// The `Output units` of a vector table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HorizonsUnits {
+    KilometresPerSecond,
+    KilometresPerDay,
+    AstronomicalUnitsPerDay
+}
+
+
+impl HorizonsUnits {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_uppercase().as_str() {
+            "KM-S" => Some(Self::KilometresPerSecond),
+            "KM-D" => Some(Self::KilometresPerDay),
+            "AU-D" => Some(Self::AstronomicalUnitsPerDay),
+            _ => None
+        }
+    }
+
+    /// The length of the unit of distance (in metres)
+    pub fn length(&self) -> Scalar {
+        match self {
+            Self::KilometresPerSecond | Self::KilometresPerDay => scalar!(1000),
+            Self::AstronomicalUnitsPerDay => AU.value
+        }
+    }
+
+    /// The length of the unit of time (in seconds)
+    pub fn time(&self) -> Scalar {
+        match self {
+            Self::KilometresPerSecond => scalar!(1),
+            Self::KilometresPerDay | Self::AstronomicalUnitsPerDay => DAY.value
+        }
+    }
+}
+
+
+/// # Horizons State
+/// The state of the target at one epoch, in SI units and in the frame of the simulation
+/// ## Attributes
+/// `epoch: f64` - The Julian day (TDB)\
+/// `coordinates: Point` - The position relative to the centre body\
+/// `velocity: Vector` - The velocity relative to the centre body
+#[derive(Debug, Clone, Copy)]
+pub struct HorizonsState {
+    pub epoch: f64,
+    pub coordinates: Point,
+    pub velocity: Vector
+}
+
+
+/// # Horizons Vectors
+/// A vector table (`EPHEM_TYPE=VECTORS`) exported from JPL Horizons.
+/// The states are read from the `$$SOE` ... `$$EOE` block, both the default layout
+/// (`X =... Y =... Z =...` lines) and the CSV layout (`CSV_FORMAT=YES`) are supported.
+/// The `Output units` can be `KM-S`, `KM-D` or `AU-D`, and the `Reference plane` can be
+/// `ECLIPTIC` or `FRAME` (the ICRF equator), which is rotated to the ecliptic.
+/// ## Attributes
+/// `target: String` - The name of the target body (e.g. `Earth`)\
+/// `center: String` - The name of the body the states are relative to (e.g. `Solar System Barycenter`)\
+/// `center_id: Option<i32>` - The NAIF ID of the centre body, when the header gives it (e.g. `Sun (10)`)\
+/// `states: Vec<HorizonsState>` - The states in the order of the table
+#[derive(Debug, Clone)]
+pub struct HorizonsVectors {
+    pub target: String,
+    pub center: String,
+    pub center_id: Option<i32>,
+    pub states: Vec<HorizonsState>
+}
+
+
+impl HorizonsVectors {
+    /// Read a vector table from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, HorizonsError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse the text of a vector table
+    pub fn parse(text: &str) -> Result<Self, HorizonsError> {
+        let mut target: Option<String> = None;
+        let mut center: Option<String> = None;
+        let mut center_id: Option<i32> = None;
+        let mut units: Option<HorizonsUnits> = None;
+        let mut equatorial: bool = false;
+        let mut columns: Option<Vec<String>> = None;
+        let mut last_header: &str = "";
+        let mut states: Vec<HorizonsState> = Vec::new();
+        let mut record: Option<(f64, [Option<f64>; 6], usize)> = None;
+        let mut inside: bool = false;
+        let mut ended: bool = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let number: usize = index + 1;
+            let content: &str = line.trim();
+
+            if !inside {
+                if content == "$$SOE" {
+                    if units.is_none() {
+                        return Err(HorizonsError::format(number, "missing 'Output units' before $$SOE"));
+                    }
+                    if last_header.contains(',') {
+                        columns = Some(last_header.split(',').map(|column| column.trim().to_uppercase()).collect());
+                    }
+                    inside = true;
+                    continue;
+                }
+                if let Some((key, value)) = content.split_once(':') {
+                    let value: &str = value.trim();
+                    match key.trim() {
+                        "Target body name" => target = Some(body_name(value)),
+                        "Center body name" => {
+                            center = Some(body_name(value));
+                            center_id = body_id(value);
+                        },
+                        "Output units" => {
+                            let unit: &str = value.split_whitespace().next().unwrap_or("");
+                            units = Some(HorizonsUnits::parse(unit).ok_or_else(|| HorizonsError::format(
+                                number, format!("unsupported output units '{unit}' (expected KM-S, KM-D or AU-D)")
+                            ))?);
+                        },
+                        "Reference plane" => {
+                            let plane: String = value.to_uppercase();
+                            if plane.starts_with("FRAME") || plane.starts_with("ICRF") || plane.contains("EQUATOR") {
+                                equatorial = true;
+                            } else if plane.starts_with("ECLIPTIC") {
+                                equatorial = false;
+                            } else {
+                                return Err(HorizonsError::format(number, format!("unsupported reference plane '{value}'")));
+                            }
+                        },
+                        "Output type" if value.to_uppercase().contains("OBSERVER") => {
+                            return Err(HorizonsError::format(number, "not a vector table (EPHEM_TYPE must be VECTORS)"));
+                        },
+                        _ => ()
+                    }
+                }
+                if !content.is_empty() && !content.starts_with('*') {
+                    last_header = content;
+                }
+                continue;
+            }
+
+            if content == "$$EOE" {
+                ended = true;
+                break;
+            }
+            if content.is_empty() { continue; }
+
+            let units: HorizonsUnits = units.unwrap_or(HorizonsUnits::KilometresPerSecond);
+
+            // CSV layout: one line per state
+            if let Some(columns) = &columns {
+                let fields: Vec<&str> = content.split(',').map(str::trim).collect();
+                let field = |name: &str| -> Result<f64, HorizonsError> {
+                    let position: usize = columns.iter().position(|column| column == name)
+                        .ok_or_else(|| HorizonsError::format(number, format!("missing column {name}")))?;
+                    let value: &str = fields.get(position).copied().unwrap_or("");
+                    value.parse().map_err(|_| HorizonsError::format(number, format!("invalid {name} '{value}'")))
+                };
+                let epoch: f64 = field("JDTDB")?;
+                let values: [f64; 6] = [field("X")?, field("Y")?, field("Z")?, field("VX")?, field("VY")?, field("VZ")?];
+                states.push(to_state(epoch, values, units, equatorial));
+                continue;
+            }
+
+            // Default layout: the epoch line is followed by the labelled values
+            if content.starts_with(|character: char| character.is_ascii_digit()) {
+                if let Some((epoch, values, line)) = record.take() {
+                    states.push(to_state(epoch, complete(values, line)?, units, equatorial));
+                }
+                let epoch: &str = content.split(|character: char| character == '=' || character.is_whitespace())
+                    .next()
+                    .unwrap_or("");
+                let epoch: f64 = epoch.parse()
+                    .map_err(|_| HorizonsError::format(number, format!("invalid Julian day '{epoch}'")))?;
+                record = Some((epoch, [None; 6], number));
+                continue;
+            }
+
+            let (_, values, _) = record.as_mut()
+                .ok_or_else(|| HorizonsError::format(number, "values before the first epoch"))?;
+            for (label, value) in labelled_values(content).map_err(|message| HorizonsError::format(number, message))? {
+                let slot: usize = match label {
+                    "X" => 0,
+                    "Y" => 1,
+                    "Z" => 2,
+                    "VX" => 3,
+                    "VY" => 4,
+                    "VZ" => 5,
+                    _ => continue
+                };
+                values[slot] = Some(value);
+            }
+        }
+
+        if !inside {
+            return Err(HorizonsError::format(text.lines().count().max(1), "missing $$SOE"));
+        }
+        if !ended {
+            return Err(HorizonsError::format(text.lines().count().max(1), "missing $$EOE"));
+        }
+        if let Some((epoch, values, line)) = record.take() {
+            let units: HorizonsUnits = units.unwrap_or(HorizonsUnits::KilometresPerSecond);
+            states.push(to_state(epoch, complete(values, line)?, units, equatorial));
+        }
+
+        Ok(Self {
+            target: target.unwrap_or_default(),
+            center: center.unwrap_or_default(),
+            center_id,
+            states
+        })
+    }
+
+    /// Get the state at the given epoch (Julian day, TDB)
+    pub fn state_at(&self, epoch: f64) -> Result<HorizonsState, HorizonsError> {
+        self.states.iter()
+            .find(|state| (state.epoch - epoch).abs() <= EPOCH_TOLERANCE)
+            .copied()
+            .ok_or(HorizonsError::MissingEpoch(epoch))
+    }
+
+    /// Get the state at the given epoch, or at the first epoch of the table if `None`
+    pub fn state(&self, epoch: Option<f64>) -> Result<HorizonsState, HorizonsError> {
+        match epoch {
+            Some(epoch) => self.state_at(epoch),
+            None => self.states.first().copied().ok_or_else(|| HorizonsError::format(1, "the table is empty"))
+        }
+    }
+
+    /// Create a star with the state of the target at the given epoch
+    pub fn star(&self, epoch: Option<f64>, mass: Mass, radius: Length) -> Result<Star, HorizonsError> {
+        let state: HorizonsState = self.state(epoch)?;
+        Ok(Star::new(self.target.clone(), state.velocity, state.coordinates, mass, radius))
+    }
+
+    /// Create a planet with the state of the target at the given epoch
+    pub fn planet(&self,
+                  epoch: Option<f64>,
+                  mass: Mass,
+                  radius: Length,
+                  planet_type: PlanetType) -> Result<Planet, HorizonsError> {
+        let state: HorizonsState = self.state(epoch)?;
+        Ok(Planet::new(self.target.clone(), state.velocity, state.coordinates, mass, radius, planet_type))
+    }
+}
+
+
+/// The name of a body without its ID and source, e.g. `Earth (399)   {source: DE441}` -> `Earth`
+fn body_name(value: &str) -> String {
+    let end: usize = value.find(['(', '{']).unwrap_or(value.len());
+    value[..end].trim().to_string()
+}
+
+
+/// Read the NAIF ID of a body from its header value, e.g. `10` from `Sun (10)`
+fn body_id(value: &str) -> Option<i32> {
+    let start: usize = value.find('(')? + 1;
+    let end: usize = start + value[start..].find(')')?;
+    value[start..end].trim().parse().ok()
+}
+
+
+/// Read the `LABEL= value` pairs of a line, e.g. ` X =-2.649903375682292E+07 Y = 1.327574173192497E+08`
+fn labelled_values(line: &str) -> Result<Vec<(&str, f64)>, String> {
+    let mut pairs: Vec<(&str, f64)> = Vec::new();
+    let mut rest: &str = line.trim_start();
+    while !rest.is_empty() {
+        let (label, after) = rest.split_once('=').ok_or_else(|| format!("expected 'LABEL= value', found '{rest}'"))?;
+        let after: &str = after.trim_start();
+        let end: usize = after.find(|character: char| {
+            !(character.is_ascii_digit() || matches!(character, '.' | '+' | '-' | 'E' | 'e'))
+        }).unwrap_or(after.len());
+
+        let number: &str = &after[..end];
+        let value: f64 = number.parse().map_err(|_| format!("invalid value '{number}' for {}", label.trim()))?;
+        pairs.push((label.trim(), value));
+        rest = after[end..].trim_start();
+    }
+    Ok(pairs)
+}
+
+
+/// Convert the values of a table row (`X`, `Y`, `Z`, `VX`, `VY`, `VZ`) to SI units in the frame of the simulation
+fn to_state(epoch: f64, values: [f64; 6], units: HorizonsUnits, equatorial: bool) -> HorizonsState {
+    let mut position: [f64; 3] = [values[0], values[1], values[2]];
+    let mut velocity: [f64; 3] = [values[3], values[4], values[5]];
+    if equatorial {
+        position = equatorial_to_ecliptic(position);
+        velocity = equatorial_to_ecliptic(velocity);
+    }
+    HorizonsState {
+        epoch,
+        coordinates: (ecliptic_to_simulation(position) * units.length()).to_point(),
+        velocity: ecliptic_to_simulation(velocity) * (units.length() / units.time())
+    }
+}
+
+
+/// Check that every coordinate of a record was given
+fn complete(values: [Option<f64>; 6], line: usize) -> Result<[f64; 6], HorizonsError> {
+    let mut complete: [f64; 6] = [0.0; 6];
+    for (i, value) in values.iter().enumerate() {
+        complete[i] = value.ok_or_else(|| HorizonsError::format(
+            line, format!("the state has no {}", ["X", "Y", "Z", "VX", "VY", "VZ"][i])
+        ))?;
+    }
+    Ok(complete)
+}
@@ -0,0 +1,73 @@
+use crate::*;
+
+
+/// # Integrator
+/// The scheme used to advance the celestials by one time step.
+/// ## Variants
+/// `Euler` - Move the bodies with their old velocities, then accelerate them (first order, not reversible, the default)\
+/// `Leapfrog` - Kick-drift-kick leapfrog (second order and symplectic).
+/// The scheme is symmetric in time, so a run with `-dt` retraces a run with `dt`.
+/// It needs one force calculation per step, the accelerations at the end of a step are reused
+/// at the start of the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integrator {
+    #[default]
+    Euler,
+    Leapfrog
+}
+
+
+impl Integrator {
+    /// Is the integration reversible (`dt` -> `-dt` retraces the trajectory)?
+    pub fn is_reversible(&self) -> bool { *self == Integrator::Leapfrog }
+
+    /// Parse an integrator, ignoring the case (e.g. `euler`, `Leapfrog`)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "euler" => Some(Self::Euler),
+            "leapfrog" => Some(Self::Leapfrog),
+            _ => None
+        }
+    }
+}
+
+
+impl std::fmt::Display for Integrator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Integrator::Euler => write!(f, "Euler"),
+            Integrator::Leapfrog => write!(f, "Leapfrog")
+        }
+    }
+}
+
+
+/// # Reversibility Report
+/// The result of integrating forward and then backward by the same number of steps.
+/// ## Attributes
+/// `steps: usize` - The number of steps in each direction\
+/// `position_error: Scalar` - The largest distance of a body from its original position\
+/// `velocity_error: Scalar` - The largest difference of a body's velocity from its original velocity\
+/// `worst: Option<String>` - The name of the body with the largest position error
+#[derive(Debug, Clone)]
+pub struct ReversibilityReport {
+    pub steps: usize,
+    pub position_error: Scalar,
+    pub velocity_error: Scalar,
+    pub worst: Option<String>
+}
+
+
+impl std::fmt::Display for ReversibilityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} steps forward and back: position error {} m, velocity error {} m/s",
+            self.steps, self.position_error, self.velocity_error
+        )?;
+        if let Some(worst) = &self.worst {
+            write!(f, " (worst: {worst})")?;
+        }
+        Ok(())
+    }
+}
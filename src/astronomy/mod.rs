@@ -1,7 +1,45 @@
 pub mod astronomical_simulation;
+pub mod checkpoint;
+pub mod clusters;
+pub mod events;
+pub mod frames;
+pub mod galaxy;
+pub mod generators;
+pub mod horizons;
+pub mod integrator;
+pub mod mpc;
 pub mod star;
+pub mod trajectory;
+pub mod observation;
+pub mod oem;
+pub mod orbital_elements;
+pub mod photometry;
 pub mod planet;
+pub mod scenario;
+pub mod sgp4;
+pub mod spacecraft;
+pub mod spk;
+pub mod tle;
 
 pub use astronomical_simulation::*;
+pub use checkpoint::*;
+pub use clusters::*;
+pub use events::*;
+pub use frames::*;
+pub use galaxy::*;
+pub use generators::*;
+pub use horizons::*;
+pub use integrator::*;
+pub use mpc::*;
 pub use star::*;
-pub use planet::*;
\ No newline at end of file
+pub use trajectory::*;
+pub use observation::*;
+pub use oem::*;
+pub use orbital_elements::*;
+pub use photometry::*;
+pub use planet::*;
+pub use scenario::*;
+pub use sgp4::*;
+pub use spacecraft::*;
+pub use spk::*;
+pub use tle::*;
\ No newline at end of file
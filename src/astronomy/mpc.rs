@@ -0,0 +1,536 @@
+use crate::*;
+use std::f64::consts::PI as PI_F64;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+
+/// The geometric albedo used to estimate the size of a minor planet from its absolute magnitude
+const ALBEDO: f64 = 0.14;
+
+/// The density of the minor bodies (kg / m³)
+const DENSITY: f64 = 2000.0;
+
+/// The radius of the bodies without an absolute magnitude, and of the comet nuclei (in metres)
+const DEFAULT_RADIUS: f64 = 1000.0;
+
+
+/// # MPC Error
+/// An error while reading a Minor Planet Center orbit file
+/// - `Io` - The file could not be read\
+/// - `Format` - The file is invalid, at the given line (starting from 1) and column (starting from 1)\
+/// - `MissingCenter` - The simulation has no body with the given name\
+/// - `MissingEpoch` - The date of the simulation is not known
+#[derive(Debug)]
+pub enum MpcError {
+    Io(std::io::Error),
+    Format { line: usize, column: usize, message: String },
+    MissingCenter(String),
+    MissingEpoch
+}
+
+
+impl Display for MpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Format { line, column, message } => write!(f, "line {line}, column {column}: {message}"),
+            Self::MissingCenter(name) => write!(f, "there is no body called {name}"),
+            Self::MissingEpoch => write!(f, "the date of the simulation is not known")
+        }
+    }
+}
+
+
+impl std::error::Error for MpcError {}
+
+
+impl From<std::io::Error> for MpcError {
+    fn from(error: std::io::Error) -> Self { Self::Io(error) }
+}
+
+
+/// # Orbit Class
+/// The dynamical class of a minor body.
+/// The classes of the minor planets are read from the flags of MPCORB (the orbit type);
+/// without the flags they are derived from the elements.
+/// `MainBelt` covers the unflagged orbits between `2.0 AU` and `3.3 AU`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitClass {
+    Atira,
+    Aten,
+    Apollo,
+    Amor,
+    MarsCrosser,
+    Hungaria,
+    Phocaea,
+    MainBelt,
+    Hilda,
+    JupiterTrojan,
+    Distant,
+    Comet,
+    Unclassified
+}
+
+
+impl OrbitClass {
+    /// Is the class one of the Near-Earth Object groups (Atira, Aten, Apollo, Amor)?
+    pub fn is_near_earth(&self) -> bool {
+        matches!(self, Self::Atira | Self::Aten | Self::Apollo | Self::Amor)
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace(['-', '_'], " ").as_str() {
+            "atira" => Some(Self::Atira),
+            "aten" => Some(Self::Aten),
+            "apollo" => Some(Self::Apollo),
+            "amor" => Some(Self::Amor),
+            "mars crosser" => Some(Self::MarsCrosser),
+            "hungaria" => Some(Self::Hungaria),
+            "phocaea" => Some(Self::Phocaea),
+            "main belt" => Some(Self::MainBelt),
+            "hilda" => Some(Self::Hilda),
+            "jupiter trojan" | "trojan" => Some(Self::JupiterTrojan),
+            "distant" => Some(Self::Distant),
+            "comet" => Some(Self::Comet),
+            "unclassified" => Some(Self::Unclassified),
+            _ => None
+        }
+    }
+
+    /// The class of the orbit type in the low 6 bits of the MPCORB flags
+    fn from_orbit_type(orbit_type: u16, semi_major_axis: f64) -> Self {
+        match orbit_type {
+            1 => Self::Atira,
+            2 => Self::Aten,
+            3 => Self::Apollo,
+            4 => Self::Amor,
+            5 => Self::MarsCrosser,
+            6 => Self::Hungaria,
+            7 => Self::Phocaea,
+            8 => Self::Hilda,
+            9 => Self::JupiterTrojan,
+            10 => Self::Distant,
+            _ if (2.0..=3.3).contains(&semi_major_axis) => Self::MainBelt,
+            _ => Self::Unclassified
+        }
+    }
+
+    /// The class of an orbit (the distances in AU, the inclination in degrees)
+    fn from_elements(semi_major_axis: f64, eccentricity: f64, inclination: f64) -> Self {
+        let perihelion: f64 = semi_major_axis * (1.0 - eccentricity);
+        let aphelion: f64 = semi_major_axis * (1.0 + eccentricity);
+        match semi_major_axis {
+            a if a < 1.0 && aphelion < 0.983 => Self::Atira,
+            a if a < 1.0 => Self::Aten,
+            _ if perihelion < 1.017 => Self::Apollo,
+            _ if perihelion < 1.3 => Self::Amor,
+            _ if perihelion < 1.665 => Self::MarsCrosser,
+            a if a > 1.78 && a < 2.0 && eccentricity < 0.18 && (16.0..=34.0).contains(&inclination) => Self::Hungaria,
+            a if (2.0..=3.3).contains(&a) => Self::MainBelt,
+            a if (3.7..=4.2).contains(&a) => Self::Hilda,
+            a if (5.05..=5.35).contains(&a) => Self::JupiterTrojan,
+            a if a > 5.5 => Self::Distant,
+            _ => Self::Unclassified
+        }
+    }
+}
+
+
+impl Display for OrbitClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name: &str = match self {
+            Self::Atira => "Atira",
+            Self::Aten => "Aten",
+            Self::Apollo => "Apollo",
+            Self::Amor => "Amor",
+            Self::MarsCrosser => "Mars-crosser",
+            Self::Hungaria => "Hungaria",
+            Self::Phocaea => "Phocaea",
+            Self::MainBelt => "Main belt",
+            Self::Hilda => "Hilda",
+            Self::JupiterTrojan => "Jupiter Trojan",
+            Self::Distant => "Distant",
+            Self::Comet => "Comet",
+            Self::Unclassified => "Unclassified"
+        };
+        write!(f, "{name}")
+    }
+}
+
+
+/// # MPC Orbit
+/// The heliocentric osculating elements of a minor planet or a comet (ecliptic and equinox J2000)
+/// ## Attributes
+/// `designation: String` - The packed designation of MPCORB, or the designation of the comet\
+/// `name: String` - The readable designation (e.g. `(433) Eros` or `1P/Halley`)\
+/// `class: OrbitClass` - The dynamical class\
+/// `absolute_magnitude: Option<f64>` - `H` of a minor planet, the total absolute magnitude of a comet\
+/// `epoch: f64` - The Julian day (TT) of the mean anomaly: the epoch of the elements,
+/// or the time of the perihelion passage of a comet\
+/// `mean_anomaly: f64` - The mean anomaly at the epoch (degrees; 0 for comets)\
+/// `perihelion_distance: f64` - In AU\
+/// `eccentricity: f64` - `1` or more for open orbits\
+/// `inclination`, `longitude_of_ascending_node`, `argument_of_perihelion` - In degrees
+#[derive(Debug, Clone, PartialEq)]
+pub struct MpcOrbit {
+    pub designation: String,
+    pub name: String,
+    pub class: OrbitClass,
+    pub absolute_magnitude: Option<f64>,
+    pub epoch: f64,
+    pub mean_anomaly: f64,
+    pub perihelion_distance: f64,
+    pub eccentricity: f64,
+    pub inclination: f64,
+    pub longitude_of_ascending_node: f64,
+    pub argument_of_perihelion: f64
+}
+
+
+impl MpcOrbit {
+    /// Calculate the state relative to the Sun at the given Julian day (TT) with Keplerian motion,
+    /// in SI units and in the frame of the simulation
+    /// ## Params
+    /// - `julian_day: f64` - The time of the state
+    /// - `gravitational_parameter: Scalar` - `GM` of the Sun
+    pub fn state(&self, julian_day: f64, gravitational_parameter: Scalar) -> (Vector, Vector) {
+        let q: f64 = self.perihelion_distance * AU.value.to_f64();
+        let mu: f64 = gravitational_parameter.to_f64();
+        let dt: f64 = (julian_day - self.epoch) * DAY.value.to_f64();
+        let inclination: f64 = self.inclination.to_radians();
+        let node: f64 = self.longitude_of_ascending_node.to_radians();
+        let perihelion: f64 = self.argument_of_perihelion.to_radians();
+
+        if self.eccentricity == 1.0 {
+            // Barker's equation: tan(ν/2)³ / 3 + tan(ν/2) = sqrt(μ / 2q³) t
+            let w: f64 = 3.0 * (mu / (2.0 * q.powi(3))).sqrt() * dt;
+            let root: f64 = (w * w / 4.0 + 1.0).sqrt();
+            let s: f64 = (w / 2.0 + root).cbrt() + (w / 2.0 - root).cbrt();
+            return OrbitalElements::state_from_true_anomaly(
+                scalar!(2.0 * q), 1.0, inclination, node, perihelion, 2.0 * s.atan(), gravitational_parameter
+            );
+        }
+
+        let semi_major_axis: f64 = q / (1.0 - self.eccentricity);
+        let mean_motion: f64 = (mu / semi_major_axis.abs().powi(3)).sqrt();
+        let mean_anomaly: f64 = self.mean_anomaly.to_radians() + mean_motion * dt;
+        OrbitalElements::new(
+            scalar!(semi_major_axis),
+            self.eccentricity,
+            inclination,
+            node,
+            perihelion,
+            if self.eccentricity < 1.0 { mean_anomaly.rem_euclid(2.0 * PI_F64) } else { mean_anomaly }
+        ).to_state(gravitational_parameter)
+    }
+
+    /// The radius estimated from the absolute magnitude: `D = 1329 km / sqrt(albedo) * 10^(-H / 5)`
+    pub fn radius(&self) -> Length {
+        match (self.class, self.absolute_magnitude) {
+            (OrbitClass::Comet, _) | (_, None) => Length::from(DEFAULT_RADIUS),
+            (_, Some(magnitude)) => Length::from(1329e3 / ALBEDO.sqrt() * 10f64.powf(-magnitude / 5.0) / 2.0)
+        }
+    }
+
+    /// Create the body around the Sun at the given Julian day (TT)
+    pub fn body(&self, sun: &PointBody, julian_day: f64, test_particle: bool) -> Planet {
+        let (position, velocity): (Vector, Vector) = self.state(julian_day, constants::G * sun.mass.value);
+        let radius: Length = self.radius();
+        let mass: Mass = Mass::new(scalar!(DENSITY * 4.0 / 3.0 * PI_F64) * radius.value.powi(3));
+
+        let mut body: Planet = Planet::new(
+            self.name.clone(),
+            sun.velocity + velocity,
+            sun.coordinates + position.to_point(),
+            mass,
+            radius,
+            PlanetType::Terrestrial
+        );
+        body.set_test_particle(test_particle);
+        body
+    }
+}
+
+
+/// # MPC Catalogue
+/// The orbits read from the fixed-width files of the Minor Planet Center:
+/// `MPCORB.DAT` (and its extracts, such as `NEA.txt`) for the minor planets,
+/// and `CometEls.txt` for the comets.
+/// The orbits can be filtered with the builder methods, which are applied in the order of the calls:
+/// ```text
+/// MpcCatalogue::load_mpcorb("MPCORB.DAT")?.near_earth().brighter_than(18.0).first(1000)
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MpcCatalogue {
+    pub orbits: Vec<MpcOrbit>
+}
+
+
+impl MpcCatalogue {
+    /// Read the minor planets of an MPCORB file
+    pub fn load_mpcorb(path: impl AsRef<Path>) -> Result<Self, MpcError> {
+        Self::parse_mpcorb(&std::fs::read_to_string(path)?)
+    }
+
+    /// Read the comets of a `CometEls.txt` file
+    pub fn load_comets(path: impl AsRef<Path>) -> Result<Self, MpcError> {
+        Self::parse_comets(&std::fs::read_to_string(path)?)
+    }
+
+    /// Read the minor planets of the MPCORB format.
+    /// The header of the file ends with a line of dashes, and the blank lines are skipped.
+    pub fn parse_mpcorb(text: &str) -> Result<Self, MpcError> {
+        let lines: Vec<(usize, &str)> = text.lines().enumerate().map(|(n, line)| (n + 1, line)).collect();
+        let start: usize = lines.iter()
+            .position(|(_, line)| line.starts_with("-----"))
+            .map_or(0, |position| position + 1);
+
+        let mut orbits: Vec<MpcOrbit> = Vec::new();
+        for (number, line) in lines[start..].iter() {
+            if line.trim().is_empty() { continue; }
+            orbits.push(parse_minor_planet(&Record { number: *number, text: line })?);
+        }
+        Ok(Self { orbits })
+    }
+
+    /// Read the comets of the `CometEls.txt` format, one comet per line
+    pub fn parse_comets(text: &str) -> Result<Self, MpcError> {
+        let mut orbits: Vec<MpcOrbit> = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            if line.trim().is_empty() { continue; }
+            orbits.push(parse_comet(&Record { number: n + 1, text: line })?);
+        }
+        Ok(Self { orbits })
+    }
+
+    /// Add the orbits of another catalogue
+    pub fn merge(mut self, other: Self) -> Self {
+        self.orbits.extend(other.orbits);
+        self
+    }
+
+    /// Only keep the orbits of the given classes
+    pub fn only(mut self, classes: &[OrbitClass]) -> Self {
+        self.orbits.retain(|orbit| classes.contains(&orbit.class));
+        self
+    }
+
+    /// Only keep the Near-Earth Objects (Atira, Aten, Apollo and Amor)
+    pub fn near_earth(mut self) -> Self {
+        self.orbits.retain(|orbit| orbit.class.is_near_earth());
+        self
+    }
+
+    /// Only keep the bodies with an absolute magnitude of at most `magnitude` (the larger bodies)
+    pub fn brighter_than(mut self, magnitude: f64) -> Self {
+        self.orbits.retain(|orbit| orbit.absolute_magnitude.is_some_and(|h| h <= magnitude));
+        self
+    }
+
+    /// Only keep the first `count` orbits
+    pub fn first(mut self, count: usize) -> Self {
+        self.orbits.truncate(count);
+        self
+    }
+
+    /// Create the bodies around the Sun at the given Julian day (TT)
+    pub fn bodies(&self, sun: &PointBody, julian_day: f64, test_particles: bool) -> Vec<Planet> {
+        self.orbits.iter().map(|orbit| orbit.body(sun, julian_day, test_particles)).collect()
+    }
+}
+
+
+/// A line of an MPC file, with the fixed columns of the format
+struct Record<'a> {
+    number: usize,
+    text: &'a str
+}
+
+
+impl Record<'_> {
+    fn error(&self, column: usize, message: impl ToString) -> MpcError {
+        MpcError::Format { line: self.number, column, message: message.to_string() }
+    }
+
+    /// The trimmed text between the columns `first` and `last` (starting from 1, inclusive),
+    /// empty if the line is shorter
+    fn text(&self, first: usize, last: usize) -> &str {
+        self.text.get(first - 1..last.min(self.text.len())).unwrap_or_default().trim()
+    }
+
+    fn number(&self, first: usize, last: usize) -> Result<f64, MpcError> {
+        let text: &str = self.text(first, last);
+        text.parse::<f64>().map_err(|_| self.error(first, format!("invalid number '{text}'")))
+    }
+
+    fn optional(&self, first: usize, last: usize) -> Result<Option<f64>, MpcError> {
+        if self.text(first, last).is_empty() { return Ok(None); }
+        self.number(first, last).map(Some)
+    }
+}
+
+
+fn parse_minor_planet(record: &Record) -> Result<MpcOrbit, MpcError> {
+    if !record.text.is_ascii() {
+        return Err(record.error(1, "the line is not ASCII"));
+    }
+    if record.text.len() < 103 {
+        return Err(record.error(record.text.len() + 1, "the line is shorter than 103 columns"));
+    }
+
+    let designation: String = record.text(1, 7).to_string();
+    let epoch: f64 = unpack_epoch(record.text(21, 25)).ok_or_else(|| record.error(21, "invalid packed epoch"))?;
+    let eccentricity: f64 = record.number(71, 79)?;
+    let semi_major_axis: f64 = record.number(93, 103)?;
+    let inclination: f64 = record.number(60, 68)?;
+
+    let flags: &str = record.text(162, 165);
+    let class: OrbitClass = if flags.is_empty() {
+        OrbitClass::from_elements(semi_major_axis, eccentricity, inclination)
+    } else {
+        let flags: u16 = u16::from_str_radix(flags, 16).map_err(|_| record.error(162, format!("invalid flags '{flags}'")))?;
+        OrbitClass::from_orbit_type(flags & 0x3f, semi_major_axis)
+    };
+
+    let readable: &str = record.text(167, 194);
+    Ok(MpcOrbit {
+        name: if readable.is_empty() { designation.clone() } else { readable.to_string() },
+        designation,
+        class,
+        absolute_magnitude: record.optional(9, 13)?,
+        epoch,
+        mean_anomaly: record.number(27, 35)?,
+        perihelion_distance: semi_major_axis * (1.0 - eccentricity),
+        eccentricity,
+        inclination,
+        longitude_of_ascending_node: record.number(49, 57)?,
+        argument_of_perihelion: record.number(38, 46)?
+    })
+}
+
+
+fn parse_comet(record: &Record) -> Result<MpcOrbit, MpcError> {
+    if !record.text.is_ascii() {
+        return Err(record.error(1, "the line is not ASCII"));
+    }
+    if record.text.len() < 79 {
+        return Err(record.error(record.text.len() + 1, "the line is shorter than 79 columns"));
+    }
+
+    let year: f64 = record.number(15, 18)?;
+    let month: f64 = record.number(20, 21)?;
+    let day: f64 = record.number(23, 29)?;
+    if !(1.0..=12.0).contains(&month) {
+        return Err(record.error(20, format!("invalid month {month}")));
+    }
+
+    let designation: String = format!("{}{}", record.text(1, 5), record.text(6, 12));
+    let name: &str = record.text(103, 158);
+    Ok(MpcOrbit {
+        name: if name.is_empty() { designation.clone() } else { name.to_string() },
+        designation,
+        class: OrbitClass::Comet,
+        absolute_magnitude: record.optional(92, 95)?,
+        epoch: julian_day(year as i32, month as u32, day),
+        mean_anomaly: 0.0,
+        perihelion_distance: record.number(31, 39)?,
+        eccentricity: record.number(42, 49)?,
+        inclination: record.number(72, 79)?,
+        longitude_of_ascending_node: record.number(62, 69)?,
+        argument_of_perihelion: record.number(52, 59)?
+    })
+}
+
+
+/// The Julian day of a packed MPC date, such as `K24AH` (2024 October 17.0)
+fn unpack_epoch(packed: &str) -> Option<f64> {
+    let digit = |character: char| -> Option<u32> {
+        match character {
+            '1'..='9' => character.to_digit(10),
+            'A'..='V' => Some(character as u32 - 'A' as u32 + 10),
+            _ => None
+        }
+    };
+
+    let characters: Vec<char> = packed.chars().collect();
+    if characters.len() != 5 { return None; }
+    let century: i32 = match characters[0] {
+        'I' => 1800,
+        'J' => 1900,
+        'K' => 2000,
+        _ => return None
+    };
+    let year: i32 = century + packed.get(1..3)?.parse::<i32>().ok()?;
+    let month: u32 = digit(characters[3]).filter(|month| *month <= 12)?;
+    let day: u32 = digit(characters[4])?;
+    Some(julian_day(year, month, day as f64))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use super::unpack_epoch;
+
+    /// An MPCORB line of (433) Eros, with every field at its column (starting from 1)
+    fn eros() -> String {
+        let mut line: Vec<u8> = vec![b' '; 202];
+        let fields: [(usize, &str); 11] = [
+            (1, "00433"), (9, "10.38"), (21, "K24AH"), (27, "310.55453"), (38, "178.92989"), (49, "304.28231"),
+            (60, "10.82831"), (71, "0.2228359"), (93, "1.4581461"), (162, "0004"), (167, "(433) Eros")
+        ];
+        for (column, text) in fields.iter() {
+            line[column - 1..column - 1 + text.len()].copy_from_slice(text.as_bytes());
+        }
+        String::from_utf8(line).unwrap()
+    }
+
+    #[test]
+    fn packed_epochs_are_unpacked() {
+        assert_eq!(unpack_epoch("K24AH"), Some(2_460_600.5));
+        assert_eq!(unpack_epoch("J9611"), Some(2_450_083.5));
+        assert_eq!(unpack_epoch("K2555"), Some(2_460_800.5));
+        for packed in ["K24AW", "K24D1", "L24AH", "K24A", "K2xAH"] {
+            assert_eq!(unpack_epoch(packed), None, "{packed}");
+        }
+    }
+
+    #[test]
+    fn mpcorb_columns_are_read() {
+        let text: String = format!("Header\n---------\n\n{}\n", eros());
+        let catalogue: MpcCatalogue = MpcCatalogue::parse_mpcorb(&text).unwrap();
+        assert_eq!(catalogue.orbits, vec![MpcOrbit {
+            designation: String::from("00433"),
+            name: String::from("(433) Eros"),
+            class: OrbitClass::Amor,
+            absolute_magnitude: Some(10.38),
+            epoch: 2_460_600.5,
+            mean_anomaly: 310.55453,
+            perihelion_distance: 1.4581461 * (1.0 - 0.2228359),
+            eccentricity: 0.2228359,
+            inclination: 10.82831,
+            longitude_of_ascending_node: 304.28231,
+            argument_of_perihelion: 178.92989
+        }]);
+
+        // The errors point at the column of the field
+        let mut broken: String = eros();
+        broken.replace_range(70..79, "0.22x8359");
+        assert!(matches!(MpcCatalogue::parse_mpcorb(&broken), Err(MpcError::Format { line: 1, column: 71, .. })));
+        assert!(matches!(MpcCatalogue::parse_mpcorb(&eros()[..100]), Err(MpcError::Format { line: 1, column: 101, .. })));
+    }
+
+    #[test]
+    fn minor_bodies_are_added_at_the_current_date() {
+        let catalogue: MpcCatalogue = MpcCatalogue::parse_mpcorb(&eros()).unwrap();
+        let mut simulation: AstronomicalSimulation = astronomical_simulation!(3600, vec![Box::new(star!("Sun", 0, 0, SOLAR_MASS, SOLAR_RADIUS))]);
+        assert!(matches!(simulation.add_minor_bodies(&catalogue, None, true), Err(MpcError::MissingEpoch)));
+
+        simulation.set_epoch(Some(2_460_600.5));
+        simulation.set_time(scalar!(86_400 * 10));
+        assert_eq!(simulation.add_minor_bodies(&catalogue, None, true).unwrap(), 1);
+        let sun: PointBody = simulation.get_celestials()[0].point_body().clone();
+        let expected: Planet = catalogue.bodies(&sun, 2_460_610.5, true).remove(0);
+        assert_eq!(simulation.get_celestials()[1].point_body().coordinates, expected.coordinates());
+    }
+}
@@ -0,0 +1,262 @@
+use crate::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+
+/// Milliarcseconds in a radian
+const MILLIARCSECONDS_PER_RADIAN: f64 = 180.0 / std::f64::consts::PI * 3600.0 * 1000.0;
+
+
+/// Calculate the barycenter of the given celestials.
+/// The result is a `PointBody` with the total mass,
+/// the coordinates of the centre of mass and its velocity.
+pub fn barycenter(celestials: &[Box<dyn Celestial + Sync>]) -> PointBody {
+    let mut mass: Mass = Mass::new(ZERO);
+    let mut position: Vector = NULL_VECTOR;
+    let mut momentum: Vector = NULL_VECTOR;
+
+    for celestial in celestials.iter() {
+        let body: &PointBody = celestial.point_body();
+        mass += body.mass;
+        position += Vector::from_point(body.coordinates) * body.mass.value;
+        momentum += body.momentum();
+    }
+
+    if mass.value == ZERO {
+        return PointBody::new(mass, NULL_VECTOR, ORIGO, ZERO);
+    }
+    PointBody::new(mass, momentum / mass.value, (position / mass.value).to_point(), ZERO)
+}
+
+
+/// Calculate the kinetic and the gravitational potential energy of the given celestials.
+/// Only the massive bodies are counted, the test particles do not attract anything,
+/// so their energy is not conserved. The static potentials are not included either.
+pub fn energy(celestials: &[Box<dyn Celestial + Sync>]) -> (Energy, Energy) {
+    let massive: Vec<&PointBody> = celestials.iter()
+        .map(|celestial| celestial.point_body())
+        .filter(|body| !body.test_particle)
+        .collect();
+
+    let mut kinetic: Summation = Summation::new();
+    let mut potential: Summation = Summation::new();
+    for (i, a) in massive.iter().enumerate() {
+        kinetic.add(a.kinetic_energy().value);
+        for b in massive[i + 1..].iter() {
+            potential.add(-constants::G * a.mass.value * b.mass.value / a.coordinates.distance(&b.coordinates));
+        }
+    }
+    (Energy::new(kinetic.total()), Energy::new(potential.total()))
+}
+
+
+/// # Observer
+/// A distant observer looking at the simulated system.
+/// ## Attributes
+/// `direction: Vector` - The unit vector pointing from the system towards the observer\
+/// `distance: Scalar` - The distance between the system and the observer
+/// ## Sky plane
+/// The sky plane is perpendicular to `direction`.
+/// Its first axis is the projection of the simulation's `x` axis
+/// (or `z`, if the observer looks along `x`), the second axis completes a right-handed frame.
+#[derive(Debug, Copy, Clone)]
+pub struct Observer {
+    pub direction: Vector,
+    pub distance: Scalar
+}
+
+
+impl Observer {
+    pub fn new(direction: Vector, distance: Scalar) -> Self {
+        Self { direction: direction.unit(), distance }
+    }
+
+    /// The two unit vectors spanning the sky plane
+    pub fn sky_axes(&self) -> (Vector, Vector) {
+        let reference: Vector = if self.direction.point.x.abs() > scalar!(0.9) {
+            vector!(0, 0, 1)
+        } else {
+            vector!(1, 0, 0)
+        };
+        let first: Vector = (reference - self.direction * reference.dot(&self.direction)).unit();
+        let second: Vector = (self.direction * first).unit();
+        (first, second)
+    }
+
+    /// Calculate the line-of-sight velocity of a body relative to the barycenter.
+    /// Positive values mean the body is moving away from the observer.
+    pub fn radial_velocity(&self, body: &PointBody, barycenter: &PointBody) -> Scalar {
+        -(body.velocity - barycenter.velocity).dot(&self.direction)
+    }
+
+    /// Calculate the displacement of a body from the barycenter in the sky plane (in metres)
+    pub fn sky_displacement(&self, body: &PointBody, barycenter: &PointBody) -> [Scalar; 2] {
+        let offset: Vector = Vector::from_point(body.coordinates - barycenter.coordinates);
+        let (first, second) = self.sky_axes();
+        [offset.dot(&first), offset.dot(&second)]
+    }
+
+    /// Calculate the angular displacement of a body from the barycenter in the sky plane (in radians)
+    pub fn astrometric_offset(&self, body: &PointBody, barycenter: &PointBody) -> [f64; 2] {
+        let [x, y] = self.sky_displacement(body, barycenter);
+        [(x / self.distance).to_f64().atan(), (y / self.distance).to_f64().atan()]
+    }
+}
+
+
+/// # Wobble Sample
+/// The observable motion of a star at a given time
+/// ## Attributes
+/// `radial_velocity: Scalar` - Line-of-sight velocity (m/s), positive when receding\
+/// `displacement: [Scalar; 2]` - Displacement in the sky plane (m)\
+/// `offset: [f64; 2]` - Angular displacement in the sky plane (rad)
+#[derive(Debug, Clone)]
+pub struct WobbleSample {
+    pub time: Scalar,
+    pub star: String,
+    pub radial_velocity: Scalar,
+    pub displacement: [Scalar; 2],
+    pub offset: [f64; 2]
+}
+
+
+/// # Stellar Wobble
+/// Records the radial velocity and the astrometric wobble of the stars of the simulation,
+/// as seen by an `Observer`. Both are measured relative to the barycenter of the system.
+/// The light travel time across the system is neglected.
+#[derive(Debug, Clone)]
+pub struct StellarWobble {
+    pub observer: Observer,
+    pub stars: Option<Vec<String>>,
+    pub samples: Vec<WobbleSample>
+}
+
+
+impl StellarWobble {
+    /// Create a new recorder for every star of the simulation
+    pub fn new(observer: Observer) -> Self {
+        Self { observer, stars: None, samples: Vec::new() }
+    }
+
+    /// Only record the stars with the given names
+    pub fn only(mut self, stars: Vec<String>) -> Self {
+        self.stars = Some(stars);
+        self
+    }
+
+    /// Record the stars at the given time
+    pub fn record(&mut self, time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) {
+        let center: PointBody = barycenter(celestials);
+
+        for celestial in celestials.iter().filter(|celestial| celestial.is_star()) {
+            let name: String = celestial.get_name();
+            if let Some(stars) = &self.stars {
+                if !stars.contains(&name) { continue; }
+            }
+
+            let body: &PointBody = celestial.point_body();
+            self.samples.push(
+                WobbleSample {
+                    time,
+                    star: name,
+                    radial_velocity: self.observer.radial_velocity(body, &center),
+                    displacement: self.observer.sky_displacement(body, &center),
+                    offset: self.observer.astrometric_offset(body, &center)
+                }
+            );
+        }
+    }
+
+    /// Record the stars at the current state of the simulation
+    pub fn record_simulation(&mut self, simulation: &AstronomicalSimulation) {
+        self.record(simulation.get_time(), simulation.get_celestials());
+    }
+
+    /// Add Gaussian measurement noise to the recorded samples.
+    /// `radial_velocity` is in m/s, `angle` is in radians.
+    /// The same seed always results in the same noise.
+    pub fn add_noise(&mut self, radial_velocity: Scalar, angle: f64, seed: u64) {
+        let mut random: Random = Random::new(seed);
+        for sample in self.samples.iter_mut() {
+            sample.radial_velocity += scalar!(random.normal(0.0, radial_velocity.to_f64()));
+            sample.offset[0] += random.normal(0.0, angle);
+            sample.offset[1] += random.normal(0.0, angle);
+        }
+    }
+
+    /// Write the samples into a CSV file.
+    /// The radial velocities are in m/s, the angular offsets in milliarcseconds.
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut file: BufWriter<File> = BufWriter::new(File::create(path)?);
+        writeln!(file, "time,star,radial_velocity,x,y,offset_x,offset_y")?;
+        for sample in self.samples.iter() {
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{}",
+                sample.time.to_f64(),
+                sample.star,
+                sample.radial_velocity.to_f64(),
+                sample.displacement[0].to_f64(),
+                sample.displacement[1].to_f64(),
+                sample.offset[0] * MILLIARCSECONDS_PER_RADIAN,
+                sample.offset[1] * MILLIARCSECONDS_PER_RADIAN
+            )?;
+        }
+        file.flush()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const STAR_MASS: f64 = 2e30;
+    const PLANET_MASS: f64 = 2e27;
+    const SEPARATION: f64 = 1.5e11;
+
+    /// A star and a planet on circular orbits in the x-z plane around their barycenter at the origin.
+    /// The star starts on the -x side, moving towards -z.
+    fn two_body() -> AstronomicalSimulation {
+        let total: f64 = STAR_MASS + PLANET_MASS;
+        let speed: f64 = (G.to_f64() * total / SEPARATION).sqrt();
+        let star: Star = star!(
+            "Star", (0, 0, -speed * PLANET_MASS / total), (-SEPARATION * PLANET_MASS / total, 0, 0), STAR_MASS, 7e8
+        );
+        let planet: Planet = planet!(
+            "Planet", (0, 0, speed * STAR_MASS / total), (SEPARATION * STAR_MASS / total, 0, 0), PLANET_MASS, 7e7
+        );
+        let mut simulation: AstronomicalSimulation = astronomical_simulation!(3600, vec![Box::new(star), Box::new(planet)]);
+        simulation.set_integrator(Integrator::Leapfrog);
+        simulation
+    }
+
+    #[test]
+    fn wobble_matches_the_circular_orbit() {
+        let total: f64 = STAR_MASS + PLANET_MASS;
+        let period: f64 = 2.0 * std::f64::consts::PI * (SEPARATION.powi(3) / (G.to_f64() * total)).sqrt();
+        let distance: f64 = 3e17;
+
+        // Edge-on, from +z: the star starts moving away from the observer
+        let mut simulation: AstronomicalSimulation = two_body();
+        let mut wobble: StellarWobble = StellarWobble::new(Observer::new(vector!(0, 0, 1), scalar!(distance)));
+        let steps: u64 = (period / 3600.0).ceil() as u64;
+        for _ in 0..=steps {
+            wobble.record_simulation(&simulation);
+            simulation.step();
+        }
+        assert!(wobble.samples[0].radial_velocity > ZERO);
+
+        // K = (2 pi G / P)^(1/3) m sin(i) / (M + m)^(2/3)
+        let semi_amplitude: f64 = (2.0 * std::f64::consts::PI * G.to_f64() / period).cbrt() * PLANET_MASS / total.powf(2.0 / 3.0);
+        let largest: f64 = wobble.samples.iter().map(|sample| sample.radial_velocity.to_f64()).fold(f64::MIN, f64::max);
+        assert!((largest / semi_amplitude - 1.0).abs() < 1e-3, "{largest} {semi_amplitude}");
+
+        // The astrometric amplitude is a* / d, along the first axis of the sky plane
+        let amplitude: f64 = SEPARATION * PLANET_MASS / total / distance;
+        let widest: f64 = wobble.samples.iter().map(|sample| sample.offset[0].abs()).fold(0.0, f64::max);
+        assert!((widest / amplitude - 1.0).abs() < 1e-3, "{widest} {amplitude}");
+        assert!(wobble.samples.iter().all(|sample| sample.offset[1].abs() < amplitude * 1e-6));
+    }
+}
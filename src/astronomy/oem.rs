@@ -0,0 +1,527 @@
+use crate::*;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+
+/// The version of the CCSDS Orbit Ephemeris Message standard written
+const OEM_VERSION: &str = "2.0";
+
+
+/// # OEM Output
+/// Exports the trajectories of the bodies as a CCSDS Orbit Ephemeris Message (OEM) in the KVN text format.
+/// Every body gets a segment with its metadata (object name, centre, reference frame, time system)
+/// and its states (`km` and `km/s`) in the `EME2000` frame, optionally followed by a covariance block.
+/// The samples are kept in memory, and the whole file is rewritten by `write`.
+/// ## Attributes
+/// `path: PathBuf` - The path of the file\
+/// `epoch: f64` - The Julian day of the time `0` of the simulation\
+/// `every: u64` - The cadence: a sample is taken after every `every` steps\
+/// `bodies: Option<Vec<String>>` - The names of the bodies to write (every body if `None`)\
+/// `center: Option<String>` - The body the states are relative to (the barycenter of the simulation if `None`)\
+/// `originator: String` - The creator of the message\
+/// `time_system: String` - The time system of the simulation (`TDB` by default)\
+/// `covariances: Vec<(String, [[f64; 6]; 6])>` - The covariance matrices (`km`, `km/s`) of the named bodies,
+/// given at the start of their segments
+pub struct OemOutput {
+    pub path: PathBuf,
+    pub epoch: f64,
+    pub every: u64,
+    pub bodies: Option<Vec<String>>,
+    pub center: Option<String>,
+    pub originator: String,
+    pub time_system: String,
+    pub covariances: Vec<(String, [[f64; 6]; 6])>,
+    segments: Vec<OemSegment>
+}
+
+
+/// The samples of one body
+struct OemSegment {
+    name: String,
+    times: Vec<f64>,
+    states: Vec<[f64; 6]>
+}
+
+
+impl OemOutput {
+    /// Export a sample after every step into the file at `path`.
+    /// The time `0` of the simulation is at the Julian day `epoch`.
+    pub fn new(path: impl AsRef<Path>, epoch: f64) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            epoch,
+            every: 1,
+            bodies: None,
+            center: None,
+            originator: String::from("PHYSICS SIMULATOR"),
+            time_system: String::from("TDB"),
+            covariances: Vec::new(),
+            segments: Vec::new()
+        }
+    }
+
+    /// Only take a sample after every `steps` steps
+    pub fn every(mut self, steps: u64) -> Self {
+        self.every = steps.max(1);
+        self
+    }
+
+    /// Only write the bodies with the given names
+    pub fn only(mut self, bodies: Vec<String>) -> Self {
+        self.bodies = Some(bodies);
+        self
+    }
+
+    /// Write the states relative to the body with the given name
+    pub fn center(mut self, center: impl ToString) -> Self {
+        self.center = Some(center.to_string());
+        self
+    }
+
+    pub fn originator(mut self, originator: impl ToString) -> Self {
+        self.originator = originator.to_string();
+        self
+    }
+
+    pub fn time_system(mut self, time_system: impl ToString) -> Self {
+        self.time_system = time_system.to_string();
+        self
+    }
+
+    /// Add a covariance block to the segment of the named body
+    pub fn covariance(mut self, body: impl ToString, matrix: [[f64; 6]; 6]) -> Self {
+        self.covariances.push((body.to_string(), matrix));
+        self
+    }
+
+    fn is_included(&self, name: &str) -> bool {
+        match &self.bodies {
+            Some(bodies) => bodies.iter().any(|body| body == name),
+            None => true
+        }
+    }
+
+    /// Take a sample if the step is on the cadence
+    pub fn record(&mut self, step: u64, time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) {
+        if step.is_multiple_of(self.every) {
+            self.sample(time, celestials);
+        }
+    }
+
+    /// Take a sample of the bodies.
+    /// If the centre body does not exist, the sample is skipped and the error is reported on the standard error.
+    pub fn sample(&mut self, time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) {
+        let center: PointBody = match &self.center {
+            Some(name) => match celestials.iter().find(|celestial| celestial.get_name() == *name) {
+                Some(celestial) => celestial.point_body().clone(),
+                None => {
+                    eprintln!("Could not sample the ephemeris: there is no body called {name}");
+                    return;
+                }
+            },
+            None => barycenter(celestials)
+        };
+
+        for celestial in celestials.iter() {
+            let name: String = celestial.get_name();
+            if !self.is_included(&name) || Some(&name) == self.center.as_ref() { continue; }
+
+            let body: &PointBody = celestial.point_body();
+            let position: [f64; 3] = ecliptic_to_equatorial(simulation_to_ecliptic(&Vector::from_point(body.coordinates - center.coordinates)));
+            let velocity: [f64; 3] = ecliptic_to_equatorial(simulation_to_ecliptic(&(body.velocity - center.velocity)));
+            let state: [f64; 6] = [position[0], position[1], position[2], velocity[0], velocity[1], velocity[2]]
+                .map(|value| value / 1000.0);
+
+            let segment: &mut OemSegment = match self.segments.iter().position(|segment| segment.name == name) {
+                Some(i) => &mut self.segments[i],
+                None => {
+                    self.segments.push(OemSegment { name, times: Vec::new(), states: Vec::new() });
+                    self.segments.last_mut().unwrap()
+                }
+            };
+            segment.times.push(time.to_f64());
+            segment.states.push(state);
+        }
+    }
+
+    /// Write the message with every sample taken so far
+    pub fn write(&self) -> std::io::Result<()> {
+        let mut file: BufWriter<File> = BufWriter::new(File::create(&self.path)?);
+        let center: String = match &self.center {
+            Some(center) => center.to_uppercase(),
+            None => String::from("SOLAR SYSTEM BARYCENTER")
+        };
+
+        writeln!(file, "CCSDS_OEM_VERS = {OEM_VERSION}")?;
+        writeln!(file, "CREATION_DATE = {}", format_date(unix_julian_day(), 0.0))?;
+        writeln!(file, "ORIGINATOR = {}", self.originator)?;
+
+        for segment in self.segments.iter() {
+            // The states are written in increasing time, also after a run backwards in time,
+            // and only the latest sample is kept of the ones at the same time
+            let mut order: Vec<usize> = (0..segment.times.len()).collect();
+            order.sort_by(|a, b| segment.times[*a].total_cmp(&segment.times[*b]).then(b.cmp(a)));
+            order.dedup_by(|later, earlier| segment.times[*later] == segment.times[*earlier]);
+            let (Some(first), Some(last)): (Option<&usize>, Option<&usize>) = (order.first(), order.last()) else {
+                continue;
+            };
+            let start: String = format_date(self.epoch, segment.times[*first]);
+
+            writeln!(file)?;
+            writeln!(file, "META_START")?;
+            writeln!(file, "OBJECT_NAME = {}", segment.name)?;
+            writeln!(file, "OBJECT_ID = {}", segment.name)?;
+            writeln!(file, "CENTER_NAME = {center}")?;
+            writeln!(file, "REF_FRAME = EME2000")?;
+            writeln!(file, "TIME_SYSTEM = {}", self.time_system)?;
+            writeln!(file, "START_TIME = {start}")?;
+            writeln!(file, "STOP_TIME = {}", format_date(self.epoch, segment.times[*last]))?;
+            writeln!(file, "META_STOP")?;
+            writeln!(file)?;
+
+            for i in order.iter() {
+                write!(file, "{}", format_date(self.epoch, segment.times[*i]))?;
+                for value in segment.states[*i].iter() {
+                    write!(file, " {value:.16e}")?;
+                }
+                writeln!(file)?;
+            }
+
+            if let Some((_, matrix)) = self.covariances.iter().find(|(body, _)| *body == segment.name) {
+                writeln!(file)?;
+                writeln!(file, "COVARIANCE_START")?;
+                writeln!(file, "EPOCH = {start}")?;
+                writeln!(file, "COV_REF_FRAME = EME2000")?;
+                for (row, values) in matrix.iter().enumerate() {
+                    let line: Vec<String> = values[..=row].iter().map(|value| format!("{value:.16e}")).collect();
+                    writeln!(file, "{}", line.join(" "))?;
+                }
+                writeln!(file, "COVARIANCE_STOP")?;
+            }
+        }
+        file.flush()
+    }
+}
+
+
+impl Drop for OemOutput {
+    fn drop(&mut self) {
+        if let Err(error) = self.write() {
+            eprintln!("Could not write the ephemeris {}: {error}", self.path.display());
+        }
+    }
+}
+
+
+/// # OEM Error
+/// An error while reading an Orbit Ephemeris Message
+/// - `Io` - The file could not be read
+/// - `Format` - The file is invalid, at the given line (starting from 1)
+#[derive(Debug)]
+pub enum OemError {
+    Io(std::io::Error),
+    Format { line: usize, message: String }
+}
+
+
+impl OemError {
+    fn format(line: usize, message: impl ToString) -> Self {
+        Self::Format { line, message: message.to_string() }
+    }
+}
+
+
+impl Display for OemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Format { line, message } => write!(f, "line {line}: {message}")
+        }
+    }
+}
+
+
+impl std::error::Error for OemError {}
+
+
+impl From<std::io::Error> for OemError {
+    fn from(error: std::io::Error) -> Self { Self::Io(error) }
+}
+
+
+/// # OEM State
+/// The state of an object at one epoch, in SI units and in the frame of the simulation
+/// ## Attributes
+/// `epoch: f64` - The Julian day, in the time system of the segment\
+/// `coordinates: Point` - The position relative to the centre body\
+/// `velocity: Vector` - The velocity relative to the centre body
+#[derive(Debug, Clone, Copy)]
+pub struct OemState {
+    pub epoch: f64,
+    pub coordinates: Point,
+    pub velocity: Vector
+}
+
+
+/// # OEM Object
+/// The states of one segment of a message
+/// ## Attributes
+/// `name: String` - The `OBJECT_NAME`\
+/// `center: String` - The `CENTER_NAME`, the body the states are relative to\
+/// `states: Vec<OemState>` - The states in the order of the segment
+#[derive(Debug, Clone)]
+pub struct OemObject {
+    pub name: String,
+    pub center: String,
+    pub states: Vec<OemState>
+}
+
+
+/// # OEM Ephemeris
+/// An Orbit Ephemeris Message read from the KVN text format, e.g. one written by `OemOutput`.
+/// The states (`km` and `km/s`) can be in the `EME2000`, `ICRF` or `GCRF` frame, which are rotated to the
+/// frame of the simulation. The accelerations and the covariance blocks are skipped.
+/// ## Attributes
+/// `objects: Vec<OemObject>` - The segments in the order of the message
+#[derive(Debug, Clone)]
+pub struct OemEphemeris {
+    pub objects: Vec<OemObject>
+}
+
+
+impl OemEphemeris {
+    /// Read a message from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, OemError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse the text of a message
+    pub fn parse(text: &str) -> Result<Self, OemError> {
+        let mut objects: Vec<OemObject> = Vec::new();
+        let mut meta: bool = false;
+        let mut covariance: bool = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let number: usize = index + 1;
+            let content: &str = line.trim();
+            if content.is_empty() || content.starts_with("COMMENT") { continue; }
+
+            match content {
+                "META_START" => {
+                    objects.push(OemObject { name: String::new(), center: String::new(), states: Vec::new() });
+                    meta = true;
+                    continue;
+                },
+                "META_STOP" => meta = false,
+                "COVARIANCE_START" => covariance = true,
+                "COVARIANCE_STOP" => covariance = false,
+                _ => ()
+            }
+            if covariance || content.ends_with("_STOP") { continue; }
+
+            // The metadata of the current segment, or the header before the first one
+            let object: &mut OemObject = match objects.last_mut() {
+                Some(object) if !meta => object,
+                _ => {
+                    let (key, value) = content.split_once('=')
+                        .ok_or_else(|| OemError::format(number, "expected 'KEY = value'"))?;
+                    let value: &str = value.trim();
+                    if let Some(object) = objects.last_mut() {
+                        match key.trim() {
+                            "OBJECT_NAME" => object.name = value.to_string(),
+                            "CENTER_NAME" => object.center = value.to_string(),
+                            "REF_FRAME" if !matches!(value.to_uppercase().as_str(), "EME2000" | "ICRF" | "GCRF") => {
+                                return Err(OemError::format(
+                                    number, format!("unsupported reference frame '{value}' (expected EME2000, ICRF or GCRF)")
+                                ));
+                            },
+                            _ => ()
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            // A state: the epoch, the position and the velocity, optionally followed by the acceleration
+            let fields: Vec<&str> = content.split_whitespace().collect();
+            if fields.len() != 7 && fields.len() != 10 {
+                return Err(OemError::format(number, "expected an epoch followed by 6 or 9 values"));
+            }
+            let epoch: f64 = parse_date(fields[0])
+                .ok_or_else(|| OemError::format(number, format!("invalid epoch '{}'", fields[0])))?;
+            let mut values: [f64; 6] = [0.0; 6];
+            for (value, field) in values.iter_mut().zip(fields[1..7].iter()) {
+                *value = field.parse().map_err(|_| OemError::format(number, format!("invalid value '{field}'")))?;
+            }
+            let position: [f64; 3] = equatorial_to_ecliptic([values[0], values[1], values[2]]);
+            let velocity: [f64; 3] = equatorial_to_ecliptic([values[3], values[4], values[5]]);
+            object.states.push(OemState {
+                epoch,
+                coordinates: (ecliptic_to_simulation(position) * scalar!(1000)).to_point(),
+                velocity: ecliptic_to_simulation(velocity) * scalar!(1000)
+            });
+        }
+
+        let last: usize = text.lines().count().max(1);
+        if meta {
+            return Err(OemError::format(last, "missing META_STOP"));
+        }
+        if covariance {
+            return Err(OemError::format(last, "missing COVARIANCE_STOP"));
+        }
+        if objects.is_empty() {
+            return Err(OemError::format(last, "missing META_START"));
+        }
+        Ok(Self { objects })
+    }
+}
+
+
+/// The Julian day (UTC) of the system clock
+fn unix_julian_day() -> f64 {
+    let seconds: f64 = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |duration| duration.as_secs_f64());
+    2_440_587.5 + seconds / 86_400.0
+}
+
+
+/// Format the time `seconds` after the Julian day `epoch` as `YYYY-MM-DDThh:mm:ss.ssssss`
+pub fn format_date(epoch: f64, seconds: f64) -> String {
+    // Count from the midnight before the epoch, in whole microseconds
+    let midnight: f64 = (epoch + 0.5).floor() - 0.5;
+    let microseconds: i64 = (((epoch - midnight) * 86_400.0 + seconds) * 1e6).round() as i64;
+    let days: i64 = microseconds.div_euclid(86_400_000_000);
+    let microseconds: i64 = microseconds.rem_euclid(86_400_000_000);
+
+    // The calendar date of the day (Meeus, Astronomical Algorithms, chapter 7)
+    let z: i64 = (midnight + 0.5) as i64 + days;
+    let a: i64 = if z < 2_299_161 { z } else {
+        let alpha: i64 = ((z as f64 - 1_867_216.25) / 36_524.25).floor() as i64;
+        z + 1 + alpha - alpha.div_euclid(4)
+    };
+    let b: i64 = a + 1524;
+    let years: i64 = ((b as f64 - 122.1) / 365.25).floor() as i64;
+    let d: i64 = (365.25 * years as f64).floor() as i64;
+    let e: i64 = ((b - d) as f64 / 30.6001).floor() as i64;
+    let day: i64 = b - d - (30.6001 * e as f64).floor() as i64;
+    let month: i64 = if e < 14 { e - 1 } else { e - 13 };
+    let year: i64 = if month > 2 { years - 4716 } else { years - 4715 };
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:06}",
+        microseconds / 3_600_000_000,
+        microseconds / 60_000_000 % 60,
+        microseconds / 1_000_000 % 60,
+        microseconds % 1_000_000
+    )
+}
+
+
+/// Read a date `YYYY-MM-DDThh:mm:ss.ssssss` (or `YYYY-DDDThh:mm:ss` with the day of the year) as a Julian day.
+/// Like in `format_date`, the dates before 1582-10-15 are in the Julian calendar.
+pub fn parse_date(text: &str) -> Option<f64> {
+    let (date, time) = text.trim().trim_end_matches('Z').split_once('T')?;
+    let mut parts = date.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let (month, day): (i64, i64) = match (parts.next()?, parts.next()) {
+        (month, Some(day)) => (month.parse().ok()?, day.parse().ok()?),
+        // The days of the year count on from the first of January
+        (day, None) => (1, day.parse().ok()?)
+    };
+    let mut clock = time.split(':');
+    let hours: f64 = clock.next()?.parse().ok()?;
+    let minutes: f64 = clock.next()?.parse().ok()?;
+    let seconds: f64 = clock.next()?.parse().ok()?;
+    if parts.next().is_some() || clock.next().is_some() || !(1..=12).contains(&month) || day < 1 {
+        return None;
+    }
+
+    // The Julian day of the midnight (Meeus, Astronomical Algorithms, chapter 7)
+    let (year, month): (i64, i64) = if month <= 2 { (year - 1, month + 12) } else { (year, month) };
+    let julian: i64 = (365.25 * (year + 4716) as f64).floor() as i64 + (30.6001 * (month + 1) as f64).floor() as i64 + day - 1524;
+    let century: i64 = year.div_euclid(100);
+    let gregorian: i64 = julian + 2 - century + century.div_euclid(4);
+    let midnight: f64 = if gregorian >= 2_299_161 { gregorian } else { julian } as f64 - 0.5;
+    Some(midnight + (hours * 3600.0 + minutes * 60.0 + seconds) / 86_400.0)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn dates_are_read_in_both_calendars() {
+        assert_eq!(parse_date("2000-01-01T12:00:00.000000"), Some(2_451_545.0));
+        assert_eq!(parse_date("2000-032T00:00:00"), Some(2_451_575.5));
+        assert_eq!(parse_date("1582-10-04T00:00:00"), Some(2_299_159.5));
+        assert_eq!(parse_date("1582-10-15T00:00:00"), Some(2_299_160.5));
+        assert_eq!(parse_date("2000-13-01T00:00:00"), None);
+        for date in ["1066-10-14T09:00:00.000000", "2024-02-29T21:00:00.000000"] {
+            assert_eq!(format_date(parse_date(date).unwrap(), 0.0), date);
+        }
+    }
+
+    #[test]
+    fn written_ephemerides_are_read_back() {
+        let path: std::path::PathBuf = std::env::temp_dir().join(format!("read-back-{}.oem", std::process::id()));
+        let mut celestials: Vec<Box<dyn Celestial + Sync>> = vec![
+            Box::new(star!("Sun", 0, 0, 2e30, 7e8)),
+            Box::new(planet!("Earth", (1000, 29_780, -50), (1.496e11, 2e9, 3e7), 6e24, 6.4e6))
+        ];
+        let mut output: OemOutput = OemOutput::new(&path, 2_451_545.0).center("Sun");
+        output.sample(scalar!(0), &celestials);
+        celestials[1].point_body_mut().advance(Time::from(3600));
+        output.sample(scalar!(3600), &celestials);
+        output.write().unwrap();
+        let ephemeris: Result<OemEphemeris, OemError> = OemEphemeris::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let ephemeris: OemEphemeris = ephemeris.unwrap();
+        assert_eq!(ephemeris.objects.len(), 1);
+        let earth: &OemObject = &ephemeris.objects[0];
+        assert_eq!((earth.name.as_str(), earth.center.as_str()), ("Earth", "SUN"));
+        assert_eq!(earth.states.len(), 2);
+        assert!((earth.states[1].epoch - (2_451_545.0 + 1.0 / 24.0)).abs() < 1e-9);
+        let body: &PointBody = celestials[1].point_body();
+        assert!(earth.states[1].coordinates.distance(&body.coordinates) < scalar!(1e-2));
+        assert!((earth.states[1].velocity - body.velocity).magnitude() < scalar!(1e-9));
+    }
+
+    #[test]
+    fn dates_are_formatted_in_both_calendars() {
+        assert_eq!(format_date(2_451_545.0, 0.0), "2000-01-01T12:00:00.000000");
+        assert_eq!(format_date(2_451_545.0, -43_200.5), "1999-12-31T23:59:59.500000");
+        // The Julian calendar ends on 1582-10-04, the next day is 1582-10-15
+        assert_eq!(format_date(2_299_159.5, 0.0), "1582-10-04T00:00:00.000000");
+        assert_eq!(format_date(2_299_159.5, 86_400.0), "1582-10-15T00:00:00.000000");
+        assert_eq!(format_date(2_086_307.5, 0.0), "1000-01-01T00:00:00.000000");
+    }
+
+    #[test]
+    fn backward_runs_write_increasing_epochs() {
+        let path: std::path::PathBuf = std::env::temp_dir().join(format!("backward-{}.oem", std::process::id()));
+        let mut simulation: AstronomicalSimulation = astronomical_simulation!(-60, vec![
+            Box::new(star!("Sun", 0, 0, 2e30, 7e8)),
+            Box::new(planet!("Earth", (0, 0, 29_780), (1.496e11, 0, 0), 6e24, 6.4e6))
+        ]);
+        simulation.add_oem_output(OemOutput::new(&path, 2_451_545.0).only(vec![String::from("Earth")]));
+        simulation.run_for(3);
+        simulation.flush_trajectories().unwrap();
+        let text: Result<String, std::io::Error> = std::fs::read_to_string(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let text: String = text.unwrap();
+        let epochs: Vec<&str> = text.lines()
+            .filter(|line| line.starts_with(|character: char| character.is_ascii_digit()))
+            .map(|line| line.split_whitespace().next().unwrap())
+            .collect();
+        assert_eq!(epochs, [
+            "2000-01-01T11:57:00.000000", "2000-01-01T11:58:00.000000", "2000-01-01T11:59:00.000000", "2000-01-01T12:00:00.000000"
+        ]);
+        assert!(text.contains("START_TIME = 2000-01-01T11:57:00.000000\nSTOP_TIME = 2000-01-01T12:00:00.000000"));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::*;
+use std::f64::consts::PI as PI_F64;
+
+
+/// The tolerance of the Kepler equation solver
+const KEPLER_TOLERANCE: f64 = 1e-14;
+const KEPLER_ITERATIONS: u16 = 100;
+
+
+/// # Orbital Elements
+/// The Keplerian elements of an orbit, relative to the reference plane of the simulation
+/// (the `x`-`z` plane, with `y` pointing north).
+/// ## Attributes
+/// `semi_major_axis: Scalar` - Negative for hyperbolic orbits\
+/// `eccentricity: f64`\
+/// `inclination: f64` - In radians\
+/// `longitude_of_ascending_node: f64` - In radians\
+/// `argument_of_periapsis: f64` - In radians\
+/// `mean_anomaly: f64` - In radians
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OrbitalElements {
+    pub semi_major_axis: Scalar,
+    pub eccentricity: f64,
+    pub inclination: f64,
+    pub longitude_of_ascending_node: f64,
+    pub argument_of_periapsis: f64,
+    pub mean_anomaly: f64
+}
+
+
+impl OrbitalElements {
+    pub const fn new(semi_major_axis: Scalar,
+                     eccentricity: f64,
+                     inclination: f64,
+                     longitude_of_ascending_node: f64,
+                     argument_of_periapsis: f64,
+                     mean_anomaly: f64) -> Self {
+        Self {
+            semi_major_axis,
+            eccentricity,
+            inclination,
+            longitude_of_ascending_node,
+            argument_of_periapsis,
+            mean_anomaly
+        }
+    }
+
+    /// Calculate the elements of the orbit of `body` around `center`
+    pub fn from_bodies(body: &PointBody, center: &PointBody) -> Self {
+        Self::from_state(
+            &Vector::from_point(body.coordinates - center.coordinates),
+            &(body.velocity - center.velocity),
+            constants::G * (body.mass + center.mass).value
+        )
+    }
+
+    /// Calculate the elements from a relative position and velocity
+    /// ## Params
+    /// - `position: &Vector` - The position relative to the central body
+    /// - `velocity: &Vector` - The velocity relative to the central body
+    /// - `gravitational_parameter: Scalar` - `G(M + m)`
+    pub fn from_state(position: &Vector, velocity: &Vector, gravitational_parameter: Scalar) -> Self {
+        let r: [f64; 3] = simulation_to_ecliptic(position);
+        let v: [f64; 3] = simulation_to_ecliptic(velocity);
+        let mu: f64 = gravitational_parameter.to_f64();
+
+        let radius: f64 = norm(r);
+        let speed_squared: f64 = dot(v, v);
+        let h: [f64; 3] = cross(r, v);
+        let h_magnitude: f64 = norm(h);
+        let node: [f64; 3] = [-h[1], h[0], 0.0];
+        let node_magnitude: f64 = norm(node);
+
+        // Eccentricity vector
+        let radial_speed: f64 = dot(r, v);
+        let e_vector: [f64; 3] = [
+            ((speed_squared - mu / radius) * r[0] - radial_speed * v[0]) / mu,
+            ((speed_squared - mu / radius) * r[1] - radial_speed * v[1]) / mu,
+            ((speed_squared - mu / radius) * r[2] - radial_speed * v[2]) / mu
+        ];
+        let eccentricity: f64 = norm(e_vector);
+
+        let energy: f64 = speed_squared / 2.0 - mu / radius;
+        let semi_major_axis: f64 = -mu / (2.0 * energy);
+
+        let inclination: f64 = (h[2] / h_magnitude).clamp(-1.0, 1.0).acos();
+
+        let longitude_of_ascending_node: f64 = if node_magnitude > 0.0 {
+            node[1].atan2(node[0]).rem_euclid(2.0 * PI_F64)
+        } else {
+            0.0
+        };
+
+        // The argument of periapsis and the true anomaly, measured from the node
+        // (or from the x axis, if the orbit is not inclined)
+        let reference: [f64; 3] = if node_magnitude > 0.0 {
+            [node[0] / node_magnitude, node[1] / node_magnitude, 0.0]
+        } else {
+            [1.0, 0.0, 0.0]
+        };
+        let normal: [f64; 3] = [h[0] / h_magnitude, h[1] / h_magnitude, h[2] / h_magnitude];
+        let angle = |vector: [f64; 3]| -> f64 {
+            dot(cross(reference, vector), normal).atan2(dot(reference, vector))
+        };
+
+        let (argument_of_periapsis, true_anomaly): (f64, f64) = if eccentricity > 1e-12 {
+            let periapsis: f64 = angle(e_vector);
+            (periapsis.rem_euclid(2.0 * PI_F64), (angle(r) - periapsis).rem_euclid(2.0 * PI_F64))
+        } else {
+            (0.0, angle(r).rem_euclid(2.0 * PI_F64))
+        };
+
+        Self {
+            semi_major_axis: scalar!(semi_major_axis),
+            eccentricity,
+            inclination,
+            longitude_of_ascending_node,
+            argument_of_periapsis,
+            mean_anomaly: Self::mean_from_true_anomaly(true_anomaly, eccentricity)
+        }
+    }
+
+    /// Calculate the position and velocity relative to the central body
+    pub fn to_state(&self, gravitational_parameter: Scalar) -> (Vector, Vector) {
+        let true_anomaly: f64 = Self::true_from_mean_anomaly(self.mean_anomaly, self.eccentricity);
+        Self::state_from_true_anomaly(
+            self.semi_latus_rectum(),
+            self.eccentricity,
+            self.inclination,
+            self.longitude_of_ascending_node,
+            self.argument_of_periapsis,
+            true_anomaly,
+            gravitational_parameter
+        )
+    }
+
+    /// Calculate the position and velocity on a conic section at the given true anomaly.
+    /// Works for every kind of orbit, including parabolic ones.
+    pub fn state_from_true_anomaly(
+        semi_latus_rectum: Scalar,
+        eccentricity: f64,
+        inclination: f64,
+        longitude_of_ascending_node: f64,
+        argument_of_periapsis: f64,
+        true_anomaly: f64,
+        gravitational_parameter: Scalar
+    ) -> (Vector, Vector) {
+        let p: f64 = semi_latus_rectum.to_f64();
+        let mu: f64 = gravitational_parameter.to_f64();
+        let (sin_nu, cos_nu): (f64, f64) = true_anomaly.sin_cos();
+
+        // Position and velocity in the plane of the orbit
+        let radius: f64 = p / (1.0 + eccentricity * cos_nu);
+        let position: [f64; 3] = [radius * cos_nu, radius * sin_nu, 0.0];
+        let factor: f64 = (mu / p).sqrt();
+        let velocity: [f64; 3] = [-factor * sin_nu, factor * (eccentricity + cos_nu), 0.0];
+
+        // Rotate into the reference frame
+        let rotate = |v: [f64; 3]| -> [f64; 3] {
+            let (sin_o, cos_o): (f64, f64) = longitude_of_ascending_node.sin_cos();
+            let (sin_w, cos_w): (f64, f64) = argument_of_periapsis.sin_cos();
+            let (sin_i, cos_i): (f64, f64) = inclination.sin_cos();
+            [
+                (cos_o * cos_w - sin_o * sin_w * cos_i) * v[0] + (-cos_o * sin_w - sin_o * cos_w * cos_i) * v[1],
+                (sin_o * cos_w + cos_o * sin_w * cos_i) * v[0] + (-sin_o * sin_w + cos_o * cos_w * cos_i) * v[1],
+                (sin_w * sin_i) * v[0] + (cos_w * sin_i) * v[1]
+            ]
+        };
+
+        (ecliptic_to_simulation(rotate(position)), ecliptic_to_simulation(rotate(velocity)))
+    }
+
+    /// The semi-latus rectum of the orbit: `p = a(1 - e^2)`
+    pub fn semi_latus_rectum(&self) -> Scalar {
+        self.semi_major_axis * scalar!(1.0 - self.eccentricity.powi(2))
+    }
+
+    /// The distance of the periapsis from the central body
+    pub fn periapsis(&self) -> Scalar { self.semi_major_axis * scalar!(1.0 - self.eccentricity) }
+
+    /// The distance of the apoapsis from the central body (infinite for open orbits)
+    pub fn apoapsis(&self) -> Scalar {
+        if self.eccentricity >= 1.0 { return scalar!(f64::INFINITY); }
+        self.semi_major_axis * scalar!(1.0 + self.eccentricity)
+    }
+
+    /// The mean motion of the orbit (in radians per second)
+    pub fn mean_motion(&self, gravitational_parameter: Scalar) -> Scalar {
+        (gravitational_parameter / self.semi_major_axis.abs().powi(3)).sqrt()
+    }
+
+    /// The orbital period (infinite for open orbits)
+    pub fn period(&self, gravitational_parameter: Scalar) -> Scalar {
+        if self.eccentricity >= 1.0 { return scalar!(f64::INFINITY); }
+        scalar!(2.0 * PI_F64) / self.mean_motion(gravitational_parameter)
+    }
+
+    /// Advance the mean anomaly by `dt` seconds of Keplerian motion
+    pub fn propagate(&self, dt: Scalar, gravitational_parameter: Scalar) -> Self {
+        let mut elements: Self = *self;
+        let mean_anomaly: f64 = self.mean_anomaly + (self.mean_motion(gravitational_parameter) * dt).to_f64();
+        elements.mean_anomaly = if self.eccentricity < 1.0 {
+            mean_anomaly.rem_euclid(2.0 * PI_F64)
+        } else {
+            mean_anomaly
+        };
+        elements
+    }
+
+    /// Solve Kepler's equation for the true anomaly
+    pub fn true_from_mean_anomaly(mean_anomaly: f64, eccentricity: f64) -> f64 {
+        if eccentricity < 1.0 {
+            // M = E - e sin(E)
+            let mean_anomaly: f64 = mean_anomaly.rem_euclid(2.0 * PI_F64);
+            let mut anomaly: f64 = if eccentricity > 0.8 { PI_F64 } else { mean_anomaly };
+            for _ in 0..KEPLER_ITERATIONS {
+                let step: f64 = (anomaly - eccentricity * anomaly.sin() - mean_anomaly)
+                    / (1.0 - eccentricity * anomaly.cos());
+                anomaly -= step;
+                if step.abs() < KEPLER_TOLERANCE { break; }
+            }
+            2.0 * ((1.0 + eccentricity).sqrt() * (anomaly / 2.0).sin())
+                .atan2((1.0 - eccentricity).sqrt() * (anomaly / 2.0).cos())
+        } else {
+            // M = e sinh(H) - H
+            let mut anomaly: f64 = (2.0 * mean_anomaly / eccentricity).asinh();
+            for _ in 0..KEPLER_ITERATIONS {
+                let step: f64 = (eccentricity * anomaly.sinh() - anomaly - mean_anomaly)
+                    / (eccentricity * anomaly.cosh() - 1.0);
+                anomaly -= step;
+                if step.abs() < KEPLER_TOLERANCE { break; }
+            }
+            2.0 * (((eccentricity + 1.0) / (eccentricity - 1.0)).sqrt() * (anomaly / 2.0).tanh()).atan()
+        }
+    }
+
+    /// Calculate the mean anomaly from the true anomaly
+    pub fn mean_from_true_anomaly(true_anomaly: f64, eccentricity: f64) -> f64 {
+        if eccentricity < 1.0 {
+            let anomaly: f64 = 2.0 * ((1.0 - eccentricity).sqrt() * (true_anomaly / 2.0).sin())
+                .atan2((1.0 + eccentricity).sqrt() * (true_anomaly / 2.0).cos());
+            (anomaly - eccentricity * anomaly.sin()).rem_euclid(2.0 * PI_F64)
+        } else {
+            let anomaly: f64 = 2.0 * (((eccentricity - 1.0) / (eccentricity + 1.0)).sqrt() * (true_anomaly / 2.0).tan()).atanh();
+            eccentricity * anomaly.sinh() - anomaly
+        }
+    }
+}
+
+
+impl std::fmt::Display for OrbitalElements {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "a: {} m e: {:.6} i: {:.4}° Ω: {:.4}° ω: {:.4}° M: {:.4}°",
+            self.semi_major_axis,
+            self.eccentricity,
+            self.inclination.to_degrees(),
+            self.longitude_of_ascending_node.to_degrees(),
+            self.argument_of_periapsis.to_degrees(),
+            self.mean_anomaly.to_degrees()
+        )
+    }
+}
+
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 { a[0] * b[0] + a[1] * b[1] + a[2] * b[2] }
+
+fn norm(a: [f64; 3]) -> f64 { dot(a, a).sqrt() }
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// Check that the elements survive the conversion to a state and back
+    fn assert_round_trip(elements: OrbitalElements) {
+        let mu: Scalar = constants::G * SOLAR_MASS.value;
+        let (position, velocity) = elements.to_state(mu);
+        let back: OrbitalElements = OrbitalElements::from_state(&position, &velocity, mu);
+
+        let angle = |a: f64, b: f64| (a - b + std::f64::consts::PI).rem_euclid(2.0 * std::f64::consts::PI) - std::f64::consts::PI;
+        assert!(((back.semi_major_axis / elements.semi_major_axis).to_f64() - 1.0).abs() < 1e-9, "{back}");
+        assert!((back.eccentricity - elements.eccentricity).abs() < 1e-9, "{back}");
+        assert!((back.inclination - elements.inclination).abs() < 1e-9, "{back}");
+        assert!(angle(back.longitude_of_ascending_node, elements.longitude_of_ascending_node).abs() < 1e-9, "{back}");
+        assert!(angle(back.argument_of_periapsis, elements.argument_of_periapsis).abs() < 1e-9, "{back}");
+        assert!(angle(back.mean_anomaly, elements.mean_anomaly).abs() < 1e-9, "{back}");
+    }
+
+    #[test]
+    fn elliptic_elements_round_trip() {
+        assert_round_trip(OrbitalElements::new(scalar!(1.5e11), 0.3, 0.4, 1.0, 2.0, 0.5));
+        assert_round_trip(OrbitalElements::new(scalar!(4e11), 0.9, 2.5, 5.0, 0.1, 4.0));
+    }
+
+    #[test]
+    fn hyperbolic_elements_round_trip() {
+        assert_round_trip(OrbitalElements::new(scalar!(-1e11), 1.5, 0.4, 1.0, 2.0, 0.8));
+        assert_round_trip(OrbitalElements::new(scalar!(-3e10), 3.0, 1.2, 4.0, 5.5, -2.0));
+    }
+}
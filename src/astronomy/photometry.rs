@@ -0,0 +1,219 @@
+use crate::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+
+/// The default number of annuli the stellar disk is divided into
+const DEFAULT_RESOLUTION: usize = 1000;
+
+
+/// # Limb Darkening
+/// The intensity profile of a stellar disk, as a function of `mu = cos(theta)`,
+/// where `theta` is the angle between the line of sight and the normal of the surface.
+/// - `Uniform` - `I(mu) = 1`
+/// - `Quadratic` - `I(mu) = 1 - u1 (1 - mu) - u2 (1 - mu)^2`
+/// - `Nonlinear` - `I(mu) = 1 - sum(a_k (1 - mu^(k/2)))`, the four-parameter law of Claret (2000)
+#[derive(Debug, Copy, Clone)]
+pub enum LimbDarkening {
+    Uniform,
+    Quadratic { u1: f64, u2: f64 },
+    Nonlinear { a1: f64, a2: f64, a3: f64, a4: f64 }
+}
+
+
+impl LimbDarkening {
+    /// Calculate the relative intensity at the given `mu`
+    pub fn intensity(&self, mu: f64) -> f64 {
+        match *self {
+            Self::Uniform => 1.0,
+            Self::Quadratic { u1, u2 } => 1.0 - u1 * (1.0 - mu) - u2 * (1.0 - mu).powi(2),
+            Self::Nonlinear { a1, a2, a3, a4 } => {
+                let root: f64 = mu.sqrt();
+                1.0 - a1 * (1.0 - root)
+                    - a2 * (1.0 - mu)
+                    - a3 * (1.0 - mu * root)
+                    - a4 * (1.0 - mu.powi(2))
+            }
+        }
+    }
+
+    /// Calculate the relative intensity at the given distance from the centre of the disk
+    /// (in units of the stellar radius)
+    pub fn intensity_at(&self, r: f64) -> f64 {
+        self.intensity((1.0 - r.powi(2)).max(0.0).sqrt())
+    }
+}
+
+
+/// # Light Curve
+/// The brightness of a `Star`, as seen by a distant observer,
+/// while the other bodies of the simulation pass in front of it.
+/// ## Attributes
+/// `star: String` - The name of the observed star\
+/// `observer: Vector` - The direction pointing from the system towards the observer\
+/// `limb_darkening: LimbDarkening` - The intensity profile of the star\
+/// `samples: Vec<(Scalar, f64)>` - The recorded times and relative fluxes
+/// ## Method
+/// The stellar disk is divided into thin annuli.
+/// For each transiting body, the covered arc of every annulus is weighted by the intensity of the annulus.
+/// Overlapping transits are treated as independent.
+#[derive(Debug, Clone)]
+pub struct LightCurve {
+    pub star: String,
+    pub observer: Vector,
+    pub limb_darkening: LimbDarkening,
+    pub samples: Vec<(Scalar, f64)>,
+    resolution: usize
+}
+
+
+impl LightCurve {
+    pub fn new(star: impl ToString, observer: Vector, limb_darkening: LimbDarkening) -> Self {
+        Self {
+            star: star.to_string(),
+            observer: observer.unit(),
+            limb_darkening,
+            samples: Vec::new(),
+            resolution: DEFAULT_RESOLUTION
+        }
+    }
+
+    /// Set the number of annuli used for the integration over the stellar disk
+    pub fn set_resolution(&mut self, resolution: usize) { self.resolution = resolution.max(1); }
+
+    /// Calculate the flux of the star relative to its unobstructed flux.
+    /// Returns `None` if the star does not exist.
+    pub fn relative_flux(&self, celestials: &[Box<dyn Celestial + Sync>]) -> Option<f64> {
+        let star = celestials.iter().find(|celestial| celestial.get_name() == self.star)?;
+        let center: Point = star.point_body().coordinates;
+        let radius: Length = star.get_radius();
+
+        let total: f64 = self.integrate(0.0, 1.0, |_| 1.0);
+        let mut blocked: f64 = 0.0;
+
+        for celestial in celestials.iter() {
+            if celestial.get_name() == self.star { continue; }
+
+            // Only the bodies between the star and the observer block its light
+            let offset: Vector = Vector::from_point(celestial.point_body().coordinates - center);
+            let along: Scalar = offset.dot(&self.observer);
+            if along <= ZERO { continue; }
+
+            // Separation of the centres and planet radius in the sky plane, in stellar radii
+            let z: f64 = ((offset - self.observer * along).magnitude() / radius.value).to_f64();
+            let p: f64 = (celestial.get_radius() / radius).to_f64();
+            if z >= 1.0 + p { continue; }
+
+            blocked += self.integrate(
+                (z - p).max(0.0),
+                (z + p).min(1.0),
+                |r| Self::covered_fraction(r, z, p)
+            );
+        }
+
+        Some(1.0 - blocked / total)
+    }
+
+    /// Integrate the intensity of the disk between the radii `from` and `to`,
+    /// weighted by `weight(r)`
+    fn integrate(&self, from: f64, to: f64, weight: impl Fn(f64) -> f64) -> f64 {
+        if to <= from { return 0.0; }
+
+        // Use the same annulus width as a full-disk integration
+        let steps: usize = ((to - from) * self.resolution as f64).ceil().max(1.0) as usize;
+        let width: f64 = (to - from) / steps as f64;
+
+        (0..steps).map(|i| {
+            let r: f64 = from + (i as f64 + 0.5) * width;
+            self.limb_darkening.intensity_at(r) * weight(r) * 2.0 * std::f64::consts::PI * r * width
+        }).sum()
+    }
+
+    /// The fraction of the annulus of radius `r` covered by a disk of radius `p` at distance `z`
+    fn covered_fraction(r: f64, z: f64, p: f64) -> f64 {
+        if r + z <= p { return 1.0; }
+        if r >= z + p || r <= z - p { return 0.0; }
+        let cosine: f64 = (r.powi(2) + z.powi(2) - p.powi(2)) / (2.0 * r * z);
+        cosine.clamp(-1.0, 1.0).acos() / std::f64::consts::PI
+    }
+
+    /// Record the flux at the given time
+    pub fn record(&mut self, time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) {
+        if let Some(flux) = self.relative_flux(celestials) {
+            self.samples.push((time, flux));
+        }
+    }
+
+    /// Record the flux at the current state of the simulation
+    pub fn record_simulation(&mut self, simulation: &AstronomicalSimulation) {
+        self.record(simulation.get_time(), simulation.get_celestials());
+    }
+
+    /// Add Gaussian noise with the given standard deviation to the recorded fluxes.
+    /// The same seed always results in the same noise.
+    pub fn add_noise(&mut self, standard_deviation: f64, seed: u64) {
+        let mut random: Random = Random::new(seed);
+        for (_, flux) in self.samples.iter_mut() {
+            *flux += random.normal(0.0, standard_deviation);
+        }
+    }
+
+    /// Write the light curve into a CSV file with the columns `time` and `flux`
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut file: BufWriter<File> = BufWriter::new(File::create(path)?);
+        writeln!(file, "time,flux")?;
+        for (time, flux) in self.samples.iter() {
+            writeln!(file, "{},{}", time.to_f64(), flux)?;
+        }
+        file.flush()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// A star of radius 7e8 m seen from +z, with a planet of a tenth of its radius at the given position
+    fn transit(x: f64, z: f64) -> Vec<Box<dyn Celestial + Sync>> {
+        vec![
+            Box::new(star!("Sun", 0, 0, 2e30, 7e8)),
+            Box::new(planet!("P", (0, 0, 0), (x, 0, z), 1e25, 7e7))
+        ]
+    }
+
+    #[test]
+    fn central_transit_depth_is_the_area_ratio() {
+        let curve: LightCurve = LightCurve::new("Sun", vector!(0, 0, 1), LimbDarkening::Uniform);
+        let flux: f64 = curve.relative_flux(&transit(0.0, 1e11)).unwrap();
+        assert!((1.0 - flux - 0.01).abs() < 1e-5, "{flux}");
+    }
+
+    #[test]
+    fn out_of_transit_flux_is_one() {
+        let curve: LightCurve = LightCurve::new(
+            "Sun", vector!(0, 0, 1), LimbDarkening::Quadratic { u1: 0.4, u2: 0.25 }
+        );
+        // Behind the star, and beside it
+        assert_eq!(curve.relative_flux(&transit(0.0, -1e11)), Some(1.0));
+        assert_eq!(curve.relative_flux(&transit(8e8, 1e11)), Some(1.0));
+        assert_eq!(curve.relative_flux(&transit(0.0, 1e11)[1..]), None);
+    }
+
+    #[test]
+    fn noise_depends_only_on_the_seed() {
+        let mut curve: LightCurve = LightCurve::new("Sun", vector!(0, 0, 1), LimbDarkening::Uniform);
+        for time in 0..100 {
+            curve.record(scalar!(time), &transit(0.0, -1e11));
+        }
+        let noisy = |seed: u64| -> Vec<f64> {
+            let mut copy: LightCurve = curve.clone();
+            copy.add_noise(1e-3, seed);
+            copy.samples.iter().map(|(_, flux)| *flux).collect()
+        };
+        assert_eq!(noisy(42), noisy(42));
+        assert_ne!(noisy(42), noisy(43));
+        assert!(noisy(42).iter().any(|flux| *flux != 1.0));
+    }
+}
@@ -10,19 +10,27 @@ pub enum PlanetType {
 }
 
 
-impl<P> From<P> for PlanetType where String: From<P> {
-    fn from(value: P) -> Self {
-        let string: String = String::from(value).to_lowercase().replace(' ', "");
+impl PlanetType {
+    /// Parse a planet type, ignoring the case and the spaces (e.g. `Gas Giant`, `rock planet`)
+    pub fn parse(value: &str) -> Option<Self> {
+        let string: String = value.to_lowercase().replace(' ', "");
         match string.as_str() {
-            "terrestrial" => Self::Terrestrial,
-            "gasgiant" => Self::GasGiant,
-            "satellite" => Self::GasGiant,
-            _ => panic!("Unknown planet type")
+            "terrestrial" | "rockplanet" | "rocky" => Some(Self::Terrestrial),
+            "gasgiant" => Some(Self::GasGiant),
+            "satellite" | "moon" => Some(Self::Satellite),
+            _ => None
         }
     }
 }
 
 
+impl<P> From<P> for PlanetType where String: From<P> {
+    fn from(value: P) -> Self {
+        Self::parse(&String::from(value)).expect("Unknown planet type")
+    }
+}
+
+
 impl Display for PlanetType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match *self {
@@ -35,7 +43,7 @@ impl Display for PlanetType {
 
 pub struct Planet {
     pub name: String,
-    pub radius: Scalar,
+    pub radius: Length,
     pub planet_type: PlanetType,
     point_body: PointBody
 }
@@ -44,8 +52,8 @@ impl Planet {
     pub fn new(name: String,
                velocity: Vector,
                coordinates: Point,
-               mass: Scalar,
-               radius: Scalar,
+               mass: Mass,
+               radius: Length,
                planet_type: PlanetType) -> Self {
         let point_body: PointBody = PointBody::new(mass, velocity, coordinates, scalar!());
         Self {
@@ -58,28 +66,31 @@ impl Planet {
     
 
     /* ----- POINT BODY FIELDS ----- */
-    pub fn mass(&self) -> Scalar { self.point_body.mass }
-    pub fn velocity(&self) -> Vector { self.point_body.velocity.clone() }
-    pub fn coordinates(&self) -> Point { self.point_body.coordinates.clone() }
+    pub fn mass(&self) -> Mass { self.point_body.get_mass() }
+    pub fn velocity(&self) -> Vector { self.point_body.velocity }
+    pub fn coordinates(&self) -> Point { self.point_body.coordinates }
 
-    pub fn set_mass(&mut self, mass: Scalar) { self.point_body.mass = mass; }
+    pub fn set_mass(&mut self, mass: Mass) { self.point_body.mass = mass; }
     pub fn set_velocity(&mut self, velocity: Vector) { self.point_body.velocity = velocity; }
     pub fn set_coordinates(&mut self, coordinates: Point) { self.point_body.coordinates = coordinates; }
+    pub fn set_test_particle(&mut self, test_particle: bool) { self.point_body.test_particle = test_particle; }
 
     /* ----- POINT BODY METHODS ----- */
     pub fn momentum(&self) -> Vector { self.point_body.momentum() }
-    pub fn kinetic_energy(&self) -> Scalar { self.point_body.kinetic_energy() }
+    pub fn kinetic_energy(&self) -> Energy { self.point_body.kinetic_energy() }
     pub fn acceleration(&self, force: Vector) -> Vector { self.point_body.acceleration(force) }
     pub fn force(&self, acceleration: Vector) -> Vector { self.point_body.force(acceleration) }
-    pub fn distance(&self, other: &Point) -> Scalar { self.point_body.distance(other) }
+    pub fn acceleration_magnitude(&self, force: Force) -> Acceleration { self.point_body.acceleration_magnitude(force) }
+    pub fn force_magnitude(&self, acceleration: Acceleration) -> Force { self.point_body.force_magnitude(acceleration) }
+    pub fn distance(&self, other: &Point) -> Length { self.point_body.distance(other) }
     pub fn gravitational_force(&self, other: &PointBody) -> Vector { self.point_body.gravitational_force(other) }
-    pub fn advance(&mut self, dt: Scalar) { self.point_body.advance(dt) }
+    pub fn advance(&mut self, dt: Time) { self.point_body.advance(dt) }
     
     /* ----- PLANET METHODS ----- */
     pub fn surface_acceleration(&self) -> Vector {
         Vector::new(
             ZERO,
-            -G * self.mass() / self.radius.powi(2),
+            -G * self.mass().value / self.radius.value.powi(2),
             ZERO
         )
     }
@@ -92,7 +103,7 @@ impl Celestial for Planet {
     #[inline]
     fn point_body_mut(&mut self) -> &mut PointBody { &mut self.point_body }
     #[inline]
-    fn get_radius(&self) -> Scalar { self.radius }
+    fn get_radius(&self) -> Length { self.radius }
     #[inline]
     fn get_name(&self) -> String { self.name.clone() }
     #[inline]
@@ -102,6 +113,8 @@ impl Celestial for Planet {
     #[inline]
     fn planet_type(&self) -> Option<PlanetType> { Some(self.planet_type) }
     #[inline]
+    fn concrete(&self) -> Option<ConcreteCelestial<'_>> { Some(ConcreteCelestial::Planet(self)) }
+    #[inline]
     fn get_color(&self) -> [f64; 4] {
         match self.planet_type {
             PlanetType::Terrestrial => [0.2, 0.2, 0.2, 1.0],
@@ -125,13 +138,13 @@ macro_rules! planet {
         $mass: expr,
         $radius: expr
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
-            vector!($velocity),
-            point!($coordinates),
-            scalar!($mass),
-            scalar!($radius),
-            PlanetType::Terrestrial
+            $crate::vector!($velocity),
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
+            $crate::astronomy::PlanetType::Terrestrial
         )
     };
     (
@@ -141,12 +154,13 @@ macro_rules! planet {
         $mass: expr,
         $radius: expr
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
             $velocity,
-            point!($coordinates),
-            scalar!($mass),
-            scalar!($radius)
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
+            $crate::astronomy::PlanetType::Terrestrial
         )
     };
     (
@@ -156,12 +170,13 @@ macro_rules! planet {
         $mass: expr,
         $radius: expr
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
-            vector!($velocity),
+            $crate::vector!($velocity),
             $coordinates,
-            scalar!($mass),
-            scalar!($radius)
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
+            $crate::astronomy::PlanetType::Terrestrial
         )
     };
     (
@@ -171,13 +186,13 @@ macro_rules! planet {
         $mass: expr,
         $radius: expr
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
             $velocity,
             $coordinates,
-            scalar!($mass),
-            scalar!($radius),
-            PlanetType::Terrestrial
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
+            $crate::astronomy::PlanetType::Terrestrial
         )
     };
     (
@@ -188,13 +203,13 @@ macro_rules! planet {
         $radius: expr,
         $planet_type: literal
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
-            vector!($velocity),
-            point!($coordinates),
-            scalar!($mass),
-            scalar!($radius),
-            PlanetType::from($planet_type)
+            $crate::vector!($velocity),
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
+            $crate::astronomy::PlanetType::from($planet_type)
         )
     };
     (
@@ -205,12 +220,12 @@ macro_rules! planet {
         $radius: expr,
         $planet_type: expr
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
-            vector!($velocity),
-            point!($coordinates),
-            scalar!($mass),
-            scalar!($radius),
+            $crate::vector!($velocity),
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
             $planet_type
         )
     };
@@ -222,13 +237,13 @@ macro_rules! planet {
         $radius: expr,
         $planet_type: literal
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
             $velocity,
-            point!($coordinates),
-            scalar!($mass),
-            scalar!($radius),
-            PlanetType::from($planet_type)
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
+            $crate::astronomy::PlanetType::from($planet_type)
         )
     };
     (
@@ -239,12 +254,12 @@ macro_rules! planet {
         $radius: expr,
         $planet_type: expr
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
             $velocity,
-            point!($coordinates),
-            scalar!($mass),
-            scalar!($radius),
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
             $planet_type
         )
     };
@@ -256,13 +271,13 @@ macro_rules! planet {
         $radius: expr,
         $planet_type: literal
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
-            vector!($velocity),
+            $crate::vector!($velocity),
             $coordinates,
-            scalar!($mass),
-            scalar!($radius),
-            PlanetType::from($planet_type)
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
+            $crate::astronomy::PlanetType::from($planet_type)
         )
     };
     (
@@ -273,12 +288,12 @@ macro_rules! planet {
         $radius: expr,
         $planet_type: expr
     ) => {
-        Planet::new(
+        $crate::astronomy::Planet::new(
             String::from($name),
-            vector!($velocity),
+            $crate::vector!($velocity),
             $coordinates,
-            scalar!($mass),
-            scalar!($radius),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius),
             $planet_type
         )
     };
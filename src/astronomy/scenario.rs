@@ -0,0 +1,952 @@
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::path::Path;
+use crate::*;
+
+
+/// # Scenario Error
+/// An error while reading a `.psa` file
+/// - `Io` - The file could not be read
+/// - `Syntax` - The file is invalid, at the given line and column (both starting from 1)
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(std::io::Error),
+    Syntax { line: usize, column: usize, message: String }
+}
+
+
+impl ScenarioError {
+    fn syntax(line: usize, column: usize, message: impl ToString) -> Self {
+        Self::Syntax { line, column, message: message.to_string() }
+    }
+
+    /// Convert an error in a quantity on the given line, where the value starts at `column`
+    fn unit(line: usize, column: usize, error: UnitError) -> Self {
+        Self::syntax(line, column + error.position, error.message)
+    }
+}
+
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Syntax { line, column, message } => write!(f, "line {line}, column {column}: {message}")
+        }
+    }
+}
+
+
+impl std::error::Error for ScenarioError {}
+
+
+impl From<std::io::Error> for ScenarioError {
+    fn from(error: std::io::Error) -> Self { Self::Io(error) }
+}
+
+
+/// # Body Kind
+/// The type of a body in a scenario: `Star`, `Spacecraft`, or one of the `PlanetType`s
+#[derive(Debug, Clone, Copy)]
+pub enum BodyKind {
+    Star,
+    Planet(PlanetType),
+    Spacecraft
+}
+
+
+impl BodyKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.trim().eq_ignore_ascii_case("star") {
+            Some(Self::Star)
+        } else if value.trim().eq_ignore_ascii_case("spacecraft") {
+            Some(Self::Spacecraft)
+        } else {
+            PlanetType::parse(value).map(Self::Planet)
+        }
+    }
+}
+
+
+impl Display for BodyKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Star => write!(f, "Star"),
+            Self::Planet(planet_type) => write!(f, "{planet_type}"),
+            Self::Spacecraft => write!(f, "Spacecraft")
+        }
+    }
+}
+
+
+/// # Engine
+/// The propulsion of a spacecraft in a scenario.
+/// The `mass` of the spacecraft is its total mass, the propellant is the part above the dry mass.
+/// ## Attributes
+/// `dry_mass: Mass` - The mass of the spacecraft without propellant\
+/// `specific_impulse: Time` - The specific impulse of the engine\
+/// `thrust: Force` - The thrust of the engine during a burn\
+/// `reference: Option<String>` - The name of the body the burn directions are measured around\
+/// `burns: Vec<Burn>` - The burn schedule
+#[derive(Debug, Clone)]
+pub struct Engine {
+    pub dry_mass: Mass,
+    pub specific_impulse: Time,
+    pub thrust: Force,
+    pub reference: Option<String>,
+    pub burns: Vec<Burn>
+}
+
+
+/// # Body Description
+/// One body of a scenario, the `engine` is only given for spacecraft
+#[derive(Debug, Clone)]
+pub struct BodyDescription {
+    pub name: String,
+    pub kind: BodyKind,
+    pub mass: Mass,
+    pub radius: Length,
+    pub velocity: Vector,
+    pub coordinates: Point,
+    pub tracer: bool,
+    pub test_particle: bool,
+    pub engine: Option<Engine>
+}
+
+
+impl BodyDescription {
+    /// Describe a celestial of a simulation.
+    /// Returns `None` for the celestials which are neither stars, planets nor spacecraft.
+    pub fn from_celestial(celestial: &(dyn Celestial + Sync)) -> Option<Self> {
+        let mut engine: Option<Engine> = None;
+        let kind: BodyKind = if let Some(ConcreteCelestial::Spacecraft(spacecraft)) = celestial.concrete() {
+            engine = Some(Engine {
+                dry_mass: spacecraft.dry_mass,
+                specific_impulse: spacecraft.specific_impulse,
+                thrust: spacecraft.max_thrust,
+                reference: spacecraft.reference.clone(),
+                burns: spacecraft.burns.clone()
+            });
+            BodyKind::Spacecraft
+        } else if celestial.is_star() {
+            BodyKind::Star
+        } else if celestial.is_planet() {
+            BodyKind::Planet(celestial.planet_type()?)
+        } else {
+            return None;
+        };
+
+        let body: &PointBody = celestial.point_body();
+        Some(Self {
+            name: celestial.get_name(),
+            kind,
+            mass: body.get_mass(),
+            radius: celestial.get_radius(),
+            velocity: body.velocity,
+            coordinates: body.coordinates,
+            tracer: celestial.is_tracer(),
+            test_particle: body.test_particle,
+            engine
+        })
+    }
+
+    /// Create the celestial described
+    pub fn build(&self) -> Box<dyn Celestial + Sync> {
+        let mut celestial: Box<dyn Celestial + Sync> = match self.kind {
+            BodyKind::Star if self.tracer => {
+                let mut star: Star = Star::tracer(self.name.clone(), self.velocity, self.coordinates, self.mass);
+                star.radius = self.radius;
+                Box::new(star)
+            },
+            BodyKind::Star => Box::new(Star::new(
+                self.name.clone(), self.velocity, self.coordinates, self.mass, self.radius
+            )),
+            BodyKind::Planet(planet_type) => Box::new(Planet::new(
+                self.name.clone(), self.velocity, self.coordinates, self.mass, self.radius, planet_type
+            )),
+            BodyKind::Spacecraft => {
+                // Without an engine, the whole mass is dry
+                let dry_mass: Mass = self.engine.as_ref().map_or(self.mass, |engine| engine.dry_mass);
+                let mut spacecraft: Spacecraft = Spacecraft::new(
+                    self.name.clone(),
+                    self.velocity,
+                    self.coordinates,
+                    dry_mass,
+                    self.mass - dry_mass,
+                    self.engine.as_ref().map_or(Time::new(ZERO), |engine| engine.specific_impulse),
+                    self.engine.as_ref().map_or(Force::new(ZERO), |engine| engine.thrust)
+                );
+                spacecraft.radius = self.radius;
+                if let Some(engine) = &self.engine {
+                    spacecraft.reference = engine.reference.clone();
+                    spacecraft.burns = engine.burns.clone();
+                }
+                Box::new(spacecraft)
+            }
+        };
+        // The sum of the dry mass and the propellant may round, so the mass is set exactly
+        celestial.point_body_mut().mass = self.mass;
+        celestial.point_body_mut().test_particle = self.test_particle;
+        celestial
+    }
+}
+
+
+impl Display for BodyDescription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}:", self.name)?;
+        writeln!(f, "    type: {}", self.kind)?;
+        writeln!(f, "    radius: {}", format_quantity(self.radius.value, &["km", "m"]))?;
+        writeln!(f, "    mass: {}", format_quantity(self.mass.value, &["kg"]))?;
+        writeln!(f, "    velocity: {}", format_vector(&self.velocity, &["km/s", "m/s"]))?;
+        writeln!(f, "    coordinates: {}", format_vector(&Vector::from_point(self.coordinates), &["km", "m"]))?;
+        if self.tracer { writeln!(f, "    tracer: true")?; }
+        if self.test_particle { writeln!(f, "    test particle: true")?; }
+        if let Some(engine) = &self.engine {
+            writeln!(f, "    dry mass: {}", format_quantity(engine.dry_mass.value, &["kg"]))?;
+            writeln!(f, "    specific impulse: {}", format_quantity(engine.specific_impulse.value, &["s"]))?;
+            writeln!(f, "    thrust: {}", format_quantity(engine.thrust.value, &["kN", "N"]))?;
+            if let Some(reference) = &engine.reference {
+                writeln!(f, "    reference: {reference}")?;
+            }
+            for burn in engine.burns.iter() {
+                let direction: String = match burn.direction {
+                    BurnDirection::Fixed(direction) => format!(
+                        "({}, {}, {})",
+                        format_quantity(direction.point.x, &[]),
+                        format_quantity(direction.point.y, &[]),
+                        format_quantity(direction.point.z, &[])
+                    ),
+                    direction => direction.to_string()
+                };
+                writeln!(
+                    f, "    burn: {}, {}, {direction}",
+                    format_quantity(burn.start, &["s"]), format_quantity(burn.duration, &["s"])
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// # Scenario
+/// The description of a simulation, as stored in a `.psa` file:
+/// ```text
+/// dt: 2 h
+/// name: Solar System
+/// Sun:
+///     type: Star
+///     radius: 696 340 km
+///     mass: 1 SM
+///     velocity: 0
+///     coordinates: (0, 0, 0)
+/// ```
+/// The settings (`dt`, `name`, the optional starting `time` and `epoch`) are at the top level.
+/// Every body starts with its name and a colon, followed by its indented properties:
+/// `type` (`Star`, `Terrestrial` / `Rock Planet`, `Gas Giant`, `Satellite` or `Spacecraft`), `mass` and `radius` are required,
+/// `velocity` and `coordinates` default to zero, `tracer` and `test particle` default to `false`.
+/// The `mass` of a spacecraft includes its propellant, and its engine is described by
+/// `dry mass`, `specific impulse` and `thrust` (required), the optional `reference` body of the burn directions,
+/// and one `burn: <start>, <duration>, <direction>` line per burn
+/// (the direction is `Prograde`, `Retrograde`, `Normal`, `Anti-normal`, `Radial out`, `Radial in` or a vector).
+/// The quantities can have units (see `parse_quantity`), lines starting with `#` are comments.
+/// ## Horizons
+/// Instead of `velocity` and `coordinates`, a body can read its state from a JPL Horizons vector table
+/// with `horizons: path/to/table.txt` (relative to the `.psa` file).
+/// Every table is read at the `epoch` (Julian day, TDB), which defaults to the first epoch of the first table,
+/// and the states of a scenario must share the same centre body.
+/// ## SPK kernels
+/// With the `kernel: path/to/de440s.bsp` setting, a body can read its state at the `epoch` from the kernel
+/// with `naif: <NAIF ID>` (e.g. `399` for the Earth). The states are relative to the `center: <NAIF ID>` setting,
+/// the Solar System barycenter (`0`) by default. The tables relative to another body are moved to the `center`
+/// with the kernel, the scenarios without a kernel report the tables relative to another body as an error.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub dt: Time,
+    pub time: Time,
+    pub epoch: Option<f64>,
+    pub bodies: Vec<BodyDescription>
+}
+
+
+/// The properties of the body being read
+#[derive(Default)]
+struct PartialBody {
+    name: String,
+    line: usize,
+    kind: Option<BodyKind>,
+    mass: Option<Mass>,
+    radius: Option<Length>,
+    velocity: Option<Vector>,
+    coordinates: Option<Point>,
+    tracer: Option<bool>,
+    test_particle: Option<bool>,
+    dry_mass: Option<Mass>,
+    specific_impulse: Option<Time>,
+    thrust: Option<Force>,
+    reference: Option<String>,
+    burns: Vec<Burn>,
+    engine_line: usize,
+    horizons: Option<(HorizonsVectors, usize, usize)>,
+    naif: Option<(i32, usize, usize)>
+}
+
+
+impl PartialBody {
+    fn finish(self) -> Result<BodyDescription, ScenarioError> {
+        let missing = |property: &str| ScenarioError::syntax(
+            self.line, 1, format!("the body '{}' has no {property}", self.name)
+        );
+        let kind: BodyKind = self.kind.ok_or_else(|| missing("type"))?;
+        let mass: Mass = self.mass.ok_or_else(|| missing("mass"))?;
+
+        // The engine properties are only read for spacecraft
+        let engine: Option<Engine> = if matches!(kind, BodyKind::Spacecraft) {
+            let dry_mass: Mass = self.dry_mass.ok_or_else(|| missing("dry mass"))?;
+            if dry_mass.value > mass.value {
+                return Err(ScenarioError::syntax(
+                    self.line, 1, format!("the dry mass of '{}' is larger than its mass", self.name)
+                ));
+            }
+            Some(Engine {
+                dry_mass,
+                specific_impulse: self.specific_impulse.ok_or_else(|| missing("specific impulse"))?,
+                thrust: self.thrust.ok_or_else(|| missing("thrust"))?,
+                reference: self.reference,
+                burns: self.burns
+            })
+        } else if self.engine_line != 0 {
+            return Err(ScenarioError::syntax(
+                self.engine_line, 1, format!("the body '{}' is not a spacecraft, it has no engine", self.name)
+            ));
+        } else { None };
+
+        Ok(BodyDescription {
+            kind,
+            mass,
+            radius: self.radius.ok_or_else(|| missing("radius"))?,
+            velocity: self.velocity.unwrap_or(NULL_VECTOR),
+            coordinates: self.coordinates.unwrap_or(ORIGO),
+            tracer: self.tracer.unwrap_or(false),
+            test_particle: self.test_particle.unwrap_or(false),
+            engine,
+            name: self.name
+        })
+    }
+}
+
+
+impl Scenario {
+    /// Read a scenario from a `.psa` file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path: &Path = path.as_ref();
+        Self::parse_in(&std::fs::read_to_string(path)?, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Parse the contents of a `.psa` file.
+    /// The Horizons tables are looked up relative to the current directory.
+    pub fn parse(text: &str) -> Result<Self, ScenarioError> {
+        Self::parse_in(text, Path::new(""))
+    }
+
+    /// Parse the contents of a `.psa` file, whose Horizons tables are in `directory`
+    fn parse_in(text: &str, directory: &Path) -> Result<Self, ScenarioError> {
+        let mut name: Option<String> = None;
+        let mut dt: Option<Time> = None;
+        let mut time: Option<Time> = None;
+        let mut epoch: Option<f64> = None;
+        let mut kernel: Option<SpkKernel> = None;
+        let mut origin: Option<i32> = None;
+        let mut pending: Vec<PartialBody> = Vec::new();
+        let mut bodies: Vec<BodyDescription> = Vec::new();
+        let mut body: Option<PartialBody> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let number: usize = index + 1;
+            let content: &str = line.trim();
+            if content.is_empty() || content.starts_with('#') { continue; }
+
+            let indented: bool = line.starts_with([' ', '\t']);
+            let (key, value) = content.split_once(':').ok_or_else(|| {
+                ScenarioError::syntax(number, column(line, content), "expected 'key: value' or 'name:'")
+            })?;
+            let key: &str = key.trim();
+            let value: &str = value.trim();
+            let value_column: usize = if value.is_empty() { line.chars().count() + 1 } else { column(line, value) };
+
+            if indented {
+                let current: &mut PartialBody = body.as_mut().ok_or_else(|| {
+                    ScenarioError::syntax(number, column(line, content), "indented property outside of a body")
+                })?;
+                if key == "horizons" {
+                    if current.horizons.is_some() {
+                        return Err(ScenarioError::syntax(number, column(line, key), "duplicate property 'horizons'"));
+                    }
+                    let table: HorizonsVectors = HorizonsVectors::load(directory.join(value))
+                        .map_err(|error| ScenarioError::syntax(number, value_column, format!("{value}: {error}")))?;
+                    current.horizons = Some((table, number, value_column));
+                } else if key == "naif" {
+                    if current.naif.is_some() {
+                        return Err(ScenarioError::syntax(number, column(line, key), "duplicate property 'naif'"));
+                    }
+                    let id: i32 = value.parse().map_err(|_| ScenarioError::syntax(
+                        number, value_column, format!("expected a NAIF ID, found '{value}'")
+                    ))?;
+                    current.naif = Some((id, number, value_column));
+                } else {
+                    read_property(current, key, value, number, column(line, key), value_column)?;
+                }
+                continue;
+            }
+
+            // A new top-level entry ends the current body
+            if let Some(finished) = body.take() {
+                pending.push(finished);
+            }
+
+            match (key, value.is_empty()) {
+                (_, true) => {
+                    if key.is_empty() {
+                        return Err(ScenarioError::syntax(number, 1, "missing body name"));
+                    }
+                    if pending.iter().any(|existing| existing.name == key) {
+                        return Err(ScenarioError::syntax(number, 1, format!("duplicate body '{key}'")));
+                    }
+                    body = Some(PartialBody { name: key.to_string(), line: number, ..PartialBody::default() });
+                },
+                ("dt" | "time", false) => {
+                    let duration: Time = parse_quantity(value)
+                        .and_then(|quantity| quantity.time())
+                        .map_err(|error| ScenarioError::unit(number, value_column, error))?;
+                    let setting: &mut Option<Time> = if key == "dt" { &mut dt } else { &mut time };
+                    if setting.is_some() {
+                        return Err(ScenarioError::syntax(number, 1, format!("duplicate setting '{key}'")));
+                    }
+                    *setting = Some(duration);
+                },
+                ("name", false) => name = Some(value.to_string()),
+                ("epoch", false) => {
+                    let julian_day: f64 = value.parse().map_err(|_| ScenarioError::syntax(
+                        number, value_column, format!("expected a Julian day, found '{value}'")
+                    ))?;
+                    if epoch.replace(julian_day).is_some() {
+                        return Err(ScenarioError::syntax(number, 1, "duplicate setting 'epoch'"));
+                    }
+                },
+                ("kernel", false) => {
+                    if kernel.is_some() {
+                        return Err(ScenarioError::syntax(number, 1, "duplicate setting 'kernel'"));
+                    }
+                    kernel = Some(SpkKernel::open(directory.join(value))
+                        .map_err(|error| ScenarioError::syntax(number, value_column, format!("{value}: {error}")))?);
+                },
+                ("center", false) => {
+                    let id: i32 = value.parse().map_err(|_| ScenarioError::syntax(
+                        number, value_column, format!("expected a NAIF ID, found '{value}'")
+                    ))?;
+                    if origin.replace(id).is_some() {
+                        return Err(ScenarioError::syntax(number, 1, "duplicate setting 'center'"));
+                    }
+                },
+                (_, false) => {
+                    return Err(ScenarioError::syntax(number, column(line, key), format!("unknown setting '{key}'")));
+                }
+            }
+        }
+
+        if let Some(finished) = body.take() {
+            pending.push(finished);
+        }
+
+        // The epoch is known now, so the states can be read from the Horizons tables and the kernel
+        if epoch.is_none() {
+            epoch = pending.iter()
+                .find_map(|partial| partial.horizons.as_ref())
+                .and_then(|(table, _, _)| table.states.first())
+                .map(|state| state.epoch);
+        }
+        // The kernel states are relative to the `center` setting, the Solar System barycenter by default.
+        // The tables relative to another body are moved to it with the kernel, when the scenario has one
+        // and the centre is given or used by the `naif` bodies.
+        let naif_center: i32 = origin.unwrap_or(SOLAR_SYSTEM_BARYCENTER);
+        let recenter: bool = kernel.is_some() && (origin.is_some() || pending.iter().any(|partial| partial.naif.is_some()));
+        let mut center: Option<(Option<i32>, String)> = origin.map(|id| (Some(id), naif_name(id)));
+        let mut check_center = |id: Option<i32>, name: String, line: usize, value_column: usize| -> Result<(), ScenarioError> {
+            match &center {
+                Some((earlier_id, earlier)) => {
+                    let same: bool = match (id, earlier_id) {
+                        (Some(id), Some(earlier_id)) => id == *earlier_id,
+                        _ => name == *earlier
+                    };
+                    if same { Ok(()) } else {
+                        Err(ScenarioError::syntax(
+                            line, value_column, format!("the state is relative to '{name}', but the earlier ones to '{earlier}'")
+                        ))
+                    }
+                },
+                None => {
+                    center = Some((id, name));
+                    Ok(())
+                }
+            }
+        };
+        for mut partial in pending {
+            let given: bool = partial.velocity.is_some() || partial.coordinates.is_some();
+            if let Some((id, line, value_column)) = partial.naif {
+                if given || partial.horizons.is_some() {
+                    return Err(ScenarioError::syntax(
+                        line, value_column, "a body can not have 'naif' together with 'horizons', 'velocity' or 'coordinates'"
+                    ));
+                }
+                let kernel: &SpkKernel = kernel.as_ref()
+                    .ok_or_else(|| ScenarioError::syntax(line, value_column, "missing setting 'kernel'"))?;
+                let epoch: f64 = epoch
+                    .ok_or_else(|| ScenarioError::syntax(line, value_column, "missing setting 'epoch'"))?;
+                let (coordinates, velocity) = kernel.state(id, naif_center, julian_day_to_et(epoch))
+                    .map_err(|error| ScenarioError::syntax(line, value_column, error))?;
+                check_center(Some(naif_center), naif_name(naif_center), line, value_column)?;
+                partial.velocity = Some(velocity);
+                partial.coordinates = Some(coordinates);
+            }
+            if let Some((table, line, value_column)) = &partial.horizons {
+                if given {
+                    return Err(ScenarioError::syntax(
+                        *line, *value_column, "a body can not have both 'horizons' and 'velocity' or 'coordinates'"
+                    ));
+                }
+                let epoch: f64 = epoch.unwrap_or_default();
+                let mut state: HorizonsState = table.state_at(epoch)
+                    .map_err(|error| ScenarioError::syntax(*line, *value_column, error))?;
+                match (&kernel, table.center_id) {
+                    (Some(kernel), Some(id)) if recenter && id != naif_center => {
+                        let (coordinates, velocity) = kernel.state(id, naif_center, julian_day_to_et(epoch))
+                            .map_err(|error| ScenarioError::syntax(*line, *value_column, error))?;
+                        state.coordinates = state.coordinates + coordinates;
+                        state.velocity += velocity;
+                        check_center(Some(naif_center), naif_name(naif_center), *line, *value_column)?;
+                    },
+                    _ => check_center(table.center_id, table.center.clone(), *line, *value_column)?
+                }
+                partial.velocity = Some(state.velocity);
+                partial.coordinates = Some(state.coordinates);
+            }
+            bodies.push(partial.finish()?);
+        }
+
+        Ok(Self {
+            name: name.unwrap_or_else(|| String::from("Simulation")),
+            dt: dt.ok_or_else(|| ScenarioError::syntax(1, 1, "missing setting 'dt'"))?,
+            time: time.unwrap_or(Time::new(ZERO)),
+            epoch,
+            bodies
+        })
+    }
+
+    /// Describe the current state of a simulation.
+    /// The celestials which are neither stars, planets nor spacecraft are left out,
+    /// their names are returned next to the scenario.
+    pub fn from_simulation(simulation: &AstronomicalSimulation) -> (Self, Vec<String>) {
+        let mut bodies: Vec<BodyDescription> = Vec::new();
+        let mut skipped: Vec<String> = Vec::new();
+        for celestial in simulation.get_celestials() {
+            match BodyDescription::from_celestial(celestial.as_ref()) {
+                Some(body) => bodies.push(body),
+                None => skipped.push(celestial.get_name())
+            }
+        }
+
+        let scenario: Self = Self {
+            name: simulation.get_name(),
+            dt: Time::new(simulation.get_dt()),
+            time: Time::new(simulation.get_time()),
+            // The epoch of the scenario is the date of its state
+            epoch: simulation.get_julian_day(),
+            bodies
+        };
+        (scenario, skipped)
+    }
+
+    /// Write the scenario to a `.psa` file
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut file: std::fs::File = std::fs::File::create(path)?;
+        write!(file, "{self}")
+    }
+
+    /// Create the simulation described by the scenario
+    pub fn build(&self) -> AstronomicalSimulation {
+        let mut simulation: AstronomicalSimulation = AstronomicalSimulation::new(
+            self.dt.value,
+            self.bodies.iter().map(BodyDescription::build).collect(),
+            self.name.clone(),
+            0.02,
+            false
+        );
+        simulation.set_time(self.time.value);
+        simulation.set_epoch(self.epoch.map(|epoch| epoch - (self.time / DAY).to_f64()));
+        simulation
+    }
+}
+
+
+/// The values are written so that loading the file gives back exactly the same scenario
+impl Display for Scenario {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "dt: {}", format_quantity(self.dt.value, &["h", "min", "s"]))?;
+        if self.time.value != ZERO {
+            writeln!(f, "time: {}", format_quantity(self.time.value, &["s"]))?;
+        }
+        writeln!(f, "name: {}", self.name)?;
+        if let Some(epoch) = self.epoch {
+            writeln!(f, "epoch: {epoch}")?;
+        }
+        for body in self.bodies.iter() {
+            write!(f, "{body}")?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Read a `.psa` file and create the simulation it describes
+pub fn load_simulation(path: impl AsRef<Path>) -> Result<AstronomicalSimulation, ScenarioError> {
+    Ok(Scenario::load(path)?.build())
+}
+
+
+/// Save the current state of a simulation to a `.psa` file.
+/// The celestials which are neither stars, planets nor spacecraft are left out, and their names are returned.
+pub fn save_simulation(simulation: &AstronomicalSimulation, path: impl AsRef<Path>) -> std::io::Result<Vec<String>> {
+    let (scenario, skipped) = Scenario::from_simulation(simulation);
+    scenario.save(path)?;
+    Ok(skipped)
+}
+
+
+/// Format a quantity in the first of the units, which reads back as exactly the same value.
+/// The last unit should be the SI unit, which always does.
+fn format_quantity(value: Scalar, units: &[&str]) -> String {
+    if value == ZERO { return String::from("0"); }
+    for unit in units.iter() {
+        let factor: Scalar = match parse_quantity(&format!("1 {unit}")) {
+            Ok(quantity) => quantity.value,
+            Err(_) => continue
+        };
+        let text: String = format!("{} {unit}", (value / factor).format_exact());
+        if parse_quantity(&text).is_ok_and(|quantity| quantity.value == value) {
+            return text;
+        }
+    }
+    value.format_exact()
+}
+
+
+/// Format a vector as `(x, y, z)`, or `0` for the null vector
+fn format_vector(vector: &Vector, units: &[&str]) -> String {
+    if *vector == NULL_VECTOR { return String::from("0"); }
+    format!(
+        "({}, {}, {})",
+        format_quantity(vector.point.x, units),
+        format_quantity(vector.point.y, units),
+        format_quantity(vector.point.z, units)
+    )
+}
+
+
+/// The name of a centre body given by its NAIF ID, as the Horizons tables write it
+fn naif_name(id: i32) -> String {
+    match id {
+        SOLAR_SYSTEM_BARYCENTER => String::from("Solar System Barycenter"),
+        10 => String::from("Sun"),
+        _ => format!("NAIF ID {id}")
+    }
+}
+
+
+/// The column (starting from 1) where `part`, a slice of `line`, starts
+fn column(line: &str, part: &str) -> usize {
+    let offset: usize = part.as_ptr() as usize - line.as_ptr() as usize;
+    line[..offset].chars().count() + 1
+}
+
+
+/// Read one property of a body
+fn read_property(body: &mut PartialBody,
+                 key: &str,
+                 value: &str,
+                 line: usize,
+                 key_column: usize,
+                 value_column: usize) -> Result<(), ScenarioError> {
+    let unit_error = |error: UnitError| ScenarioError::unit(line, value_column, error);
+    let duplicate: bool = match key {
+        "type" => body.kind.is_some(),
+        "mass" => body.mass.is_some(),
+        "radius" => body.radius.is_some(),
+        "velocity" => body.velocity.is_some(),
+        "coordinates" => body.coordinates.is_some(),
+        "tracer" => body.tracer.is_some(),
+        "test particle" => body.test_particle.is_some(),
+        "dry mass" => body.dry_mass.is_some(),
+        "specific impulse" => body.specific_impulse.is_some(),
+        "thrust" => body.thrust.is_some(),
+        "reference" => body.reference.is_some(),
+        _ => false
+    };
+    if duplicate {
+        return Err(ScenarioError::syntax(line, key_column, format!("duplicate property '{key}'")));
+    }
+
+    if matches!(key, "dry mass" | "specific impulse" | "thrust" | "reference" | "burn") && body.engine_line == 0 {
+        body.engine_line = line;
+    }
+
+    match key {
+        "type" => {
+            body.kind = Some(BodyKind::parse(value).ok_or_else(|| ScenarioError::syntax(
+                line, value_column,
+                format!("unknown type '{value}' (expected Star, Terrestrial, Rock Planet, Gas Giant, Satellite or Spacecraft)")
+            ))?);
+        },
+        "mass" => body.mass = Some(parse_quantity(value).and_then(|quantity| quantity.mass()).map_err(unit_error)?),
+        "radius" => body.radius = Some(parse_quantity(value).and_then(|quantity| quantity.length()).map_err(unit_error)?),
+        "velocity" => {
+            body.velocity = Some(
+                parse_vector(value).and_then(|vector| vector.expect(Dimension::VELOCITY)).map_err(unit_error)?
+            );
+        },
+        "coordinates" => {
+            body.coordinates = Some(
+                parse_vector(value).and_then(|vector| vector.expect(Dimension::LENGTH)).map_err(unit_error)?.to_point()
+            );
+        },
+        "tracer" | "test particle" => {
+            let flag: bool = match value.to_lowercase().as_str() {
+                "true" | "yes" => true,
+                "false" | "no" => false,
+                _ => return Err(ScenarioError::syntax(line, value_column, format!("expected true or false, found '{value}'")))
+            };
+            if key == "tracer" { body.tracer = Some(flag); } else { body.test_particle = Some(flag); }
+        },
+        "dry mass" => body.dry_mass = Some(parse_quantity(value).and_then(|quantity| quantity.mass()).map_err(unit_error)?),
+        "specific impulse" => {
+            body.specific_impulse = Some(parse_quantity(value).and_then(|quantity| quantity.time()).map_err(unit_error)?);
+        },
+        "thrust" => body.thrust = Some(parse_quantity(value).and_then(|quantity| quantity.force()).map_err(unit_error)?),
+        "reference" => body.reference = Some(value.to_string()),
+        "burn" => body.burns.push(read_burn(value, line, value_column)?),
+        _ => return Err(ScenarioError::syntax(line, key_column, format!("unknown property '{key}'")))
+    }
+    Ok(())
+}
+
+
+/// Read a burn: `<start>, <duration>, <direction>`,
+/// where the direction is `Prograde`, `Retrograde`, `Normal`, `Anti-normal`, `Radial out`, `Radial in` or a vector
+fn read_burn(value: &str, line: usize, value_column: usize) -> Result<Burn, ScenarioError> {
+    let part_column = |part: &str| value_column + column(value, part) - 1;
+    let mut parts = value.splitn(3, ',').map(str::trim);
+    let (start, duration, direction) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(start), Some(duration), Some(direction)) if !direction.is_empty() => (start, duration, direction),
+        _ => return Err(ScenarioError::syntax(line, value_column, "expected '<start>, <duration>, <direction>'"))
+    };
+
+    let time = |part: &str| parse_quantity(part)
+        .and_then(|quantity| quantity.time())
+        .map_err(|error| ScenarioError::unit(line, part_column(part), error));
+    let direction: BurnDirection = match BurnDirection::parse(direction) {
+        Some(direction) => direction,
+        None if direction.starts_with('(') => BurnDirection::Fixed(
+            parse_vector(direction)
+                .and_then(|vector| vector.expect(Dimension::DIMENSIONLESS))
+                .map_err(|error| ScenarioError::unit(line, part_column(direction), error))?
+        ),
+        None => return Err(ScenarioError::syntax(
+            line, part_column(direction),
+            format!("unknown burn direction '{direction}' (expected Prograde, Retrograde, Normal, Anti-normal, Radial out, Radial in or a vector)")
+        ))
+    };
+    Ok(Burn::new(time(start)?.value, time(duration)?.value, direction))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const SCENARIO: &str = "\
+dt: 1 h
+time: 864000 s
+name: Dated
+epoch: 2451555.0
+Sun:
+    type: Star
+    radius: 696340 km
+    mass: 1 SM
+";
+
+    #[test]
+    fn epoch_is_kept_by_the_simulation() {
+        let scenario: Scenario = Scenario::parse(SCENARIO).unwrap();
+        let simulation: AstronomicalSimulation = scenario.build();
+        assert_eq!(simulation.get_epoch(), Some(2451545.0));
+
+        let (saved, _) = Scenario::from_simulation(&simulation);
+        assert_eq!(saved.epoch, Some(2451555.0));
+    }
+
+    #[test]
+    fn epoch_is_written_to_checkpoints() {
+        let simulation: AstronomicalSimulation = Scenario::parse(SCENARIO).unwrap().build();
+        let path: std::path::PathBuf = std::env::temp_dir().join(format!("epoch-{}.psc", std::process::id()));
+        simulation.write_checkpoint(&path).unwrap();
+        let restored: AstronomicalSimulation = AstronomicalSimulation::read_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restored.get_epoch(), Some(2451545.0));
+    }
+
+    #[test]
+    fn spacecraft_are_saved_with_their_engine() {
+        let mut probe: Spacecraft = spacecraft!(
+            "Probe", (0.1, 7_784.261_3, 1.0 / 3.0), (6.771e6, 0, 0), 812.5, 187.3, 311.2, 440.0
+        );
+        probe.set_reference("Earth");
+        probe.add_burn(Burn::new(scalar!(600), scalar!(92.5), BurnDirection::Prograde));
+        probe.add_burn(Burn::new(scalar!(5400), scalar!(30), BurnDirection::AntiNormal));
+        probe.add_burn(Burn::new(scalar!(9000), scalar!(1) / scalar!(3), BurnDirection::Fixed(vector!(0.6, -0.8, 0.1))));
+        let mut simulation: AstronomicalSimulation = astronomical_simulation!(scalar!(10), vec![
+            Box::new(planet!("Earth", (0, 0, 0), (0, 0, 0), 5.972e24, 6.371e6)),
+            Box::new(probe)
+        ]);
+        simulation.run_for(70);
+
+        let (scenario, skipped) = Scenario::from_simulation(&simulation);
+        assert!(skipped.is_empty());
+        let text: String = scenario.to_string();
+        let loaded: AstronomicalSimulation = Scenario::parse(&text).unwrap().build();
+        assert_eq!(Scenario::from_simulation(&loaded).0.to_string(), text);
+
+        let saved: &Spacecraft = match simulation.get_celestial_from_index(1).concrete() {
+            Some(ConcreteCelestial::Spacecraft(spacecraft)) => spacecraft,
+            _ => panic!("the probe is not a spacecraft")
+        };
+        let restored: &Spacecraft = match loaded.get_celestial_from_index(1).concrete() {
+            Some(ConcreteCelestial::Spacecraft(spacecraft)) => spacecraft,
+            _ => panic!("the probe was not loaded as a spacecraft")
+        };
+        assert!(restored.propellant_mass() < Mass::from(187.3));
+        assert_eq!(restored.point_body().mass, saved.point_body().mass);
+        assert_eq!(restored.point_body().velocity, saved.point_body().velocity);
+        assert_eq!(restored.dry_mass, saved.dry_mass);
+        assert_eq!(restored.specific_impulse, saved.specific_impulse);
+        assert_eq!(restored.max_thrust, saved.max_thrust);
+        assert_eq!(restored.reference, saved.reference);
+        assert_eq!(restored.burns.len(), 3);
+        for (restored, saved) in restored.burns.iter().zip(saved.burns.iter()) {
+            assert_eq!((restored.start, restored.duration), (saved.start, saved.duration));
+            assert_eq!(restored.direction.to_string(), saved.direction.to_string());
+        }
+    }
+
+    #[test]
+    fn saved_scenarios_restart_from_the_same_state() {
+        let solar_system = || astronomical_simulation!(3600, vec![
+            Box::new(star!("Sun", 0, 0, 2e30, 7e8)),
+            Box::new(planet!("Earth", (0, 0, 29_780), (1.496e11, 0, 0), 5.97e24, 6.4e6)),
+            Box::new(planet!("Mars", (1000, 0, -24_000), (-2.28e11, 0, 1e10), 6.4e23, 3.4e6))
+        ]);
+        let mut straight: AstronomicalSimulation = solar_system();
+        straight.run_for(500);
+
+        let mut first: AstronomicalSimulation = solar_system();
+        first.run_for(200);
+        let (scenario, skipped) = Scenario::from_simulation(&first);
+        assert!(skipped.is_empty());
+        let mut restarted: AstronomicalSimulation = Scenario::parse(&scenario.to_string()).unwrap().build();
+        assert_eq!(restarted.get_time(), first.get_time());
+        for (restarted, saved) in restarted.get_celestials().iter().zip(first.get_celestials()) {
+            assert_eq!(restarted.get_name(), saved.get_name());
+            assert_eq!(restarted.point_body().coordinates, saved.point_body().coordinates);
+            assert_eq!(restarted.point_body().velocity, saved.point_body().velocity);
+            assert_eq!(restarted.point_body().mass, saved.point_body().mass);
+        }
+
+        // The scenarios do not keep the compensations of the summations, only the checkpoints restart bit-identically
+        restarted.run_for(300);
+        assert_eq!(restarted.get_time(), straight.get_time());
+        for (restarted, straight) in restarted.get_celestials().iter().zip(straight.get_celestials()) {
+            let position_error: Scalar = restarted.point_body().coordinates.distance(&straight.point_body().coordinates);
+            let velocity_error: Scalar = (restarted.point_body().velocity - straight.point_body().velocity).magnitude();
+            assert!(position_error < scalar!(1e-3), "{}: {position_error} m", straight.get_name());
+            assert!(velocity_error < scalar!(1e-9), "{}: {velocity_error} m/s", straight.get_name());
+        }
+    }
+
+    #[test]
+    fn engines_belong_to_spacecraft() {
+        let error: ScenarioError = Scenario::parse("dt: 1 s\nMoon:\n    type: Satellite\n    mass: 1 kg\n    radius: 1 m\n    thrust: 1 N\n")
+            .unwrap_err();
+        assert!(matches!(error, ScenarioError::Syntax { line: 6, .. }), "{error}");
+
+        let error: ScenarioError = Scenario::parse("dt: 1 s\nProbe:\n    type: Spacecraft\n    mass: 1 kg\n    radius: 1 m\n    dry mass: 1 kg\n    specific impulse: 300 s\n    thrust: 1 N\n    burn: 1 s, 2 s, sideways\n")
+            .unwrap_err();
+        assert!(matches!(error, ScenarioError::Syntax { line: 9, column: 21, .. }), "{error}");
+    }
+
+    #[test]
+    fn kernel_and_tables_share_the_center() {
+        let directory: std::path::PathBuf = std::env::temp_dir().join(format!("mixed-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("kernel.bsp"), crate::astronomy::spk::tests::type_2_kernel(&[
+            (10, 0, [[1e6, 1e10, 0.0], [2e6, 0.0, 0.0], [3e6, 0.0, 0.0]]),
+            (399, 10, [[1.5e8, 0.0, 0.0], [0.0; 3], [0.0; 3]])
+        ])).unwrap();
+        std::fs::write(directory.join("moon.txt"), "\
+Target body name: Moon (301)
+Center body name: Sun (10)
+Output units    : KM-S
+Reference plane : Ecliptic of J2000.0
+$$SOE
+2451545.000000000 = A.D. 2000-Jan-01 12:00:00.0000 TDB
+ X = 1.000000000000000E+08 Y = 0.000000000000000E+00 Z = 0.000000000000000E+00
+ VX= 0.000000000000000E+00 VY= 3.000000000000000E+01 VZ= 0.000000000000000E+00
+$$EOE
+").unwrap();
+        let bodies = "\
+Earth:
+    type: Rock Planet
+    radius: 6378 km
+    mass: 1 EM
+    naif: 399
+Moon:
+    type: Satellite
+    radius: 1737 km
+    mass: 7.35 * 10^22 kg
+    horizons: moon.txt
+";
+        let path: std::path::PathBuf = directory.join("mixed.psa");
+        std::fs::write(&path, format!("dt: 1 h\nepoch: 2451545.0\nkernel: kernel.bsp\n{bodies}")).unwrap();
+        let scenario: Result<Scenario, ScenarioError> = Scenario::load(&path);
+
+        // Without a kernel, a table relative to another body than the `center` can not be moved
+        std::fs::write(&path, "dt: 1 h\ncenter: 0\nMoon:\n    type: Satellite\n    radius: 1737 km\n    mass: 1 kg\n    horizons: moon.txt\n").unwrap();
+        let mismatch: Result<Scenario, ScenarioError> = Scenario::load(&path);
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // Both states are relative to the Solar System barycenter
+        let scenario: Scenario = scenario.unwrap();
+        let sun: Vector = ecliptic_to_simulation([1e6, 2e6, 3e6]) * scalar!(1000);
+        let earth: &BodyDescription = &scenario.bodies[0];
+        let moon: &BodyDescription = &scenario.bodies[1];
+        let expected_earth: Vector = sun + ecliptic_to_simulation([1.5e8, 0.0, 0.0]) * scalar!(1000);
+        let expected_moon: Vector = sun + ecliptic_to_simulation([1e8, 0.0, 0.0]) * scalar!(1000);
+        assert!((Vector::from_point(earth.coordinates) - expected_earth).magnitude() < scalar!(1e-3), "{:?}", earth.coordinates);
+        assert!((Vector::from_point(moon.coordinates) - expected_moon).magnitude() < scalar!(1e-3), "{:?}", moon.coordinates);
+        let expected_velocity: Vector = ecliptic_to_simulation([10.0, 30.0, 0.0]) * scalar!(1000);
+        assert!((moon.velocity - expected_velocity).magnitude() < scalar!(1e-9), "{:?}", moon.velocity);
+
+        let error: ScenarioError = mismatch.unwrap_err();
+        assert!(matches!(error, ScenarioError::Syntax { line: 7, .. }), "{error}");
+        assert!(error.to_string().contains("'Sun', but the earlier ones to 'Solar System Barycenter'"), "{error}");
+    }
+}
@@ -0,0 +1,968 @@
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+
+
+const TWO_PI: f64 = 2.0 * PI;
+const X2O3: f64 = 2.0 / 3.0;
+
+/// The rotation rate of the Earth (radians / minute)
+const RPTIM: f64 = 4.375_269_088_011_3e-3;
+
+
+/// # Gravity Model
+/// The Earth constants of the propagator.
+/// The TLEs are fitted with `Wgs72`, which is also the model of the Vallado test vectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GravityModel {
+    #[default]
+    Wgs72,
+    Wgs84
+}
+
+
+/// The constants of a gravity model: (radius in km, xke in 1/min, j2, j3, j4)
+struct Constants {
+    radius: f64,
+    xke: f64,
+    j2: f64,
+    j3: f64,
+    j4: f64
+}
+
+
+impl GravityModel {
+    fn constants(&self) -> Constants {
+        let (mu, radius, j2, j3, j4): (f64, f64, f64, f64, f64) = match self {
+            Self::Wgs72 => (398_600.8, 6378.135, 0.001_082_616, -0.000_002_538_81, -0.000_001_655_97),
+            Self::Wgs84 => (398_600.5, 6378.137, 0.001_082_629_989_05, -0.000_002_532_153_06, -0.000_001_610_987_61)
+        };
+        Constants { radius, xke: 60.0 / (radius * radius * radius / mu).sqrt(), j2, j3, j4 }
+    }
+}
+
+
+/// # SGP4 Error
+/// The reasons the propagation can fail, with the error codes of the reference implementation
+/// - `Eccentricity` (1) - The mean eccentricity is out of range\
+/// - `MeanMotion` (2) - The mean motion is negative\
+/// - `PerturbedEccentricity` (3) - The perturbed eccentricity is out of range\
+/// - `SemiLatusRectum` (4) - The semi-latus rectum is negative\
+/// - `Decayed` (6) - The satellite has decayed (it is below the surface of the Earth)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sgp4Error {
+    Eccentricity,
+    MeanMotion,
+    PerturbedEccentricity,
+    SemiLatusRectum,
+    Decayed
+}
+
+
+impl Display for Sgp4Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Eccentricity => write!(f, "the mean eccentricity is out of range"),
+            Self::MeanMotion => write!(f, "the mean motion is negative"),
+            Self::PerturbedEccentricity => write!(f, "the perturbed eccentricity is out of range"),
+            Self::SemiLatusRectum => write!(f, "the semi-latus rectum is negative"),
+            Self::Decayed => write!(f, "the satellite has decayed")
+        }
+    }
+}
+
+
+impl std::error::Error for Sgp4Error {}
+
+
+/// # TEME State
+/// A position (km) and a velocity (km/s) in the True Equator, Mean Equinox frame of the Earth
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemeState {
+    pub position: [f64; 3],
+    pub velocity: [f64; 3]
+}
+
+
+/// The mean elements of a satellite, as read from a TLE
+/// ## Attributes
+/// `epoch: f64` - The Julian day (UTC) of the elements\
+/// `bstar: f64` - The drag term (1 / Earth radii)\
+/// `inclination`, `raan`, `argument_of_perigee`, `mean_anomaly` - The angles (radians)\
+/// `eccentricity: f64` - The eccentricity\
+/// `mean_motion: f64` - The Kozai mean motion (radians / minute)
+#[derive(Debug, Clone, Copy)]
+pub struct MeanElements {
+    pub epoch: f64,
+    pub bstar: f64,
+    pub inclination: f64,
+    pub raan: f64,
+    pub eccentricity: f64,
+    pub argument_of_perigee: f64,
+    pub mean_anomaly: f64,
+    pub mean_motion: f64
+}
+
+
+/// The lunar-solar terms of the deep-space model
+#[derive(Debug, Clone, Copy, Default)]
+struct DeepSpace {
+    e3: f64, ee2: f64, se2: f64, se3: f64, sgh2: f64, sgh3: f64, sgh4: f64, sh2: f64, sh3: f64,
+    si2: f64, si3: f64, sl2: f64, sl3: f64, sl4: f64, xgh2: f64, xgh3: f64, xgh4: f64,
+    xh2: f64, xh3: f64, xi2: f64, xi3: f64, xl2: f64, xl3: f64, xl4: f64, zmol: f64, zmos: f64,
+
+    // Secular rates
+    dedt: f64, didt: f64, dmdt: f64, dnodt: f64, domdt: f64,
+
+    // Resonance
+    irez: u8,
+    d2201: f64, d2211: f64, d3210: f64, d3222: f64, d4410: f64, d4422: f64,
+    d5220: f64, d5232: f64, d5421: f64, d5433: f64,
+    del1: f64, del2: f64, del3: f64,
+    xfact: f64, xlamo: f64
+}
+
+
+/// # SGP4
+/// The SGP4/SDP4 propagator of the two-line element sets (Vallado et al., "Revisiting Spacetrack Report #3", 2006).
+/// Satellites with a period of 225 minutes or longer use the deep-space (SDP4) terms
+/// of the Sun and the Moon, and the resonances of the 12 and 24 hour orbits.
+/// The states are in the TEME frame of the Earth.
+#[derive(Debug, Clone)]
+pub struct Sgp4 {
+    pub elements: MeanElements,
+    pub model: GravityModel,
+    deep_space: Option<DeepSpace>,
+    simple: bool,
+
+    // The recovered (Brouwer) mean motion
+    no: f64,
+    gsto: f64,
+
+    aycof: f64, con41: f64, cc1: f64, cc4: f64, cc5: f64, d2: f64, d3: f64, d4: f64,
+    delmo: f64, eta: f64, argpdot: f64, omgcof: f64, sinmao: f64, t2cof: f64, t3cof: f64,
+    t4cof: f64, t5cof: f64, x1mth2: f64, x7thm1: f64, mdot: f64, nodedot: f64, xlcof: f64,
+    xmcof: f64, nodecf: f64
+}
+
+
+impl Sgp4 {
+    /// Initialise the propagator with the WGS-72 constants
+    pub fn new(elements: MeanElements) -> Result<Self, Sgp4Error> {
+        Self::with_model(elements, GravityModel::Wgs72)
+    }
+
+    /// Initialise the propagator
+    pub fn with_model(elements: MeanElements, model: GravityModel) -> Result<Self, Sgp4Error> {
+        let constants: Constants = model.constants();
+        let (radius, xke, j2, j4): (f64, f64, f64, f64) = (constants.radius, constants.xke, constants.j2, constants.j4);
+        let j3oj2: f64 = constants.j3 / j2;
+
+        let ecco: f64 = elements.eccentricity;
+        let inclo: f64 = elements.inclination;
+        let argpo: f64 = elements.argument_of_perigee;
+        let mo: f64 = elements.mean_anomaly;
+        let bstar: f64 = elements.bstar;
+        let epoch: f64 = elements.epoch - 2_433_281.5;
+
+        let ss: f64 = 78.0 / radius + 1.0;
+        let qzms2t: f64 = ((120.0 - 78.0) / radius).powi(4);
+
+        // Recover the original mean motion and the semi-major axis from the Kozai mean motion
+        let eccsq: f64 = ecco * ecco;
+        let omeosq: f64 = 1.0 - eccsq;
+        let rteosq: f64 = omeosq.sqrt();
+        let cosio: f64 = inclo.cos();
+        let cosio2: f64 = cosio * cosio;
+        let ak: f64 = (xke / elements.mean_motion).powf(X2O3);
+        let d1: f64 = 0.75 * j2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let mut del: f64 = d1 / (ak * ak);
+        let adel: f64 = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        del = d1 / (adel * adel);
+        let no: f64 = elements.mean_motion / (1.0 + del);
+        let ao: f64 = (xke / no).powf(X2O3);
+        let sinio: f64 = inclo.sin();
+        let po: f64 = ao * omeosq;
+        let con42: f64 = 1.0 - 5.0 * cosio2;
+        let con41: f64 = -con42 - cosio2 - cosio2;
+        let posq: f64 = po * po;
+        let rp: f64 = ao * (1.0 - ecco);
+        let gsto: f64 = greenwich_sidereal_time(epoch + 2_433_281.5);
+
+        if omeosq < 0.0 && no < 0.0 {
+            return Err(Sgp4Error::Eccentricity);
+        }
+
+        let mut simple: bool = rp < 220.0 / radius + 1.0;
+
+        // For perigees below 156 km, the values of s and qoms2t are altered
+        let mut sfour: f64 = ss;
+        let mut qzms24: f64 = qzms2t;
+        let perigee: f64 = (rp - 1.0) * radius;
+        if perigee < 156.0 {
+            sfour = if perigee < 98.0 { 20.0 } else { perigee - 78.0 };
+            qzms24 = ((120.0 - sfour) / radius).powi(4);
+            sfour = sfour / radius + 1.0;
+        }
+
+        let pinvsq: f64 = 1.0 / posq;
+        let tsi: f64 = 1.0 / (ao - sfour);
+        let eta: f64 = ao * ecco * tsi;
+        let etasq: f64 = eta * eta;
+        let eeta: f64 = ecco * eta;
+        let psisq: f64 = (1.0 - etasq).abs();
+        let coef: f64 = qzms24 * tsi.powi(4);
+        let coef1: f64 = coef / psisq.powf(3.5);
+        let cc2: f64 = coef1 * no * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+            + 0.375 * j2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1: f64 = bstar * cc2;
+        let cc3: f64 = if ecco > 1.0e-4 { -2.0 * coef * tsi * j3oj2 * no * sinio / ecco } else { 0.0 };
+        let x1mth2: f64 = 1.0 - cosio2;
+        let cc4: f64 = 2.0 * no * coef1 * ao * omeosq * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+            - j2 * tsi / (ao * psisq) * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+            + 0.75 * x1mth2 * (2.0 * etasq - eeta * (1.0 + etasq)) * (2.0 * argpo).cos()));
+        let cc5: f64 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+        let cosio4: f64 = cosio2 * cosio2;
+        let temp1: f64 = 1.5 * j2 * pinvsq * no;
+        let temp2: f64 = 0.5 * temp1 * j2 * pinvsq;
+        let temp3: f64 = -0.46875 * j4 * pinvsq * pinvsq * no;
+        let mdot: f64 = no + 0.5 * temp1 * rteosq * con41 + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot: f64 = -0.5 * temp1 * con42 + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1: f64 = -temp1 * cosio;
+        let nodedot: f64 = xhdot1 + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+        let xpidot: f64 = argpdot + nodedot;
+        let omgcof: f64 = bstar * cc3 * argpo.cos();
+        let xmcof: f64 = if ecco > 1.0e-4 { -X2O3 * coef * bstar / eeta } else { 0.0 };
+        let nodecf: f64 = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof: f64 = 1.5 * cc1;
+        let xlcof: f64 = -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / non_zero(1.0 + cosio);
+        let aycof: f64 = -0.5 * j3oj2 * sinio;
+        let delmo: f64 = (1.0 + eta * mo.cos()).powi(3);
+        let sinmao: f64 = mo.sin();
+        let x7thm1: f64 = 7.0 * cosio2 - 1.0;
+
+        // Deep space: the period is 225 minutes or longer
+        let mut deep_space: Option<DeepSpace> = None;
+        if TWO_PI / no >= 225.0 {
+            simple = true;
+            let common: Common = Common::new(epoch, ecco, argpo, 0.0, inclo, elements.raan, no);
+            let mut deep: DeepSpace = common.terms();
+            deep.initialise(&common, &InitialState {
+                xke, gsto, ecco, eccsq, inclo, argpo, nodeo: elements.raan, mo, no, mdot, nodedot, xpidot
+            });
+            deep_space = Some(deep);
+        }
+
+        let (mut d2, mut d3, mut d4, mut t3cof, mut t4cof, mut t5cof): (f64, f64, f64, f64, f64, f64) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        if !simple {
+            let cc1sq: f64 = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp: f64 = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2 * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        let propagator: Self = Self {
+            elements, model, deep_space, simple, no, gsto,
+            aycof, con41, cc1, cc4, cc5, d2, d3, d4, delmo, eta, argpdot, omgcof, sinmao,
+            t2cof, t3cof, t4cof, t5cof, x1mth2, x7thm1, mdot, nodedot, xlcof, xmcof, nodecf
+        };
+
+        // Check the elements at the epoch
+        propagator.propagate(0.0)?;
+        Ok(propagator)
+    }
+
+    /// Is the deep-space (SDP4) model used?
+    pub fn is_deep_space(&self) -> bool { self.deep_space.is_some() }
+
+    /// Calculate the state at the given Julian day (UTC)
+    pub fn propagate_to(&self, julian_day: f64) -> Result<TemeState, Sgp4Error> {
+        self.propagate((julian_day - self.elements.epoch) * 1440.0)
+    }
+
+    /// Calculate the state `minutes` minutes after the epoch of the elements
+    pub fn propagate(&self, minutes: f64) -> Result<TemeState, Sgp4Error> {
+        let constants: Constants = self.model.constants();
+        let (radius, xke, j2): (f64, f64, f64) = (constants.radius, constants.xke, constants.j2);
+        let j3oj2: f64 = constants.j3 / j2;
+        let vkmpersec: f64 = radius * xke / 60.0;
+        let elements: &MeanElements = &self.elements;
+        let t: f64 = minutes;
+
+        // Secular gravity and atmospheric drag
+        let xmdf: f64 = elements.mean_anomaly + self.mdot * t;
+        let argpdf: f64 = elements.argument_of_perigee + self.argpdot * t;
+        let nodedf: f64 = elements.raan + self.nodedot * t;
+        let mut argpm: f64 = argpdf;
+        let mut mm: f64 = xmdf;
+        let t2: f64 = t * t;
+        let mut nodem: f64 = nodedf + self.nodecf * t2;
+        let mut tempa: f64 = 1.0 - self.cc1 * t;
+        let mut tempe: f64 = elements.bstar * self.cc4 * t;
+        let mut templ: f64 = self.t2cof * t2;
+
+        if !self.simple {
+            let delomg: f64 = self.omgcof * t;
+            let delm: f64 = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp: f64 = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3: f64 = t2 * t;
+            let t4: f64 = t3 * t;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe += elements.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ = templ + self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+
+        let mut nm: f64 = self.no;
+        let mut em: f64 = elements.eccentricity;
+        let mut inclm: f64 = elements.inclination;
+        if let Some(deep) = &self.deep_space {
+            let secular: Secular = deep.secular(self, t, em, argpm, inclm, mm, nodem);
+            em = secular.em;
+            argpm = secular.argpm;
+            inclm = secular.inclm;
+            mm = secular.mm;
+            nodem = secular.nodem;
+            nm = secular.nm;
+        }
+
+        if nm <= 0.0 {
+            return Err(Sgp4Error::MeanMotion);
+        }
+        let am: f64 = (xke / nm).powf(X2O3) * tempa * tempa;
+        nm = xke / am.powf(1.5);
+        em -= tempe;
+
+        if !(-0.001..1.0).contains(&em) {
+            return Err(Sgp4Error::Eccentricity);
+        }
+        em = em.max(1.0e-6);
+        mm += self.no * templ;
+        let xlm: f64 = mm + argpm + nodem;
+        nodem %= TWO_PI;
+        argpm %= TWO_PI;
+        let xlm: f64 = xlm % TWO_PI;
+        mm = (xlm - argpm - nodem) % TWO_PI;
+
+        // Lunar-solar periodics
+        let mut ep: f64 = em;
+        let mut xincp: f64 = inclm;
+        let mut argpp: f64 = argpm;
+        let mut nodep: f64 = nodem;
+        let mut mp: f64 = mm;
+        let mut sinip: f64 = inclm.sin();
+        let mut cosip: f64 = inclm.cos();
+        let mut aycof: f64 = self.aycof;
+        let mut xlcof: f64 = self.xlcof;
+        let mut con41: f64 = self.con41;
+        let mut x1mth2: f64 = self.x1mth2;
+        let mut x7thm1: f64 = self.x7thm1;
+
+        if let Some(deep) = &self.deep_space {
+            deep.periodics(t, &mut ep, &mut xincp, &mut nodep, &mut argpp, &mut mp);
+            if xincp < 0.0 {
+                xincp = -xincp;
+                nodep += PI;
+                argpp -= PI;
+            }
+            if !(0.0..=1.0).contains(&ep) {
+                return Err(Sgp4Error::PerturbedEccentricity);
+            }
+
+            sinip = xincp.sin();
+            cosip = xincp.cos();
+            aycof = -0.5 * j3oj2 * sinip;
+            xlcof = -0.25 * j3oj2 * sinip * (3.0 + 5.0 * cosip) / non_zero(1.0 + cosip);
+
+            let cosisq: f64 = cosip * cosip;
+            con41 = 3.0 * cosisq - 1.0;
+            x1mth2 = 1.0 - cosisq;
+            x7thm1 = 7.0 * cosisq - 1.0;
+        }
+
+        // Long period periodics
+        let axnl: f64 = ep * argpp.cos();
+        let temp: f64 = 1.0 / (am * (1.0 - ep * ep));
+        let aynl: f64 = ep * argpp.sin() + temp * aycof;
+        let xl: f64 = mp + argpp + nodep + temp * xlcof * axnl;
+
+        // Solve Kepler's equation
+        let u: f64 = (xl - nodep) % TWO_PI;
+        let mut eo1: f64 = u;
+        let mut tem5: f64 = 9999.9;
+        let mut sineo1: f64 = 0.0;
+        let mut coseo1: f64 = 0.0;
+        let mut iteration: u8 = 1;
+        while tem5.abs() >= 1.0e-12 && iteration <= 10 {
+            sineo1 = eo1.sin();
+            coseo1 = eo1.cos();
+            tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            tem5 = tem5.clamp(-0.95, 0.95);
+            eo1 += tem5;
+            iteration += 1;
+        }
+
+        // Short period preliminary quantities
+        let ecose: f64 = axnl * coseo1 + aynl * sineo1;
+        let esine: f64 = axnl * sineo1 - aynl * coseo1;
+        let el2: f64 = axnl * axnl + aynl * aynl;
+        let pl: f64 = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err(Sgp4Error::SemiLatusRectum);
+        }
+
+        let rl: f64 = am * (1.0 - ecose);
+        let rdotl: f64 = am.sqrt() * esine / rl;
+        let rvdotl: f64 = pl.sqrt() / rl;
+        let betal: f64 = (1.0 - el2).sqrt();
+        let temp: f64 = esine / (1.0 + betal);
+        let sinu: f64 = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu: f64 = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su: f64 = sinu.atan2(cosu);
+        let sin2u: f64 = (cosu + cosu) * sinu;
+        let cos2u: f64 = 1.0 - 2.0 * sinu * sinu;
+        let temp: f64 = 1.0 / pl;
+        let temp1: f64 = 0.5 * j2 * temp;
+        let temp2: f64 = temp1 * temp;
+
+        // Update for short period periodics
+        let mrt: f64 = rl * (1.0 - 1.5 * temp2 * betal * con41) + 0.5 * temp1 * x1mth2 * cos2u;
+        su -= 0.25 * temp2 * x7thm1 * sin2u;
+        let xnode: f64 = nodep + 1.5 * temp2 * cosip * sin2u;
+        let xinc: f64 = xincp + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt: f64 = rdotl - nm * temp1 * x1mth2 * sin2u / xke;
+        let rvdot: f64 = rvdotl + nm * temp1 * (x1mth2 * cos2u + 1.5 * con41) / xke;
+
+        // Orientation vectors
+        let (sinsu, cossu): (f64, f64) = su.sin_cos();
+        let (snod, cnod): (f64, f64) = xnode.sin_cos();
+        let (sini, cosi): (f64, f64) = xinc.sin_cos();
+        let xmx: f64 = -snod * cosi;
+        let xmy: f64 = cnod * cosi;
+        let ux: [f64; 3] = [xmx * sinsu + cnod * cossu, xmy * sinsu + snod * cossu, sini * sinsu];
+        let vx: [f64; 3] = [xmx * cossu - cnod * sinsu, xmy * cossu - snod * sinsu, sini * cossu];
+
+        if mrt < 1.0 {
+            return Err(Sgp4Error::Decayed);
+        }
+
+        let mut state: TemeState = TemeState { position: [0.0; 3], velocity: [0.0; 3] };
+        for axis in 0..3 {
+            state.position[axis] = mrt * ux[axis] * radius;
+            state.velocity[axis] = (mvt * ux[axis] + rvdot * vx[axis]) * vkmpersec;
+        }
+        Ok(state)
+    }
+}
+
+
+/// Avoid the division by zero for the retrograde equatorial orbits
+fn non_zero(value: f64) -> f64 {
+    if value.abs() > 1.5e-12 { value } else { 1.5e-12 }
+}
+
+
+/// The Greenwich sidereal time (radians) at the given Julian day (UT1)
+pub fn greenwich_sidereal_time(julian_day: f64) -> f64 {
+    let tut1: f64 = (julian_day - 2_451_545.0) / 36_525.0;
+    let seconds: f64 = -6.2e-6 * tut1 * tut1 * tut1 + 0.093104 * tut1 * tut1
+        + (876600.0 * 3600.0 + 8640184.812866) * tut1 + 67310.54841;
+    (seconds * PI / 180.0 / 240.0).rem_euclid(TWO_PI)
+}
+
+
+/// The common quantities of the deep-space terms (`dscom` of the reference implementation)
+struct Common {
+    em: f64, emsq: f64, nm: f64,
+    sinim: f64, cosim: f64,
+    s1: f64, s2: f64, s3: f64, s4: f64, s5: f64,
+    ss1: f64, ss2: f64, ss3: f64, ss4: f64, ss5: f64,
+    z1: f64, z3: f64, z11: f64, z13: f64, z21: f64, z23: f64, z31: f64, z33: f64,
+    sz1: f64, sz3: f64, sz11: f64, sz13: f64, sz21: f64, sz23: f64, sz31: f64, sz33: f64,
+    terms: DeepSpace
+}
+
+
+impl Common {
+    fn new(epoch: f64, ep: f64, argpp: f64, tc: f64, inclp: f64, nodep: f64, np: f64) -> Self {
+        const ZES: f64 = 0.01675;
+        const ZEL: f64 = 0.05490;
+        const C1SS: f64 = 2.9864797e-6;
+        const C1L: f64 = 4.7968065e-7;
+        const ZSINIS: f64 = 0.39785416;
+        const ZCOSIS: f64 = 0.91744867;
+        const ZCOSGS: f64 = 0.1945905;
+        const ZSINGS: f64 = -0.98152904;
+
+        let nm: f64 = np;
+        let em: f64 = ep;
+        let (snodm, cnodm): (f64, f64) = nodep.sin_cos();
+        let (sinomm, cosomm): (f64, f64) = argpp.sin_cos();
+        let (sinim, cosim): (f64, f64) = inclp.sin_cos();
+        let emsq: f64 = em * em;
+        let betasq: f64 = 1.0 - emsq;
+        let rtemsq: f64 = betasq.sqrt();
+
+        // Initialise the lunar and solar terms
+        let day: f64 = epoch + 18_261.5 + tc / 1440.0;
+        let xnodce: f64 = (4.5236020 - 9.2422029e-4 * day) % TWO_PI;
+        let (stem, ctem): (f64, f64) = xnodce.sin_cos();
+        let zcosil: f64 = 0.91375164 - 0.03568096 * ctem;
+        let zsinil: f64 = (1.0 - zcosil * zcosil).sqrt();
+        let zsinhl: f64 = 0.089683511 * stem / zsinil;
+        let zcoshl: f64 = (1.0 - zsinhl * zsinhl).sqrt();
+        let gam: f64 = 5.8351514 + 0.0019443680 * day;
+        let zx: f64 = 0.39785416 * stem / zsinil;
+        let zy: f64 = zcoshl * ctem + 0.91744867 * zsinhl * stem;
+        let zx: f64 = gam + zx.atan2(zy) - xnodce;
+        let (zsingl, zcosgl): (f64, f64) = zx.sin_cos();
+
+        // The solar terms first, then the lunar ones
+        let mut zcosg: f64 = ZCOSGS;
+        let mut zsing: f64 = ZSINGS;
+        let mut zcosi: f64 = ZCOSIS;
+        let mut zsini: f64 = ZSINIS;
+        let mut zcosh: f64 = cnodm;
+        let mut zsinh: f64 = snodm;
+        let mut cc: f64 = C1SS;
+        let xnoi: f64 = 1.0 / nm;
+
+        // [s1..s7, z1, z2, z3, z11, z12, z13, z21, z22, z23, z31, z32, z33] for the Sun and the Moon
+        let mut values: [[f64; 19]; 2] = [[0.0; 19]; 2];
+        for body in values.iter_mut() {
+            let a1: f64 = zcosg * zcosh + zsing * zcosi * zsinh;
+            let a3: f64 = -zsing * zcosh + zcosg * zcosi * zsinh;
+            let a7: f64 = -zcosg * zsinh + zsing * zcosi * zcosh;
+            let a8: f64 = zsing * zsini;
+            let a9: f64 = zsing * zsinh + zcosg * zcosi * zcosh;
+            let a10: f64 = zcosg * zsini;
+            let a2: f64 = cosim * a7 + sinim * a8;
+            let a4: f64 = cosim * a9 + sinim * a10;
+            let a5: f64 = -sinim * a7 + cosim * a8;
+            let a6: f64 = -sinim * a9 + cosim * a10;
+
+            let x1: f64 = a1 * cosomm + a2 * sinomm;
+            let x2: f64 = a3 * cosomm + a4 * sinomm;
+            let x3: f64 = -a1 * sinomm + a2 * cosomm;
+            let x4: f64 = -a3 * sinomm + a4 * cosomm;
+            let x5: f64 = a5 * sinomm;
+            let x6: f64 = a6 * sinomm;
+            let x7: f64 = a5 * cosomm;
+            let x8: f64 = a6 * cosomm;
+
+            let z31: f64 = 12.0 * x1 * x1 - 3.0 * x3 * x3;
+            let z32: f64 = 24.0 * x1 * x2 - 6.0 * x3 * x4;
+            let z33: f64 = 12.0 * x2 * x2 - 3.0 * x4 * x4;
+            let mut z1: f64 = 3.0 * (a1 * a1 + a2 * a2) + z31 * emsq;
+            let mut z2: f64 = 6.0 * (a1 * a3 + a2 * a4) + z32 * emsq;
+            let mut z3: f64 = 3.0 * (a3 * a3 + a4 * a4) + z33 * emsq;
+            let z11: f64 = -6.0 * a1 * a5 + emsq * (-24.0 * x1 * x7 - 6.0 * x3 * x5);
+            let z12: f64 = -6.0 * (a1 * a6 + a3 * a5) + emsq * (-24.0 * (x2 * x7 + x1 * x8) - 6.0 * (x3 * x6 + x4 * x5));
+            let z13: f64 = -6.0 * a3 * a6 + emsq * (-24.0 * x2 * x8 - 6.0 * x4 * x6);
+            let z21: f64 = 6.0 * a2 * a5 + emsq * (24.0 * x1 * x5 - 6.0 * x3 * x7);
+            let z22: f64 = 6.0 * (a4 * a5 + a2 * a6) + emsq * (24.0 * (x2 * x5 + x1 * x6) - 6.0 * (x4 * x7 + x3 * x8));
+            let z23: f64 = 6.0 * a4 * a6 + emsq * (24.0 * x2 * x6 - 6.0 * x4 * x8);
+            z1 = z1 + z1 + betasq * z31;
+            z2 = z2 + z2 + betasq * z32;
+            z3 = z3 + z3 + betasq * z33;
+
+            let s3: f64 = cc * xnoi;
+            let s2: f64 = -0.5 * s3 / rtemsq;
+            let s4: f64 = s3 * rtemsq;
+            let s1: f64 = -15.0 * em * s4;
+            let s5: f64 = x1 * x3 + x2 * x4;
+            let s6: f64 = x2 * x3 + x1 * x4;
+            let s7: f64 = x2 * x4 - x1 * x3;
+            *body = [s1, s2, s3, s4, s5, s6, s7, z1, z2, z3, z11, z12, z13, z21, z22, z23, z31, z32, z33];
+
+            // Switch to the Moon
+            zcosg = zcosgl;
+            zsing = zsingl;
+            zcosi = zcosil;
+            zsini = zsinil;
+            zcosh = zcoshl * cnodm + zsinhl * snodm;
+            zsinh = snodm * zcoshl - cnodm * zsinhl;
+            cc = C1L;
+        }
+
+        let [ss1, ss2, ss3, ss4, ss5, ss6, ss7, sz1, sz2, sz3, sz11, sz12, sz13, sz21, sz22, sz23, sz31, sz32, sz33] = values[0];
+        let [s1, s2, s3, s4, s5, s6, s7, z1, z2, z3, z11, z12, z13, z21, z22, z23, z31, z32, z33] = values[1];
+
+        let terms: DeepSpace = DeepSpace {
+            zmol: (4.7199672 + 0.22997150 * day - gam) % TWO_PI,
+            zmos: (6.2565837 + 0.017201977 * day) % TWO_PI,
+
+            // Solar terms
+            se2: 2.0 * ss1 * ss6,
+            se3: 2.0 * ss1 * ss7,
+            si2: 2.0 * ss2 * sz12,
+            si3: 2.0 * ss2 * (sz13 - sz11),
+            sl2: -2.0 * ss3 * sz2,
+            sl3: -2.0 * ss3 * (sz3 - sz1),
+            sl4: -2.0 * ss3 * (-21.0 - 9.0 * emsq) * ZES,
+            sgh2: 2.0 * ss4 * sz32,
+            sgh3: 2.0 * ss4 * (sz33 - sz31),
+            sgh4: -18.0 * ss4 * ZES,
+            sh2: -2.0 * ss2 * sz22,
+            sh3: -2.0 * ss2 * (sz23 - sz21),
+
+            // Lunar terms
+            ee2: 2.0 * s1 * s6,
+            e3: 2.0 * s1 * s7,
+            xi2: 2.0 * s2 * z12,
+            xi3: 2.0 * s2 * (z13 - z11),
+            xl2: -2.0 * s3 * z2,
+            xl3: -2.0 * s3 * (z3 - z1),
+            xl4: -2.0 * s3 * (-21.0 - 9.0 * emsq) * ZEL,
+            xgh2: 2.0 * s4 * z32,
+            xgh3: 2.0 * s4 * (z33 - z31),
+            xgh4: -18.0 * s4 * ZEL,
+            xh2: -2.0 * s2 * z22,
+            xh3: -2.0 * s2 * (z23 - z21),
+            ..DeepSpace::default()
+        };
+
+        Self {
+            em, emsq, nm, sinim, cosim,
+            s1, s2, s3, s4, s5, ss1, ss2, ss3, ss4, ss5,
+            z1, z3, z11, z13, z21, z23, z31, z33,
+            sz1, sz3, sz11, sz13, sz21, sz23, sz31, sz33,
+            terms
+        }
+    }
+
+    fn terms(&self) -> DeepSpace { self.terms }
+}
+
+
+/// The initial values needed by the deep-space initialisation
+struct InitialState {
+    xke: f64, gsto: f64, ecco: f64, eccsq: f64, inclo: f64, argpo: f64, nodeo: f64, mo: f64, no: f64,
+    mdot: f64, nodedot: f64, xpidot: f64
+}
+
+
+/// The mean elements after the deep-space secular effects
+struct Secular {
+    em: f64, argpm: f64, inclm: f64, mm: f64, nodem: f64, nm: f64
+}
+
+
+impl DeepSpace {
+    /// Calculate the secular rates and the resonance terms (`dsinit` of the reference implementation)
+    fn initialise(&mut self, common: &Common, state: &InitialState) {
+        const Q22: f64 = 1.7891679e-6;
+        const Q31: f64 = 2.1460748e-6;
+        const Q33: f64 = 2.2123015e-7;
+        const ROOT22: f64 = 1.7891679e-6;
+        const ROOT44: f64 = 7.3636953e-9;
+        const ROOT54: f64 = 2.1765803e-9;
+        const ROOT32: f64 = 3.7393792e-7;
+        const ROOT52: f64 = 1.1428639e-7;
+        const ZNL: f64 = 1.5835218e-4;
+        const ZNS: f64 = 1.19459e-5;
+
+        let nm: f64 = common.nm;
+        let em: f64 = common.em;
+        let emsq: f64 = common.emsq;
+        let (sinim, cosim): (f64, f64) = (common.sinim, common.cosim);
+
+        // Deep space resonance effects
+        self.irez = 0;
+        if nm < 0.0052359877 && nm > 0.0034906585 {
+            self.irez = 1;
+        }
+        if (8.26e-3..=9.24e-3).contains(&nm) && em >= 0.5 {
+            self.irez = 2;
+        }
+
+        // Solar terms
+        let ses: f64 = common.ss1 * ZNS * common.ss5;
+        let sis: f64 = common.ss2 * ZNS * (common.sz11 + common.sz13);
+        let sls: f64 = -ZNS * common.ss3 * (common.sz1 + common.sz3 - 14.0 - 6.0 * emsq);
+        let sghs: f64 = common.ss4 * ZNS * (common.sz31 + common.sz33 - 6.0);
+        let mut shs: f64 = -ZNS * common.ss2 * (common.sz21 + common.sz23);
+        let equatorial: bool = state.inclo < 5.2359877e-2 || state.inclo > PI - 5.2359877e-2;
+        if equatorial {
+            shs = 0.0;
+        }
+        if sinim != 0.0 {
+            shs /= sinim;
+        }
+        let sgs: f64 = sghs - cosim * shs;
+
+        // Lunar terms
+        self.dedt = ses + common.s1 * ZNL * common.s5;
+        self.didt = sis + common.s2 * ZNL * (common.z11 + common.z13);
+        self.dmdt = sls - ZNL * common.s3 * (common.z1 + common.z3 - 14.0 - 6.0 * emsq);
+        let sghl: f64 = common.s4 * ZNL * (common.z31 + common.z33 - 6.0);
+        let mut shll: f64 = -ZNL * common.s2 * (common.z21 + common.z23);
+        if equatorial {
+            shll = 0.0;
+        }
+        self.domdt = sgs + sghl;
+        self.dnodt = shs;
+        if sinim != 0.0 {
+            self.domdt -= cosim / sinim * shll;
+            self.dnodt += shll / sinim;
+        }
+
+        if self.irez == 0 { return; }
+
+        let theta: f64 = state.gsto % TWO_PI;
+        let aonv: f64 = (nm / state.xke).powf(X2O3);
+
+        // Geopotential resonance for the 12 hour orbits
+        if self.irez == 2 {
+            let cosisq: f64 = cosim * cosim;
+            let em: f64 = state.ecco;
+            let emsq: f64 = state.eccsq;
+            let eoc: f64 = em * emsq;
+            let g201: f64 = -0.306 - (em - 0.64) * 0.440;
+
+            let (g211, g310, g322, g410, g422, g520): (f64, f64, f64, f64, f64, f64) = if em <= 0.65 {
+                (
+                    3.616 - 13.2470 * em + 16.2900 * emsq,
+                    -19.302 + 117.3900 * em - 228.4190 * emsq + 156.5910 * eoc,
+                    -18.9068 + 109.7927 * em - 214.6334 * emsq + 146.5816 * eoc,
+                    -41.122 + 242.6940 * em - 471.0940 * emsq + 313.9530 * eoc,
+                    -146.407 + 841.8800 * em - 1629.014 * emsq + 1083.4350 * eoc,
+                    -532.114 + 3017.977 * em - 5740.032 * emsq + 3708.2760 * eoc
+                )
+            } else {
+                (
+                    -72.099 + 331.819 * em - 508.738 * emsq + 266.724 * eoc,
+                    -346.844 + 1582.851 * em - 2415.925 * emsq + 1246.113 * eoc,
+                    -342.585 + 1554.908 * em - 2366.899 * emsq + 1215.972 * eoc,
+                    -1052.797 + 4758.686 * em - 7193.992 * emsq + 3651.957 * eoc,
+                    -3581.690 + 16178.110 * em - 24462.770 * emsq + 12422.520 * eoc,
+                    if em > 0.715 { -5149.66 + 29936.92 * em - 54087.36 * emsq + 31324.56 * eoc }
+                    else { 1464.74 - 4664.75 * em + 3763.64 * emsq }
+                )
+            };
+            let (g533, g521, g532): (f64, f64, f64) = if em < 0.7 {
+                (
+                    -919.22770 + 4988.6100 * em - 9064.7700 * emsq + 5542.21 * eoc,
+                    -822.71072 + 4568.6173 * em - 8491.4146 * emsq + 5337.524 * eoc,
+                    -853.66600 + 4690.2500 * em - 8624.7700 * emsq + 5341.4 * eoc
+                )
+            } else {
+                (
+                    -37995.780 + 161616.52 * em - 229838.20 * emsq + 109377.94 * eoc,
+                    -51752.104 + 218913.95 * em - 309468.16 * emsq + 146349.42 * eoc,
+                    -40023.880 + 170470.89 * em - 242699.48 * emsq + 115605.82 * eoc
+                )
+            };
+
+            let sini2: f64 = sinim * sinim;
+            let f220: f64 = 0.75 * (1.0 + 2.0 * cosim + cosisq);
+            let f221: f64 = 1.5 * sini2;
+            let f321: f64 = 1.875 * sinim * (1.0 - 2.0 * cosim - 3.0 * cosisq);
+            let f322: f64 = -1.875 * sinim * (1.0 + 2.0 * cosim - 3.0 * cosisq);
+            let f441: f64 = 35.0 * sini2 * f220;
+            let f442: f64 = 39.3750 * sini2 * sini2;
+            let f522: f64 = 9.84375 * sinim * (sini2 * (1.0 - 2.0 * cosim - 5.0 * cosisq)
+                + 0.33333333 * (-2.0 + 4.0 * cosim + 6.0 * cosisq));
+            let f523: f64 = sinim * (4.92187512 * sini2 * (-2.0 - 4.0 * cosim + 10.0 * cosisq)
+                + 6.56250012 * (1.0 + 2.0 * cosim - 3.0 * cosisq));
+            let f542: f64 = 29.53125 * sinim * (2.0 - 8.0 * cosim + cosisq * (-12.0 + 8.0 * cosim + 10.0 * cosisq));
+            let f543: f64 = 29.53125 * sinim * (-2.0 - 8.0 * cosim + cosisq * (12.0 + 8.0 * cosim - 10.0 * cosisq));
+
+            let xno2: f64 = nm * nm;
+            let ainv2: f64 = aonv * aonv;
+            let mut temp1: f64 = 3.0 * xno2 * ainv2;
+            let mut temp: f64 = temp1 * ROOT22;
+            self.d2201 = temp * f220 * g201;
+            self.d2211 = temp * f221 * g211;
+            temp1 *= aonv;
+            temp = temp1 * ROOT32;
+            self.d3210 = temp * f321 * g310;
+            self.d3222 = temp * f322 * g322;
+            temp1 *= aonv;
+            temp = 2.0 * temp1 * ROOT44;
+            self.d4410 = temp * f441 * g410;
+            self.d4422 = temp * f442 * g422;
+            temp1 *= aonv;
+            temp = temp1 * ROOT52;
+            self.d5220 = temp * f522 * g520;
+            self.d5232 = temp * f523 * g532;
+            temp = 2.0 * temp1 * ROOT54;
+            self.d5421 = temp * f542 * g521;
+            self.d5433 = temp * f543 * g533;
+            self.xlamo = (state.mo + state.nodeo + state.nodeo - theta - theta) % TWO_PI;
+            self.xfact = state.mdot + self.dmdt + 2.0 * (state.nodedot + self.dnodt - RPTIM) - state.no;
+        }
+
+        // Synchronous resonance terms
+        if self.irez == 1 {
+            let g200: f64 = 1.0 + emsq * (-2.5 + 0.8125 * emsq);
+            let g310: f64 = 1.0 + 2.0 * emsq;
+            let g300: f64 = 1.0 + emsq * (-6.0 + 6.60937 * emsq);
+            let f220: f64 = 0.75 * (1.0 + cosim) * (1.0 + cosim);
+            let f311: f64 = 0.9375 * sinim * sinim * (1.0 + 3.0 * cosim) - 0.75 * (1.0 + cosim);
+            let f330: f64 = 1.875 * (1.0 + cosim).powi(3);
+            let del1: f64 = 3.0 * nm * nm * aonv * aonv;
+            self.del2 = 2.0 * del1 * f220 * g200 * Q22;
+            self.del3 = 3.0 * del1 * f330 * g300 * Q33 * aonv;
+            self.del1 = del1 * f311 * g310 * Q31 * aonv;
+            self.xlamo = (state.mo + state.nodeo + state.argpo - theta) % TWO_PI;
+            self.xfact = state.mdot + state.xpidot - RPTIM + self.dmdt + self.domdt + self.dnodt - state.no;
+        }
+    }
+
+    /// Apply the deep-space secular effects and the resonances (`dspace` of the reference implementation)
+    #[allow(clippy::too_many_arguments)]
+    fn secular(&self, propagator: &Sgp4, t: f64, em: f64, argpm: f64, inclm: f64, mm: f64, nodem: f64) -> Secular {
+        const FASX2: f64 = 0.13130908;
+        const FASX4: f64 = 2.8843198;
+        const FASX6: f64 = 0.37448087;
+        const G22: f64 = 5.7686396;
+        const G32: f64 = 0.95240898;
+        const G44: f64 = 1.8014998;
+        const G52: f64 = 1.0508330;
+        const G54: f64 = 4.4108898;
+        const STEPP: f64 = 720.0;
+        const STEPN: f64 = -720.0;
+        const STEP2: f64 = 259_200.0;
+
+        let no: f64 = propagator.no;
+        let mut secular: Secular = Secular {
+            em: em + self.dedt * t,
+            argpm: argpm + self.domdt * t,
+            inclm: inclm + self.didt * t,
+            mm: mm + self.dmdt * t,
+            nodem: nodem + self.dnodt * t,
+            nm: no
+        };
+        if self.irez == 0 { return secular; }
+
+        // Integrate the resonance effects from the epoch in steps of half a day
+        let theta: f64 = (propagator.gsto + t * RPTIM) % TWO_PI;
+        let delt: f64 = if t > 0.0 { STEPP } else { STEPN };
+        let argpo: f64 = propagator.elements.argument_of_perigee;
+        let mut atime: f64 = 0.0;
+        let mut xni: f64 = no;
+        let mut xli: f64 = self.xlamo;
+        let (xndt, xldot, xnddt, ft): (f64, f64, f64, f64) = loop {
+            let (xndt, xnddt): (f64, f64) = if self.irez != 2 {
+                (
+                    self.del1 * (xli - FASX2).sin() + self.del2 * (2.0 * (xli - FASX4)).sin()
+                        + self.del3 * (3.0 * (xli - FASX6)).sin(),
+                    self.del1 * (xli - FASX2).cos() + 2.0 * self.del2 * (2.0 * (xli - FASX4)).cos()
+                        + 3.0 * self.del3 * (3.0 * (xli - FASX6)).cos()
+                )
+            } else {
+                let xomi: f64 = argpo + propagator.argpdot * atime;
+                let x2omi: f64 = xomi + xomi;
+                let x2li: f64 = xli + xli;
+                (
+                    self.d2201 * (x2omi + xli - G22).sin() + self.d2211 * (xli - G22).sin()
+                        + self.d3210 * (xomi + xli - G32).sin() + self.d3222 * (-xomi + xli - G32).sin()
+                        + self.d4410 * (x2omi + x2li - G44).sin() + self.d4422 * (x2li - G44).sin()
+                        + self.d5220 * (xomi + xli - G52).sin() + self.d5232 * (-xomi + xli - G52).sin()
+                        + self.d5421 * (xomi + x2li - G54).sin() + self.d5433 * (-xomi + x2li - G54).sin(),
+                    self.d2201 * (x2omi + xli - G22).cos() + self.d2211 * (xli - G22).cos()
+                        + self.d3210 * (xomi + xli - G32).cos() + self.d3222 * (-xomi + xli - G32).cos()
+                        + self.d5220 * (xomi + xli - G52).cos() + self.d5232 * (-xomi + xli - G52).cos()
+                        + 2.0 * (self.d4410 * (x2omi + x2li - G44).cos() + self.d4422 * (x2li - G44).cos()
+                        + self.d5421 * (xomi + x2li - G54).cos() + self.d5433 * (-xomi + x2li - G54).cos())
+                )
+            };
+            let xldot: f64 = xni + self.xfact;
+            let xnddt: f64 = xnddt * xldot;
+
+            if (t - atime).abs() < STEPP {
+                break (xndt, xldot, xnddt, t - atime);
+            }
+            xli += xldot * delt + xndt * STEP2;
+            xni += xndt * delt + xnddt * STEP2;
+            atime += delt;
+        };
+
+        let nm: f64 = xni + xndt * ft + xnddt * ft * ft * 0.5;
+        let xl: f64 = xli + xldot * ft + xndt * ft * ft * 0.5;
+        secular.mm = if self.irez != 1 {
+            xl - 2.0 * secular.nodem + 2.0 * theta
+        } else {
+            xl - secular.nodem - secular.argpm + theta
+        };
+        secular.nm = no + (nm - no);
+        secular
+    }
+
+    /// Apply the lunar-solar periodics (`dpper` of the reference implementation)
+    fn periodics(&self, t: f64, ep: &mut f64, inclp: &mut f64, nodep: &mut f64, argpp: &mut f64, mp: &mut f64) {
+        const ZNS: f64 = 1.19459e-5;
+        const ZES: f64 = 0.01675;
+        const ZNL: f64 = 1.5835218e-4;
+        const ZEL: f64 = 0.05490;
+
+        // Solar terms
+        let zm: f64 = self.zmos + ZNS * t;
+        let zf: f64 = zm + 2.0 * ZES * zm.sin();
+        let sinzf: f64 = zf.sin();
+        let f2: f64 = 0.5 * sinzf * sinzf - 0.25;
+        let f3: f64 = -0.5 * sinzf * zf.cos();
+        let ses: f64 = self.se2 * f2 + self.se3 * f3;
+        let sis: f64 = self.si2 * f2 + self.si3 * f3;
+        let sls: f64 = self.sl2 * f2 + self.sl3 * f3 + self.sl4 * sinzf;
+        let sghs: f64 = self.sgh2 * f2 + self.sgh3 * f3 + self.sgh4 * sinzf;
+        let shs: f64 = self.sh2 * f2 + self.sh3 * f3;
+
+        // Lunar terms
+        let zm: f64 = self.zmol + ZNL * t;
+        let zf: f64 = zm + 2.0 * ZEL * zm.sin();
+        let sinzf: f64 = zf.sin();
+        let f2: f64 = 0.5 * sinzf * sinzf - 0.25;
+        let f3: f64 = -0.5 * sinzf * zf.cos();
+        let sel: f64 = self.ee2 * f2 + self.e3 * f3;
+        let sil: f64 = self.xi2 * f2 + self.xi3 * f3;
+        let sll: f64 = self.xl2 * f2 + self.xl3 * f3 + self.xl4 * sinzf;
+        let sghl: f64 = self.xgh2 * f2 + self.xgh3 * f3 + self.xgh4 * sinzf;
+        let shll: f64 = self.xh2 * f2 + self.xh3 * f3;
+
+        let pe: f64 = ses + sel;
+        let pinc: f64 = sis + sil;
+        let pl: f64 = sls + sll;
+        let mut pgh: f64 = sghs + sghl;
+        let mut ph: f64 = shs + shll;
+
+        *inclp += pinc;
+        *ep += pe;
+        let (sinip, cosip): (f64, f64) = inclp.sin_cos();
+
+        if *inclp >= 0.2 {
+            ph /= sinip;
+            pgh -= cosip * ph;
+            *argpp += pgh;
+            *nodep += ph;
+            *mp += pl;
+        } else {
+            // Apply the periodics with the Lyddane modification
+            let (sinop, cosop): (f64, f64) = nodep.sin_cos();
+            let mut alfdp: f64 = sinip * sinop;
+            let mut betdp: f64 = sinip * cosop;
+            let dalf: f64 = ph * cosop + pinc * cosip * sinop;
+            let dbet: f64 = -ph * sinop + pinc * cosip * cosop;
+            alfdp += dalf;
+            betdp += dbet;
+            *nodep %= TWO_PI;
+            let mut xls: f64 = *mp + *argpp + cosip * *nodep;
+            let dls: f64 = pl + pgh - pinc * *nodep * sinip;
+            xls += dls;
+            let xnoh: f64 = *nodep;
+            *nodep = alfdp.atan2(betdp);
+            if (xnoh - *nodep).abs() > PI {
+                if *nodep < xnoh { *nodep += TWO_PI; } else { *nodep -= TWO_PI; }
+            }
+            *mp += pl;
+            *argpp = xls - *mp - cosip * *nodep;
+        }
+    }
+}
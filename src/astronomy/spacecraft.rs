@@ -0,0 +1,328 @@
+use std::fmt::{Display, Formatter};
+use crate::*;
+
+
+/// # Burn Direction
+/// The direction of a burn in the local orbital frame of the spacecraft.
+/// The frame is built from the position and velocity relative to the reference body:
+/// - `Prograde` / `Retrograde` - along / against the velocity
+/// - `Normal` / `AntiNormal` - along / against the orbital angular momentum
+/// - `RadialOut` / `RadialIn` - perpendicular to both, pointing away from / towards the reference body
+/// - `Fixed(Vector)` - a fixed direction in the simulation frame
+#[derive(Debug, Copy, Clone)]
+pub enum BurnDirection {
+    Prograde,
+    Retrograde,
+    Normal,
+    AntiNormal,
+    RadialOut,
+    RadialIn,
+    Fixed(Vector)
+}
+
+
+impl BurnDirection {
+    /// Calculate the unit vector of the direction
+    /// ## Params
+    /// - `body: &PointBody` - The spacecraft
+    /// - `reference: Option<&PointBody>` - The body the orbit is measured around (the origin if `None`)
+    pub fn unit_vector(&self, body: &PointBody, reference: Option<&PointBody>) -> Vector {
+        let (position, velocity): (Vector, Vector) = match reference {
+            Some(reference) => (
+                Vector::from_point(body.coordinates - reference.coordinates),
+                body.velocity - reference.velocity
+            ),
+            None => (Vector::from_point(body.coordinates), body.velocity)
+        };
+
+        let prograde: Vector = velocity.unit();
+        let normal: Vector = (position * velocity).unit();
+        let radial: Vector = (prograde * normal).unit();
+
+        match *self {
+            Self::Prograde => prograde,
+            Self::Retrograde => -prograde,
+            Self::Normal => normal,
+            Self::AntiNormal => -normal,
+            Self::RadialOut => radial,
+            Self::RadialIn => -radial,
+            Self::Fixed(direction) => direction.unit()
+        }
+    }
+
+    /// Parse a direction of the local orbital frame, ignoring the case, the spaces and the hyphens
+    /// (e.g. `Prograde`, `anti-normal`, `radial out`)
+    pub fn parse(value: &str) -> Option<Self> {
+        let string: String = value.to_lowercase().replace([' ', '-'], "");
+        match string.as_str() {
+            "prograde" => Some(Self::Prograde),
+            "retrograde" => Some(Self::Retrograde),
+            "normal" => Some(Self::Normal),
+            "antinormal" => Some(Self::AntiNormal),
+            "radialout" => Some(Self::RadialOut),
+            "radialin" => Some(Self::RadialIn),
+            _ => None
+        }
+    }
+}
+
+
+/// The fixed directions are written as their vector
+impl Display for BurnDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Prograde => write!(f, "Prograde"),
+            Self::Retrograde => write!(f, "Retrograde"),
+            Self::Normal => write!(f, "Normal"),
+            Self::AntiNormal => write!(f, "Anti-normal"),
+            Self::RadialOut => write!(f, "Radial out"),
+            Self::RadialIn => write!(f, "Radial in"),
+            Self::Fixed(direction) => write!(f, "{direction}")
+        }
+    }
+}
+
+
+/// # Burn
+/// A finite engine burn at maximum thrust
+/// ## Attributes
+/// `start: Scalar` - The simulation time the burn starts at\
+/// `duration: Scalar` - The length of the burn\
+/// `direction: BurnDirection` - The direction of the thrust
+#[derive(Debug, Copy, Clone)]
+pub struct Burn {
+    pub start: Scalar,
+    pub duration: Scalar,
+    pub direction: BurnDirection
+}
+
+
+impl Burn {
+    pub const fn new(start: Scalar, duration: Scalar, direction: BurnDirection) -> Self {
+        Self { start, duration, direction }
+    }
+
+    /// Calculate how long the burn is active between `from` and `to`.
+    /// The result is negative if `to` is before `from`.
+    pub fn active_time(&self, from: Scalar, to: Scalar) -> Scalar {
+        let (lower, upper, sign): (Scalar, Scalar, Scalar) = if to >= from {
+            (from, to, scalar!(1))
+        } else {
+            (to, from, scalar!(-1))
+        };
+
+        let begin: Scalar = lower.max(self.start);
+        let end: Scalar = upper.min(self.start + self.duration);
+
+        if end > begin { (end - begin) * sign } else { ZERO }
+    }
+}
+
+
+/// # Spacecraft
+/// A body with an engine, which can change its velocity by burning propellant.
+/// ## Attributes
+/// `dry_mass: Mass` - The mass of the spacecraft without propellant\
+/// `specific_impulse: Time` - The specific impulse of the engine\
+/// `max_thrust: Force` - The thrust of the engine during a burn\
+/// `burns: Vec<Burn>` - The burn schedule\
+/// `reference: Option<String>` - The name of the body the burn directions are measured around
+#[derive(Clone, Debug)]
+pub struct Spacecraft {
+    pub name: String,
+    pub radius: Length,
+    pub dry_mass: Mass,
+    pub specific_impulse: Time,
+    pub max_thrust: Force,
+    pub burns: Vec<Burn>,
+    pub reference: Option<String>,
+    point_body: PointBody
+}
+
+
+impl Spacecraft {
+    pub fn new(name: String,
+               velocity: Vector,
+               coordinates: Point,
+               dry_mass: Mass,
+               propellant_mass: Mass,
+               specific_impulse: Time,
+               max_thrust: Force) -> Self {
+        let point_body: PointBody = PointBody::new(dry_mass + propellant_mass, velocity, coordinates, scalar!());
+        Self {
+            name,
+            radius: Length::from(10),
+            dry_mass,
+            specific_impulse,
+            max_thrust,
+            burns: Vec::new(),
+            reference: None,
+            point_body
+        }
+    }
+
+    /// Add a burn to the schedule
+    pub fn add_burn(&mut self, burn: Burn) { self.burns.push(burn); }
+
+    /// Set the body the burn directions are measured around
+    pub fn set_reference(&mut self, reference: impl ToString) { self.reference = Some(reference.to_string()); }
+
+    /* ----- POINT BODY FIELDS ----- */
+    pub fn mass(&self) -> Mass { self.point_body.mass }
+    pub fn velocity(&self) -> Vector { self.point_body.velocity }
+    pub fn coordinates(&self) -> Point { self.point_body.coordinates }
+
+    pub fn set_velocity(&mut self, velocity: Vector) { self.point_body.velocity = velocity; }
+    pub fn set_coordinates(&mut self, coordinates: Point) { self.point_body.coordinates = coordinates; }
+
+    /* ----- SPACECRAFT METHODS ----- */
+    /// The mass of the remaining propellant
+    pub fn propellant_mass(&self) -> Mass { self.mass() - self.dry_mass }
+
+    /// The effective exhaust velocity of the engine: `v_e = I_sp * g_0`
+    pub fn exhaust_velocity(&self) -> Velocity { STANDARD_GRAVITY * self.specific_impulse }
+
+    /// The propellant burnt per second at maximum thrust (in kg/s)
+    pub fn mass_flow_rate(&self) -> Scalar { self.max_thrust.value / self.exhaust_velocity().value }
+
+    /// The total change in velocity the remaining propellant allows for (Tsiolkovsky rocket equation)
+    pub fn delta_v(&self) -> Velocity { self.exhaust_velocity() * (self.mass() / self.dry_mass).ln() }
+
+    /// Check whether any burn is active at the given time
+    pub fn is_burning(&self, time: Scalar) -> bool {
+        self.propellant_mass() > Mass::new(ZERO) && self.burns.iter().any(
+            |burn| time >= burn.start && time < burn.start + burn.duration
+        )
+    }
+}
+
+
+impl Celestial for Spacecraft {
+    #[inline]
+    fn point_body(&self) -> &PointBody { &self.point_body }
+    #[inline]
+    fn point_body_mut(&mut self) -> &mut PointBody { &mut self.point_body }
+    #[inline]
+    fn get_radius(&self) -> Length { self.radius }
+    #[inline]
+    fn get_name(&self) -> String { self.name.clone() }
+    #[inline]
+    fn is_star(&self) -> bool { false }
+    #[inline]
+    fn is_planet(&self) -> bool { false }
+    #[inline]
+    fn planet_type(&self) -> Option<PlanetType> { None }
+    #[inline]
+    fn concrete(&self) -> Option<ConcreteCelestial<'_>> { Some(ConcreteCelestial::Spacecraft(self)) }
+    #[inline]
+    fn get_color(&self) -> [f64; 4] { [0.8, 0.8, 0.8, 1.0] }
+    #[inline]
+    fn get_reference(&self) -> Option<String> { self.reference.clone() }
+
+    /// Burn the propellant of the active burns.
+    /// The mass change and the change in velocity follow the Tsiolkovsky rocket equation:
+    /// $$\Delta v = v_e \ln\frac{m_0}{m_1}$$
+    fn update(&mut self, time: Scalar, dt: Scalar, reference: Option<&PointBody>) {
+        for i in 0..self.burns.len() {
+            let burn: Burn = self.burns[i];
+            let active: Scalar = burn.active_time(time, time + dt);
+            if active == ZERO { continue; }
+
+            // Calculate the burnt propellant (negative when running backwards in time)
+            let burnt: Mass = Mass::new(self.mass_flow_rate() * active).min(self.propellant_mass());
+            if burnt.value == ZERO { continue; }
+
+            let initial_mass: Mass = self.mass();
+            let final_mass: Mass = initial_mass - burnt;
+            let delta_v: Velocity = self.exhaust_velocity() * (initial_mass / final_mass).ln();
+
+            let direction: Vector = burn.direction.unit_vector(&self.point_body, reference);
+            self.point_body.velocity += direction * delta_v.value;
+            self.point_body.mass = final_mass;
+        }
+    }
+}
+
+
+#[macro_export]
+macro_rules! spacecraft {
+    (
+        $name: expr,
+        $velocity: tt,
+        $coordinates: tt,
+        $dry_mass: expr,
+        $propellant_mass: expr,
+        $specific_impulse: expr,
+        $max_thrust: expr
+    ) => {
+        $crate::astronomy::Spacecraft::new(
+            String::from($name),
+            $crate::vector!($velocity),
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($dry_mass),
+            $crate::general::Mass::from($propellant_mass),
+            $crate::general::Time::from($specific_impulse),
+            $crate::general::Force::from($max_thrust)
+        )
+    };
+    (
+        $name: expr,
+        $velocity: expr,
+        $coordinates: expr,
+        $dry_mass: expr,
+        $propellant_mass: expr,
+        $specific_impulse: expr,
+        $max_thrust: expr
+    ) => {
+        $crate::astronomy::Spacecraft::new(
+            String::from($name),
+            $velocity,
+            $coordinates,
+            $crate::general::Mass::from($dry_mass),
+            $crate::general::Mass::from($propellant_mass),
+            $crate::general::Time::from($specific_impulse),
+            $crate::general::Force::from($max_thrust)
+        )
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn burns_follow_the_rocket_equation() {
+        let earth: PointBody = PointBody::new(Mass::from(5.972e24), NULL_VECTOR, ORIGO, scalar!());
+        // At (r, 0, 0) moving along +y the local frame is prograde +y, normal +z and radial out +x
+        for (direction, axis) in [
+            (BurnDirection::Prograde, vector!(0, 1, 0)),
+            (BurnDirection::Normal, vector!(0, 0, 1)),
+            (BurnDirection::RadialOut, vector!(1, 0, 0))
+        ] {
+            // The first burn leaves some propellant, the second one runs out of it
+            for duration in [100.0, 1000.0] {
+                let mut probe: Spacecraft = spacecraft!("Probe", (0, 7_500, 0), (7e6, 0, 0), 800, 200, 300, 1000);
+                probe.add_burn(Burn::new(scalar!(10), Scalar::from(duration), direction));
+                let initial_velocity: Vector = probe.velocity();
+                let initial_mass: f64 = probe.mass().to_f64();
+                let available: f64 = probe.delta_v().to_f64();
+                probe.update(ZERO, scalar!(2000), Some(&earth));
+
+                let delta_v: Vector = probe.velocity() - initial_velocity;
+                let speed: f64 = delta_v.magnitude().to_f64();
+                let expected: f64 = initial_mass * (-speed / probe.exhaust_velocity().to_f64()).exp();
+                assert!((probe.mass().to_f64() / expected - 1.0).abs() < 1e-12, "{direction} {duration}");
+                assert!((delta_v.unit() - axis).magnitude() < scalar!(1e-12), "{direction} {duration}");
+
+                let burnt: f64 = initial_mass - probe.mass().to_f64();
+                if duration < 500.0 {
+                    assert!((burnt / (probe.mass_flow_rate().to_f64() * duration) - 1.0).abs() < 1e-9, "{direction}");
+                } else {
+                    assert_eq!(probe.propellant_mass(), Mass::new(ZERO), "{direction}");
+                    assert!((speed / available - 1.0).abs() < 1e-12, "{direction}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,561 @@
+use crate::*;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+
+/// The length of a DAF record (in bytes)
+const RECORD: usize = 1024;
+
+/// The Julian day of J2000 (2000-01-01 12:00:00 TDB)
+pub const J2000_JULIAN_DAY: f64 = 2_451_545.0;
+
+/// The NAIF ID of the Solar System barycenter
+pub const SOLAR_SYSTEM_BARYCENTER: i32 = 0;
+
+/// The NAIF codes of the supported reference frames
+const FRAME_J2000: i32 = 1;
+const FRAME_ECLIPJ2000: i32 = 17;
+
+
+/// Convert a Julian day (TDB) to ephemeris time (seconds past J2000 TDB)
+pub fn julian_day_to_et(julian_day: f64) -> f64 {
+    (julian_day - J2000_JULIAN_DAY) * DAY.to_f64()
+}
+
+
+/// # SPK Error
+/// An error while reading an SPK kernel
+/// - `Io` - The file could not be read
+/// - `Format` - The file is not a valid DAF/SPK file
+/// - `Unsupported` - The file uses a segment type or frame, which can not be read
+/// - `NoData` - The kernel does not cover the body at the given ephemeris time
+#[derive(Debug)]
+pub enum SpkError {
+    Io(std::io::Error),
+    Format(String),
+    Unsupported(String),
+    NoData { target: i32, center: i32, et: f64 }
+}
+
+
+impl Display for SpkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Format(message) => write!(f, "invalid SPK file: {message}"),
+            Self::Unsupported(message) => write!(f, "unsupported SPK data: {message}"),
+            Self::NoData { target, center, et } => write!(
+                f, "no data for body {target} relative to {center} at {et} s past J2000"
+            )
+        }
+    }
+}
+
+
+impl std::error::Error for SpkError {}
+
+
+impl From<std::io::Error> for SpkError {
+    fn from(error: std::io::Error) -> Self { Self::Io(error) }
+}
+
+
+/// # SPK Segment
+/// The summary of a segment of an SPK kernel
+/// ## Attributes
+/// `start: f64`, `end: f64` - The covered interval (in seconds past J2000 TDB)\
+/// `target: i32`, `center: i32` - The NAIF IDs of the body and of the body its states are relative to\
+/// `frame: i32` - The NAIF code of the reference frame\
+/// `data_type: i32` - The type of the segment\
+/// `begin: usize`, `finish: usize` - The first and the last address of the data (in double words, from 1)
+#[derive(Debug, Clone, Copy)]
+pub struct SpkSegment {
+    pub start: f64,
+    pub end: f64,
+    pub target: i32,
+    pub center: i32,
+    pub frame: i32,
+    pub data_type: i32,
+    pub begin: usize,
+    pub finish: usize
+}
+
+
+/// # SPK Kernel
+/// A JPL/NAIF SPK ephemeris kernel (e.g. `de440s.bsp`) in the DAF format.
+/// Segments of type 2 (Chebyshev position) and 3 (Chebyshev position and velocity)
+/// in the `J2000` or `ECLIPJ2000` frame are supported, in both byte orders.
+/// The states are returned in SI units, in the frame of the simulation.
+pub struct SpkKernel {
+    pub path: PathBuf,
+    pub segments: Vec<SpkSegment>,
+    bytes: Vec<u8>,
+    little_endian: bool
+}
+
+
+impl SpkKernel {
+    /// Read a kernel file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SpkError> {
+        let path: &Path = path.as_ref();
+        let mut kernel: Self = Self::from_bytes(std::fs::read(path)?)?;
+        kernel.path = path.to_path_buf();
+        Ok(kernel)
+    }
+
+    /// Read a kernel from the contents of a file
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, SpkError> {
+        if bytes.len() < RECORD || &bytes[..7] != b"DAF/SPK" {
+            return Err(SpkError::Format(String::from("the file does not start with 'DAF/SPK'")));
+        }
+
+        let little_endian: bool = match &bytes[88..96] {
+            b"LTL-IEEE" => true,
+            b"BIG-IEEE" => false,
+            // Old files have no format string, so the byte order is guessed from the number of doubles
+            _ => i32::from_le_bytes(bytes[8..12].try_into().unwrap()) == 2
+        };
+        let mut kernel: Self = Self { path: PathBuf::new(), segments: Vec::new(), bytes, little_endian };
+
+        let doubles: usize = kernel.int(8) as usize;
+        let integers: usize = kernel.int(12) as usize;
+        if doubles != 2 || integers != 6 {
+            return Err(SpkError::Format(format!("expected ND = 2 and NI = 6, found {doubles} and {integers}")));
+        }
+        let summary_size: usize = doubles + integers.div_ceil(2);
+        let broken_chain = || SpkError::Format(String::from("broken summary record chain"));
+
+        // Walk the linked list of summary records
+        let mut record: usize = usize::try_from(kernel.int(76)).map_err(|_| broken_chain())?;
+        let mut visited: usize = 0;
+        while record != 0 {
+            let base: usize = (record - 1).checked_mul(RECORD).ok_or_else(broken_chain)?;
+            if base + RECORD > kernel.bytes.len() || visited > kernel.bytes.len() / RECORD {
+                return Err(broken_chain());
+            }
+            let next: f64 = kernel.double(base);
+            let count: f64 = kernel.double(base + 16);
+            if next.fract() != 0.0 || !(0.0..=(kernel.bytes.len() / RECORD) as f64).contains(&next) {
+                return Err(broken_chain());
+            }
+            // The summaries must fit into their record
+            if count.fract() != 0.0 || !(0.0..=((RECORD - 24) / (summary_size * 8)) as f64).contains(&count) {
+                return Err(SpkError::Format(format!("invalid number of summaries {count} in record {record}")));
+            }
+            let (next, count): (usize, usize) = (next as usize, count as usize);
+
+            for i in 0..count {
+                let summary: usize = base + 24 + i * summary_size * 8;
+                let integer = |n: usize| kernel.int(summary + 16 + n * 4);
+                kernel.segments.push(SpkSegment {
+                    start: kernel.double(summary),
+                    end: kernel.double(summary + 8),
+                    target: integer(0),
+                    center: integer(1),
+                    frame: integer(2),
+                    data_type: integer(3),
+                    begin: usize::try_from(integer(4)).unwrap_or(0),
+                    finish: usize::try_from(integer(5)).unwrap_or(0)
+                });
+            }
+            record = next;
+            visited += 1;
+        }
+
+        // Every segment has room for at least its directory (the last 4 words)
+        for segment in kernel.segments.iter() {
+            if segment.begin == 0 || segment.finish < segment.begin + 3 || segment.finish > kernel.bytes.len() / 8 {
+                return Err(SpkError::Format(format!("the segment of body {} is outside of the file", segment.target)));
+            }
+        }
+        Ok(kernel)
+    }
+
+    fn int(&self, offset: usize) -> i32 {
+        let bytes: [u8; 4] = self.bytes[offset..offset + 4].try_into().unwrap();
+        if self.little_endian { i32::from_le_bytes(bytes) } else { i32::from_be_bytes(bytes) }
+    }
+
+    fn double(&self, offset: usize) -> f64 {
+        let bytes: [u8; 8] = self.bytes[offset..offset + 8].try_into().unwrap();
+        if self.little_endian { f64::from_le_bytes(bytes) } else { f64::from_be_bytes(bytes) }
+    }
+
+    /// The double at the given address (in double words, from 1)
+    fn word(&self, address: usize) -> f64 { self.double((address - 1) * 8) }
+
+    /// The NAIF IDs of the bodies in the kernel
+    pub fn bodies(&self) -> Vec<i32> {
+        let mut bodies: Vec<i32> = self.segments.iter().map(|segment| segment.target).collect();
+        bodies.sort();
+        bodies.dedup();
+        bodies
+    }
+
+    /// Evaluate a segment at the given time.
+    /// The result is the position (km) and the velocity (km/s) in the frame of the segment.
+    fn evaluate(&self, segment: &SpkSegment, et: f64) -> Result<([f64; 3], [f64; 3]), SpkError> {
+        let components: usize = match segment.data_type {
+            2 => 3,
+            3 => 6,
+            data_type => return Err(SpkError::Unsupported(format!("segment type {data_type}")))
+        };
+
+        let init: f64 = self.word(segment.finish - 3);
+        let interval: f64 = self.word(segment.finish - 2);
+        let record_size: f64 = self.word(segment.finish - 1);
+        let records: f64 = self.word(segment.finish);
+        let invalid = || SpkError::Format(format!("invalid directory in the segment of body {}", segment.target));
+        if record_size.fract() != 0.0 || records.fract() != 0.0 || records < 1.0
+            || !interval.is_finite() || interval <= 0.0
+            || record_size < (2 + components) as f64 || records * record_size > segment.finish as f64 {
+            return Err(invalid());
+        }
+        let (record_size, records): (usize, usize) = (record_size as usize, records as usize);
+        // The records are followed by the directory
+        if segment.begin + records * record_size + 3 > segment.finish {
+            return Err(invalid());
+        }
+        let coefficients: usize = (record_size - 2) / components;
+
+        let index: usize = (((et - init) / interval).floor().max(0.0) as usize).min(records - 1);
+        let record: usize = segment.begin + index * record_size;
+        let middle: f64 = self.word(record);
+        let radius: f64 = self.word(record + 1);
+        let s: f64 = (et - middle) / radius;
+
+        // The Chebyshev polynomials and their derivatives at `s`
+        let mut polynomials: Vec<f64> = vec![0.0; coefficients];
+        let mut derivatives: Vec<f64> = vec![0.0; coefficients];
+        polynomials[0] = 1.0;
+        if coefficients > 1 {
+            polynomials[1] = s;
+            derivatives[1] = 1.0;
+        }
+        for k in 2..coefficients {
+            polynomials[k] = 2.0 * s * polynomials[k - 1] - polynomials[k - 2];
+            derivatives[k] = 2.0 * polynomials[k - 1] + 2.0 * s * derivatives[k - 1] - derivatives[k - 2];
+        }
+
+        let series = |component: usize, values: &[f64]| -> f64 {
+            let first: usize = record + 2 + component * coefficients;
+            (0..coefficients).map(|k| self.word(first + k) * values[k]).sum()
+        };
+
+        let mut position: [f64; 3] = [0.0; 3];
+        let mut velocity: [f64; 3] = [0.0; 3];
+        for axis in 0..3 {
+            position[axis] = series(axis, &polynomials);
+            velocity[axis] = if components == 6 {
+                series(axis + 3, &polynomials)
+            } else {
+                series(axis, &derivatives) / radius
+            };
+        }
+        Ok((position, velocity))
+    }
+
+    /// The state of a body relative to the center of its segment covering the given time,
+    /// in the ecliptic frame (km and km/s)
+    fn segment_state(&self, target: i32, et: f64) -> Result<(i32, [f64; 3], [f64; 3]), SpkError> {
+        // The later segments take precedence
+        let segment: &SpkSegment = self.segments.iter().rev()
+            .find(|segment| segment.target == target && segment.start <= et && et <= segment.end)
+            .ok_or(SpkError::NoData { target, center: SOLAR_SYSTEM_BARYCENTER, et })?;
+
+        let (position, velocity) = self.evaluate(segment, et)?;
+        let (position, velocity) = match segment.frame {
+            FRAME_J2000 => (equatorial_to_ecliptic(position), equatorial_to_ecliptic(velocity)),
+            FRAME_ECLIPJ2000 => (position, velocity),
+            frame => return Err(SpkError::Unsupported(format!("reference frame {frame}")))
+        };
+        Ok((segment.center, position, velocity))
+    }
+
+    /// The state of a body relative to the Solar System barycenter (km and km/s, ecliptic frame)
+    fn barycentric_state(&self, target: i32, et: f64) -> Result<([f64; 3], [f64; 3]), SpkError> {
+        let mut position: [f64; 3] = [0.0; 3];
+        let mut velocity: [f64; 3] = [0.0; 3];
+        let mut body: i32 = target;
+
+        // Follow the chain of centres (e.g. Moon -> Earth-Moon barycenter -> Solar System barycenter)
+        for _ in 0..self.segments.len() {
+            if body == SOLAR_SYSTEM_BARYCENTER { return Ok((position, velocity)); }
+            let (center, relative_position, relative_velocity) = self.segment_state(body, et)
+                .map_err(|error| match error {
+                    SpkError::NoData { .. } => SpkError::NoData { target, center: SOLAR_SYSTEM_BARYCENTER, et },
+                    error => error
+                })?;
+            for axis in 0..3 {
+                position[axis] += relative_position[axis];
+                velocity[axis] += relative_velocity[axis];
+            }
+            body = center;
+        }
+        if body == SOLAR_SYSTEM_BARYCENTER { Ok((position, velocity)) }
+        else { Err(SpkError::Format(format!("the centres of body {target} form a loop"))) }
+    }
+
+    /// Calculate the state of a body relative to another one at the given ephemeris time.
+    /// The result is in SI units, in the frame of the simulation.
+    pub fn state(&self, target: i32, center: i32, et: f64) -> Result<(Point, Vector), SpkError> {
+        let no_data = |error: SpkError| match error {
+            SpkError::NoData { .. } => SpkError::NoData { target, center, et },
+            error => error
+        };
+        let (target_position, target_velocity) = self.barycentric_state(target, et).map_err(no_data)?;
+        let (center_position, center_velocity) = self.barycentric_state(center, et).map_err(no_data)?;
+
+        let kilometre: Scalar = scalar!(1000);
+        let position: Vector = ecliptic_to_simulation([
+            target_position[0] - center_position[0],
+            target_position[1] - center_position[1],
+            target_position[2] - center_position[2]
+        ]) * kilometre;
+        let velocity: Vector = ecliptic_to_simulation([
+            target_velocity[0] - center_velocity[0],
+            target_velocity[1] - center_velocity[1],
+            target_velocity[2] - center_velocity[2]
+        ]) * kilometre;
+        Ok((position.to_point(), velocity))
+    }
+}
+
+
+/// # Ephemeris
+/// An SPK kernel, tied to the clock of a simulation.
+/// ## Attributes
+/// `kernel: Arc<SpkKernel>` - The kernel\
+/// `epoch: f64` - The ephemeris time (seconds past J2000 TDB) at the simulation time zero\
+/// `center: i32` - The NAIF ID of the body at the origin of the simulation (usually the Solar System barycenter)
+#[derive(Clone)]
+pub struct Ephemeris {
+    pub kernel: Arc<SpkKernel>,
+    pub epoch: f64,
+    pub center: i32
+}
+
+
+impl Ephemeris {
+    pub fn new(kernel: Arc<SpkKernel>, epoch: f64, center: i32) -> Self {
+        Self { kernel, epoch, center }
+    }
+
+    /// Calculate the state of a body at the given simulation time
+    pub fn state(&self, target: i32, time: Scalar) -> Result<(Point, Vector), SpkError> {
+        self.kernel.state(target, self.center, self.epoch + time.to_f64())
+    }
+
+    /// Create a star with the state of the body at the given simulation time
+    pub fn star(&self, name: String, target: i32, time: Scalar, mass: Mass, radius: Length) -> Result<Star, SpkError> {
+        let (coordinates, velocity) = self.state(target, time)?;
+        Ok(Star::new(name, velocity, coordinates, mass, radius))
+    }
+
+    /// Create a planet with the state of the body at the given simulation time
+    pub fn planet(&self,
+                  name: String,
+                  target: i32,
+                  time: Scalar,
+                  mass: Mass,
+                  radius: Length,
+                  planet_type: PlanetType) -> Result<Planet, SpkError> {
+        let (coordinates, velocity) = self.state(target, time)?;
+        Ok(Planet::new(name, velocity, coordinates, mass, radius, planet_type))
+    }
+
+    /// Compare the integrated states of the celestials with the ephemeris at the current time of the simulation
+    /// ## Params
+    /// - `simulation: &AstronomicalSimulation` - The simulation
+    /// - `bodies: &[(String, i32)]` - The names of the celestials and their NAIF IDs
+    pub fn accuracy(&self, simulation: &AstronomicalSimulation, bodies: &[(String, i32)]) -> Result<AccuracyReport, SpkError> {
+        let time: Scalar = simulation.get_time();
+        let mut report: AccuracyReport = AccuracyReport { time, entries: Vec::new() };
+
+        for (name, target) in bodies.iter() {
+            let celestial: &(dyn Celestial + Sync) = match simulation.get_celestial_from_name(name) {
+                Some(celestial) => celestial,
+                None => continue
+            };
+            let (coordinates, velocity) = self.state(*target, time)?;
+            let body: &PointBody = celestial.point_body();
+            report.entries.push(AccuracyEntry {
+                name: name.clone(),
+                position_error: body.distance(&coordinates),
+                velocity_error: Velocity::new((body.velocity - velocity).magnitude())
+            });
+        }
+        Ok(report)
+    }
+}
+
+
+/// # Rails
+/// Bodies, which are moved along the ephemeris instead of being integrated.
+/// They still attract the other bodies.
+/// ## Attributes
+/// `ephemeris: Ephemeris` - The ephemeris the bodies follow\
+/// `bodies: Vec<(String, i32)>` - The names of the celestials and their NAIF IDs
+#[derive(Clone)]
+pub struct Rails {
+    pub ephemeris: Ephemeris,
+    pub bodies: Vec<(String, i32)>
+}
+
+
+impl Rails {
+    pub fn new(ephemeris: Ephemeris, bodies: Vec<(String, i32)>) -> Self {
+        Self { ephemeris, bodies }
+    }
+
+    /// Move the bodies to their places at the given simulation time
+    pub fn apply(&self, time: Scalar, celestials: &mut [Box<dyn Celestial + Sync>]) -> Result<(), SpkError> {
+        for (name, target) in self.bodies.iter() {
+            let celestial: &mut Box<dyn Celestial + Sync> = match celestials.iter_mut().find(|celestial| celestial.get_name() == *name) {
+                Some(celestial) => celestial,
+                None => continue
+            };
+            let (coordinates, velocity) = self.ephemeris.state(*target, time)?;
+            let body: &mut PointBody = celestial.point_body_mut();
+            body.coordinates = coordinates;
+            body.velocity = velocity;
+            body.position_compensation = NULL_VECTOR;
+            body.velocity_compensation = NULL_VECTOR;
+        }
+        Ok(())
+    }
+}
+
+
+/// # Accuracy Entry
+/// The difference between the integrated and the ephemeris state of a body
+#[derive(Debug, Clone)]
+pub struct AccuracyEntry {
+    pub name: String,
+    pub position_error: Length,
+    pub velocity_error: Velocity
+}
+
+
+/// # Accuracy Report
+/// The differences between the integrated states and the ephemeris at one time
+#[derive(Debug, Clone)]
+pub struct AccuracyReport {
+    pub time: Scalar,
+    pub entries: Vec<AccuracyEntry>
+}
+
+
+impl AccuracyReport {
+    /// The entry with the largest position error
+    pub fn worst(&self) -> Option<&AccuracyEntry> {
+        self.entries.iter().max_by(|a, b| {
+            a.position_error.partial_cmp(&b.position_error).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+}
+
+
+impl Display for AccuracyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Accuracy at t = {} s:", self.time)?;
+        for entry in self.entries.iter() {
+            writeln!(f, "    {}: position error {}, velocity error {}", entry.name, entry.position_error, entry.velocity_error)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::*;
+    use super::RECORD;
+
+    /// Build a little-endian kernel with one type 2 segment (`ECLIPJ2000`) per body.
+    /// Each segment has a single record centred on J2000 with a radius of `10^9 s`,
+    /// and three Chebyshev coefficients (in km) per axis.
+    pub(crate) fn type_2_kernel(segments: &[(i32, i32, [[f64; 3]; 3])]) -> Vec<u8> {
+        let mut bytes: Vec<u8> = vec![0; 2 * RECORD];
+        bytes[..8].copy_from_slice(b"DAF/SPK ");
+        bytes[8..12].copy_from_slice(&2i32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&6i32.to_le_bytes());
+        bytes[76..80].copy_from_slice(&2i32.to_le_bytes());
+        bytes[80..84].copy_from_slice(&2i32.to_le_bytes());
+        bytes[88..96].copy_from_slice(b"LTL-IEEE");
+
+        // The summary record, followed by the data
+        let summaries: usize = RECORD;
+        bytes[summaries + 16..summaries + 24].copy_from_slice(&(segments.len() as f64).to_le_bytes());
+        for (i, (target, center, coefficients)) in segments.iter().enumerate() {
+            let begin: usize = bytes.len() / 8 + 1;
+            let mut words: Vec<f64> = vec![0.0, 1e9];
+            for axis in coefficients.iter() {
+                words.extend_from_slice(axis);
+            }
+            words.extend_from_slice(&[-1e9, 2e9, 11.0, 1.0]);
+            for word in words.iter() {
+                bytes.extend_from_slice(&word.to_le_bytes());
+            }
+
+            let summary: usize = summaries + 24 + i * 40;
+            bytes[summary..summary + 8].copy_from_slice(&(-1e9f64).to_le_bytes());
+            bytes[summary + 8..summary + 16].copy_from_slice(&1e9f64.to_le_bytes());
+            let integers: [i32; 6] = [*target, *center, 17, 2, begin as i32, (begin + words.len() - 1) as i32];
+            for (n, integer) in integers.iter().enumerate() {
+                bytes[summary + 16 + n * 4..summary + 20 + n * 4].copy_from_slice(&integer.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn type_2_segments_are_evaluated() {
+        let kernel: SpkKernel = SpkKernel::from_bytes(type_2_kernel(&[
+            (10, 0, [[1000.0, 1e6, 5.0], [-2000.0, 0.0, 0.0], [30.0, 0.0, 0.0]]),
+            (399, 10, [[1.5e8, 0.0, 0.0], [0.0, 3e10, 0.0], [0.0, 0.0, 0.0]])
+        ])).unwrap();
+        assert_eq!(kernel.bodies(), vec![10, 399]);
+
+        // At s = 0.5: T0 = 1, T1 = 0.5, T2 = -0.5, and the derivatives are 0, 1 and 2 (divided by the radius)
+        let (position, velocity) = kernel.state(399, SOLAR_SYSTEM_BARYCENTER, 5e8).unwrap();
+        let expected_position: Vector = ecliptic_to_simulation([1000.0 + 5e5 - 2.5 + 1.5e8, -2000.0 + 1.5e10, 30.0]) * scalar!(1000);
+        let expected_velocity: Vector = ecliptic_to_simulation([(1e6 + 10.0) / 1e9, 30.0, 0.0]) * scalar!(1000);
+        assert!((Vector::from_point(position) - expected_position).magnitude() < scalar!(1e-3), "{position:?}");
+        assert!((velocity - expected_velocity).magnitude() < scalar!(1e-9), "{velocity:?}");
+
+        assert!(matches!(kernel.state(499, SOLAR_SYSTEM_BARYCENTER, 0.0), Err(SpkError::NoData { target: 499, .. })));
+    }
+
+    #[test]
+    fn corrupt_kernels_are_errors() {
+        let valid: Vec<u8> = type_2_kernel(&[(399, 0, [[1.0, 0.0, 0.0]; 3])]);
+        let corrupt = |offset: usize, value: &[u8]| {
+            let mut bytes: Vec<u8> = valid.clone();
+            bytes[offset..offset + value.len()].copy_from_slice(value);
+            bytes
+        };
+        let summary: usize = RECORD + 24;
+
+        for bytes in [
+            valid[..RECORD + 100].to_vec(),
+            corrupt(76, &(-5i32).to_le_bytes()),
+            corrupt(76, &i32::MAX.to_le_bytes()),
+            corrupt(RECORD, &1e300f64.to_le_bytes()),
+            corrupt(RECORD + 16, &1000.0f64.to_le_bytes()),
+            corrupt(summary + 32, &(-1i32).to_le_bytes()),
+            corrupt(summary + 36, &2i32.to_le_bytes()),
+            corrupt(summary + 36, &i32::MAX.to_le_bytes())
+        ] {
+            assert!(matches!(SpkKernel::from_bytes(bytes), Err(SpkError::Format(_))));
+        }
+
+        // The directory promises more records than the segment holds
+        let records: usize = valid.len() - 8;
+        for word in [2.0, f64::NAN, 1e300] {
+            let kernel: SpkKernel = SpkKernel::from_bytes(corrupt(records, &word.to_le_bytes())).unwrap();
+            assert!(matches!(kernel.state(399, SOLAR_SYSTEM_BARYCENTER, 0.0), Err(SpkError::Format(_))));
+        }
+    }
+}
@@ -16,8 +16,9 @@ pub enum StarType {
 #[derive(Clone, Debug)]
 pub struct Star {
     pub name: String,
-    pub radius: Scalar,
+    pub radius: Length,
     pub radiation: Radiation,
+    pub tracer: bool,
     point_body: PointBody
 }
 
@@ -26,60 +27,79 @@ impl Star {
     pub fn new(name: String,
                velocity: Vector,
                coordinates: Point,
-               mass: Scalar,
-               radius: Scalar) -> Self {
+               mass: Mass,
+               radius: Length) -> Self {
         let point_body: PointBody = PointBody::new(mass, velocity, coordinates, scalar!());
         let mut star: Self = Self {
             name,
             radius,
             radiation: Radiation::new(scalar!(0)),
+            tracer: false,
             point_body
         };
 
-        let surface_temp: Scalar = star.surface_temperature();
-        star.radiation = Radiation::new(surface_temp);
+        let surface_temp: Temperature = star.surface_temperature();
+        star.radiation = Radiation::new(surface_temp.value);
 
         star
     }
 
+    /// Create a low-cost tracer star.
+    /// Tracers stand for a group of stars (e.g. in a galaxy), so their mass is not used for their looks:
+    /// they get the radius and the temperature of the Sun, and they are drawn as points without labels.
+    pub fn tracer(name: String,
+                  velocity: Vector,
+                  coordinates: Point,
+                  mass: Mass) -> Self {
+        Self {
+            name,
+            radius: SOLAR_RADIUS,
+            radiation: Radiation::new(SOLAR_TEMPERATURE.value),
+            tracer: true,
+            point_body: PointBody::new(mass, velocity, coordinates, scalar!())
+        }
+    }
+
     /* ----- POINT BODY FIELDS ----- */
-    pub fn mass(&self) -> Scalar { self.point_body.mass }
-    pub fn velocity(&self) -> Vector { self.point_body.velocity.clone() }
-    pub fn coordinates(&self) -> Point { self.point_body.coordinates.clone() }
+    pub fn mass(&self) -> Mass { self.point_body.get_mass() }
+    pub fn velocity(&self) -> Vector { self.point_body.velocity }
+    pub fn coordinates(&self) -> Point { self.point_body.coordinates }
 
-    pub fn set_mass(&mut self, mass: Scalar) { self.point_body.mass = mass; }
+    pub fn set_mass(&mut self, mass: Mass) { self.point_body.mass = mass; }
     pub fn set_velocity(&mut self, velocity: Vector) { self.point_body.velocity = velocity; }
     pub fn set_coordinates(&mut self, coordinates: Point) { self.point_body.coordinates = coordinates; }
 
     /* ----- POINT BODY METHODS ----- */
     pub fn momentum(&self) -> Vector { self.point_body.momentum() }
-    pub fn kinetic_energy(&self) -> Scalar { self.point_body.kinetic_energy() }
+    pub fn kinetic_energy(&self) -> Energy { self.point_body.kinetic_energy() }
     pub fn acceleration(&self, force: Vector) -> Vector { self.point_body.acceleration(force) }
     pub fn force(&self, acceleration: Vector) -> Vector { self.point_body.force(acceleration) }
-    pub fn distance(&self, other: &Point) -> Scalar { self.point_body.distance(other) }
+    pub fn acceleration_magnitude(&self, force: Force) -> Acceleration { self.point_body.acceleration_magnitude(force) }
+    pub fn force_magnitude(&self, acceleration: Acceleration) -> Force { self.point_body.force_magnitude(acceleration) }
+    pub fn distance(&self, other: &Point) -> Length { self.point_body.distance(other) }
     pub fn gravitational_force(&self, other: &PointBody) -> Vector { self.point_body.gravitational_force(other) }
-    pub fn advance(&mut self, dt: Scalar) { self.point_body.advance(dt) }
+    pub fn advance(&mut self, dt: Time) { self.point_body.advance(dt) }
 
     /* ----- STAR METHODS ----- */
     pub fn surface_acceleration(&self) -> Vector {
         Vector::new(
             ZERO,
-            -G * self.mass() / self.radius.powi(2),
+            -G * self.mass().value / self.radius.value.powi(2),
             ZERO
         )
     }
 
-    pub fn luminosity(&self) -> Scalar {
+    pub fn luminosity(&self) -> Power {
         SOLAR_LUMINOSITY * (self.mass() / SOLAR_MASS).pow(scalar!(3.5))
     }
 
-    pub fn surface_temperature(&self) -> Scalar {
+    pub fn surface_temperature(&self) -> Temperature {
         let constant: Scalar = scalar!(4) * PI * STEFAN_BOLTZMANN_CONSTANT;
-        (self.luminosity() / (constant * self.radius.powi(2))).pow(scalar!(0.25))
+        Temperature::new((self.luminosity().value / (constant * self.radius.value.powi(2))).pow(scalar!(0.25)))
     }
 
     pub fn color(&self) -> [f64; 4] {
-        match self.radiation.temperature.value {
+        match self.radiation.temperature.to_f64() {
             ..3500.0 => [0.471, 0.035, 0.02, 1.0],
             3500.0..6000.0 => [1.0, 0.804, 0.0, 1.0],
             6000.0..10000.0 => [1.0, 1.0, 1.0, 1.0],
@@ -90,7 +110,7 @@ impl Star {
     }
 
     pub fn star_type(&self) -> StarType {
-        match self.radiation.temperature.value {
+        match self.radiation.temperature.to_f64() {
             ..3700.0 => { StarType::M },
             3700.0..5200.0 => { StarType::K },
             5200.0..6000.0 => { StarType::G },
@@ -110,7 +130,7 @@ impl Celestial for Star {
     #[inline]
     fn point_body_mut(&mut self) -> &mut PointBody { &mut self.point_body }
     #[inline]
-    fn get_radius(&self) -> Scalar { self.radius }
+    fn get_radius(&self) -> Length { self.radius }
     #[inline]
     fn get_name(&self) -> String { self.name.clone() }
     #[inline]
@@ -120,7 +140,13 @@ impl Celestial for Star {
     #[inline]
     fn planet_type(&self) -> Option<PlanetType> { None }
     #[inline]
+    fn concrete(&self) -> Option<ConcreteCelestial<'_>> { Some(ConcreteCelestial::Star(self)) }
+    #[inline]
     fn get_color(&self) -> [f64; 4] { self.color() }
+    #[inline]
+    fn is_tracer(&self) -> bool { self.tracer }
+    #[inline]
+    fn get_luminosity(&self) -> Power { self.luminosity() }
 }
 
 
@@ -133,12 +159,12 @@ macro_rules! star {
         $mass: expr,
         $radius: expr
     ) => {
-        Star::new(
+        $crate::astronomy::Star::new(
             String::from($name),
-            vector!($velocity),
-            point!($coordinates),
-            scalar!($mass),
-            scalar!($radius)
+            $crate::vector!($velocity),
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius)
         )
     };
     (
@@ -148,12 +174,12 @@ macro_rules! star {
         $mass: expr,
         $radius: expr
     ) => {
-        Star::new(
+        $crate::astronomy::Star::new(
             String::from($name),
             $velocity,
-            point!($coordinates),
-            scalar!($mass),
-            scalar!($radius)
+            $crate::point!($coordinates),
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius)
         )
     };
     (
@@ -163,12 +189,12 @@ macro_rules! star {
         $mass: expr,
         $radius: expr
     ) => {
-        Star::new(
+        $crate::astronomy::Star::new(
             String::from($name),
-            vector!($velocity),
+            $crate::vector!($velocity),
             $coordinates,
-            scalar!($mass),
-            scalar!($radius)
+            $crate::general::Mass::from($mass),
+            $crate::general::Length::from($radius)
         )
     };
     (
@@ -178,6 +204,6 @@ macro_rules! star {
         $mass: expr,
         $radius: expr
     ) => {
-        Star::new(String::from($name), $velocity, $coordinates, scalar!($mass), scalar!($radius))
+        $crate::astronomy::Star::new(String::from($name), $velocity, $coordinates, $crate::general::Mass::from($mass), $crate::general::Length::from($radius))
     };
 }
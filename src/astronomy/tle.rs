@@ -0,0 +1,348 @@
+use crate::*;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+
+/// The mass given to the satellites, which are test particles (TLEs carry no mass)
+const SATELLITE_MASS: f64 = 1000.0;
+
+/// The radius given to the satellites (in metres)
+const SATELLITE_RADIUS: f64 = 10.0;
+
+
+/// # TLE Error
+/// An error while reading or propagating a two-line element set
+/// - `Io` - The file could not be read\
+/// - `Format` - The element set is invalid, at the given line (starting from 1) and column (starting from 1)\
+/// - `Checksum` - The checksum of the given line does not match\
+/// - `Propagation` - The propagator rejected the elements of the named satellite\
+/// - `MissingCenter` - The simulation has no body with the given name
+#[derive(Debug)]
+pub enum TleError {
+    Io(std::io::Error),
+    Format { line: usize, column: usize, message: String },
+    Checksum { line: usize },
+    Propagation { name: String, error: Sgp4Error },
+    MissingCenter(String)
+}
+
+
+impl TleError {
+    fn format(line: usize, column: usize, message: impl ToString) -> Self {
+        Self::Format { line, column, message: message.to_string() }
+    }
+}
+
+
+impl Display for TleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Format { line, column, message } => write!(f, "line {line}, column {column}: {message}"),
+            Self::Checksum { line } => write!(f, "line {line}: the checksum does not match"),
+            Self::Propagation { name, error } => write!(f, "{name}: {error}"),
+            Self::MissingCenter(name) => write!(f, "there is no body called {name}")
+        }
+    }
+}
+
+
+impl std::error::Error for TleError {}
+
+
+impl From<std::io::Error> for TleError {
+    fn from(error: std::io::Error) -> Self { Self::Io(error) }
+}
+
+
+/// # TLE
+/// A two-line element set of an Earth satellite, as published by NORAD / CelesTrak
+/// ## Attributes
+/// `name: String` - The name from the title line, or the catalog number if there is none\
+/// `catalog_number: String` - The satellite catalog number (also in the Alpha-5 form)\
+/// `classification: char` - `U`, `C` or `S`\
+/// `international_designator: String` - The launch year, number and piece (e.g. `58002B`)\
+/// `epoch: f64` - The Julian day (UTC) of the elements\
+/// `mean_motion_dot: f64` - The first derivative of the mean motion / 2 (revolutions / day²)\
+/// `mean_motion_ddot: f64` - The second derivative of the mean motion / 6 (revolutions / day³)\
+/// `bstar: f64` - The drag term (1 / Earth radii)\
+/// `element_set: u32` - The element set number\
+/// `inclination`, `raan`, `argument_of_perigee`, `mean_anomaly` - The angles (degrees)\
+/// `eccentricity: f64` - The eccentricity\
+/// `mean_motion: f64` - The mean motion (revolutions / day)\
+/// `revolution: u32` - The revolution number at the epoch
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tle {
+    pub name: String,
+    pub catalog_number: String,
+    pub classification: char,
+    pub international_designator: String,
+    pub epoch: f64,
+    pub mean_motion_dot: f64,
+    pub mean_motion_ddot: f64,
+    pub bstar: f64,
+    pub element_set: u32,
+    pub inclination: f64,
+    pub raan: f64,
+    pub eccentricity: f64,
+    pub argument_of_perigee: f64,
+    pub mean_anomaly: f64,
+    pub mean_motion: f64,
+    pub revolution: u32
+}
+
+
+impl Tle {
+    /// Read the element sets from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<Self>, TleError> {
+        Self::parse_all(&std::fs::read_to_string(path)?)
+    }
+
+    /// Read the element sets of a text, in the two-line or in the three-line (with a title line) form
+    pub fn parse_all(text: &str) -> Result<Vec<Self>, TleError> {
+        let lines: Vec<(usize, &str)> = text.lines()
+            .enumerate()
+            .map(|(n, line)| (n + 1, line.trim_end()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+
+        let mut tles: Vec<Self> = Vec::new();
+        let mut i: usize = 0;
+        while i < lines.len() {
+            let (n, line): (usize, &str) = lines[i];
+            let name: Option<&str> = if line.starts_with("1 ") { None } else {
+                i += 1;
+                Some(line.strip_prefix("0 ").unwrap_or(line))
+            };
+            let [first, second]: [(usize, &str); 2] = match lines.get(i..i + 2) {
+                Some(&[first, second]) => [first, second],
+                _ => return Err(TleError::format(n, 1, "incomplete element set"))
+            };
+            tles.push(Self::parse_lines(name, first, second)?);
+            i += 2;
+        }
+        Ok(tles)
+    }
+
+    /// Read an element set from its two lines
+    pub fn parse(first: &str, second: &str) -> Result<Self, TleError> {
+        Self::parse_lines(None, (1, first), (2, second))
+    }
+
+    fn parse_lines(name: Option<&str>, first: (usize, &str), second: (usize, &str)) -> Result<Self, TleError> {
+        let line1: Line = Line::new(first, '1')?;
+        let line2: Line = Line::new(second, '2')?;
+
+        let catalog_number: String = line1.text(3, 7).to_string();
+        if line2.text(3, 7) != catalog_number {
+            return Err(TleError::format(line2.number, 3, "the catalog numbers of the lines differ"));
+        }
+
+        // Two-digit years: 57-99 are 1957-1999, 00-56 are 2000-2056
+        let year: i32 = line1.number(19, 20)? as i32;
+        let year: i32 = if year < 57 { year + 2000 } else { year + 1900 };
+        let day: f64 = line1.number(21, 32)?;
+
+        Ok(Self {
+            name: name.map(|name| name.trim().to_string()).unwrap_or_else(|| catalog_number.clone()),
+            classification: line1.text(8, 8).chars().next().unwrap_or('U'),
+            international_designator: line1.text(10, 17).to_string(),
+            epoch: julian_day(year, 1, 0.0) + day,
+            mean_motion_dot: line1.number(34, 43)?,
+            mean_motion_ddot: line1.exponent(45, 52)?,
+            bstar: line1.exponent(54, 61)?,
+            element_set: line1.number(65, 68).unwrap_or(0.0) as u32,
+            inclination: line2.number(9, 16)?,
+            raan: line2.number(18, 25)?,
+            eccentricity: line2.decimal(27, 33)?,
+            argument_of_perigee: line2.number(35, 42)?,
+            mean_anomaly: line2.number(44, 51)?,
+            mean_motion: line2.number(53, 63)?,
+            revolution: line2.number(64, 68).unwrap_or(0.0) as u32,
+            catalog_number
+        })
+    }
+
+    /// The mean elements in the units of the propagator
+    pub fn elements(&self) -> MeanElements {
+        MeanElements {
+            epoch: self.epoch,
+            bstar: self.bstar,
+            inclination: self.inclination.to_radians(),
+            raan: self.raan.to_radians(),
+            eccentricity: self.eccentricity,
+            argument_of_perigee: self.argument_of_perigee.to_radians(),
+            mean_anomaly: self.mean_anomaly.to_radians(),
+            mean_motion: self.mean_motion * 2.0 * std::f64::consts::PI / 1440.0
+        }
+    }
+
+    /// Initialise the SGP4/SDP4 propagator of the satellite
+    pub fn propagator(&self) -> Result<Sgp4, TleError> {
+        Sgp4::new(self.elements()).map_err(|error| self.propagation_error(error))
+    }
+
+    fn propagation_error(&self, error: Sgp4Error) -> TleError {
+        TleError::Propagation { name: self.name.clone(), error }
+    }
+
+    /// Calculate the state of the satellite relative to the Earth at the given Julian day (UTC),
+    /// in SI units and in the frame of the simulation
+    pub fn state(&self, julian_day: f64) -> Result<(Vector, Vector), TleError> {
+        let state: TemeState = self.propagator()?
+            .propagate_to(julian_day)
+            .map_err(|error| self.propagation_error(error))?;
+        Ok(teme_to_simulation(&state, julian_day))
+    }
+
+    /// Create the satellite as a test particle around the Earth at the given Julian day (UTC)
+    pub fn satellite(&self, earth: &PointBody, julian_day: f64) -> Result<Planet, TleError> {
+        let (position, velocity): (Vector, Vector) = self.state(julian_day)?;
+        let mut satellite: Planet = Planet::new(
+            self.name.clone(),
+            earth.velocity + velocity,
+            earth.coordinates + position.to_point(),
+            Mass::from(SATELLITE_MASS),
+            Length::from(SATELLITE_RADIUS),
+            PlanetType::Satellite
+        );
+        satellite.set_test_particle(true);
+        Ok(satellite)
+    }
+}
+
+
+/// Convert a TEME state (km, km/s) at the given Julian day into SI units in the frame of the simulation
+pub fn teme_to_simulation(state: &TemeState, julian_day: f64) -> (Vector, Vector) {
+    let convert = |vector: [f64; 3]| -> Vector {
+        let ecliptic: [f64; 3] = equatorial_to_ecliptic(teme_to_equatorial(vector, julian_day));
+        ecliptic_to_simulation(ecliptic.map(|value| value * 1000.0))
+    };
+    (convert(state.position), convert(state.velocity))
+}
+
+
+/// The Julian day at 0h UTC of the given date. The day can be 0 (the last day of the previous month).
+pub fn julian_day(year: i32, month: u32, day: f64) -> f64 {
+    let (year, month): (f64, f64) = (year as f64, month as f64);
+    367.0 * year - (7.0 * (year + ((month + 9.0) / 12.0).floor()) * 0.25).floor()
+        + (275.0 * month / 9.0).floor() + day + 1_721_013.5
+}
+
+
+/// A line of an element set, with the fixed columns of the format
+struct Line<'a> {
+    number: usize,
+    text: &'a str
+}
+
+
+impl<'a> Line<'a> {
+    fn new((number, text): (usize, &'a str), kind: char) -> Result<Self, TleError> {
+        if !text.is_ascii() {
+            return Err(TleError::format(number, 1, "the line is not ASCII"));
+        }
+        if !text.starts_with(kind) {
+            return Err(TleError::format(number, 1, format!("expected line {kind} of an element set")));
+        }
+        if text.len() < 68 {
+            return Err(TleError::format(number, text.len() + 1, "the line is shorter than 68 columns"));
+        }
+
+        // The last digit is the sum of the digits modulo 10, where a minus sign counts as 1
+        if let Some(checksum) = text[68..].chars().next().and_then(|digit| digit.to_digit(10)) {
+            let sum: u32 = text[..68].chars()
+                .map(|character| match character { '-' => 1, _ => character.to_digit(10).unwrap_or(0) })
+                .sum();
+            if sum % 10 != checksum {
+                return Err(TleError::Checksum { line: number });
+            }
+        }
+        Ok(Self { number, text })
+    }
+
+    /// The trimmed text between the columns `first` and `last` (starting from 1, inclusive)
+    fn text(&self, first: usize, last: usize) -> &'a str {
+        self.text[first - 1..last].trim()
+    }
+
+    fn number(&self, first: usize, last: usize) -> Result<f64, TleError> {
+        let text: &str = self.text(first, last);
+        text.replace(' ', "").parse::<f64>()
+            .map_err(|_| TleError::format(self.number, first, format!("invalid number '{text}'")))
+    }
+
+    /// A number with an assumed leading decimal point (e.g. `1859667` is `0.1859667`)
+    fn decimal(&self, first: usize, last: usize) -> Result<f64, TleError> {
+        let text: &str = self.text(first, last);
+        format!("0.{text}").parse::<f64>()
+            .map_err(|_| TleError::format(self.number, first, format!("invalid decimal '{text}'")))
+    }
+
+    /// A number with an assumed decimal point and an exponent (e.g. `-11606-4` is `-0.11606e-4`)
+    fn exponent(&self, first: usize, last: usize) -> Result<f64, TleError> {
+        let text: &str = self.text(first, last);
+        let error = || TleError::format(self.number, first, format!("invalid number '{text}'"));
+        let (sign, digits): (&str, &str) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text.strip_prefix('+').unwrap_or(text))
+        };
+        if digits.len() < 2 {
+            return Err(error());
+        }
+        let (mantissa, exponent): (&str, &str) = digits.split_at(digits.len() - 2);
+        let exponent: &str = exponent.trim_start_matches('+');
+        format!("{sign}0.{}e{exponent}", mantissa.trim()).parse::<f64>().map_err(|_| error())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// Asserts that the state is within the tolerances (km and km/s) of the reference
+    fn assert_state(state: &TemeState, position: [f64; 3], velocity: [f64; 3], position_tolerance: f64, velocity_tolerance: f64) {
+        for i in 0..3 {
+            assert!((state.position[i] - position[i]).abs() < position_tolerance, "{:?} != {:?}", state.position, position);
+            assert!((state.velocity[i] - velocity[i]).abs() < velocity_tolerance, "{:?} != {:?}", state.velocity, velocity);
+        }
+    }
+
+    #[test]
+    fn near_earth_matches_the_vallado_vectors() {
+        let tle: Tle = Tle::parse(
+            "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753",
+            "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667"
+        ).unwrap();
+        let propagator: Sgp4 = tle.propagator().unwrap();
+        assert!(!propagator.is_deep_space());
+        // Vallado, Crawford, Hujsak and Kelso, "Revisiting Spacetrack Report #3" (2006), tcppver.out
+        let references: [(f64, [f64; 3], [f64; 3]); 3] = [
+            (0.0, [7022.46529266, -1400.08296755, 0.03995155], [1.893841015, 6.405893759, 4.534807250]),
+            (360.0, [-7154.03120202, -3783.17682504, -3536.19412294], [4.741887409, -4.151817765, -2.093935425]),
+            (720.0, [-7134.59340119, 6531.68641334, 3260.27186483], [-4.113793027, -2.911922039, -2.557327851])
+        ];
+        for (minutes, position, velocity) in references {
+            assert_state(&propagator.propagate(minutes).unwrap(), position, velocity, 1e-6, 1e-9);
+        }
+    }
+
+    #[test]
+    fn deep_space_matches_the_vallado_vectors() {
+        let tle: Tle = Tle::parse(
+            "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813",
+            "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656"
+        ).unwrap();
+        let propagator: Sgp4 = tle.propagator().unwrap();
+        assert!(propagator.is_deep_space());
+        // The lunar-solar periodics move this orbit by about 50 km, they agree with the reference to about 0.1 %
+        let references: [(f64, [f64; 3], [f64; 3]); 2] = [
+            (0.0, [2349.89483350, -14785.93811562, 0.02119378], [2.721488096, -3.256811655, 4.498416672]),
+            (120.0, [15223.91713658, -17852.95881713, 25280.39558224], [1.079041732, 0.875187372, 2.485682813])
+        ];
+        for (minutes, position, velocity) in references {
+            assert_state(&propagator.propagate(minutes).unwrap(), position, velocity, 0.1, 1e-4);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use crate::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+
+/// The first bytes of every columnar trajectory file
+const MAGIC: [u8; 8] = *b"PSIMTRAJ";
+
+/// The version of the columnar trajectory format
+pub const TRAJECTORY_VERSION: u16 = 1;
+
+/// The number of columns written for each body: x, y, z, vx, vy, vz, mass
+const BODY_COLUMNS: usize = 7;
+
+
+/// # Trajectory Format
+/// ## Variants
+/// `Csv` - One row per body and sample: `time,name,x,y,z,vx,vy,vz,mass`\
+/// `Columnar` - Compact little-endian binary, organised in chunks of samples.
+/// The file starts with a header: the magic bytes `PSIMTRAJ`, the format version (`u16`),
+/// the number of bodies (`u32`) and their names (`u32` length + UTF-8 bytes each).
+/// Every chunk starts with its number of samples `n` (`u32`), followed by `n` times,
+/// then for each body in the order of the header the columns `x`, `y`, `z`, `vx`, `vy`, `vz` and `mass`,
+/// each holding `n` values. Every value is an `f64` in SI units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrajectoryFormat {
+    Csv,
+    Columnar
+}
+
+
+/// # Trajectory Output
+/// Streams the states of the bodies (time, name, position, velocity, mass) into a file while the simulation runs.
+/// ## Attributes
+/// `format: TrajectoryFormat` - The format of the file\
+/// `every: u64` - The cadence: a sample is written after every `every` steps\
+/// `bodies: Option<Vec<String>>` - The names of the bodies to write (every body if `None`)\
+/// `chunk_size: usize` - The number of samples in a chunk of the columnar format
+pub struct TrajectoryOutput {
+    pub format: TrajectoryFormat,
+    pub every: u64,
+    pub bodies: Option<Vec<String>>,
+    pub chunk_size: usize,
+    file: BufWriter<File>,
+    columns: Option<Vec<usize>>,
+    times: Vec<f64>,
+    values: Vec<Vec<f64>>
+}
+
+
+impl TrajectoryOutput {
+    /// Create the output file, and write a sample after every step into it
+    pub fn create(path: impl AsRef<Path>, format: TrajectoryFormat) -> std::io::Result<Self> {
+        Ok(Self {
+            format,
+            every: 1,
+            bodies: None,
+            chunk_size: 1024,
+            file: BufWriter::new(File::create(path)?),
+            columns: None,
+            times: Vec::new(),
+            values: Vec::new()
+        })
+    }
+
+    /// Only write a sample after every `steps` steps
+    pub fn every(mut self, steps: u64) -> Self {
+        self.every = steps.max(1);
+        self
+    }
+
+    /// Only write the bodies with the given names
+    pub fn only(mut self, bodies: Vec<String>) -> Self {
+        self.bodies = Some(bodies);
+        self
+    }
+
+    /// Set the number of samples in a chunk of the columnar format
+    pub fn chunk_size(mut self, samples: usize) -> Self {
+        self.chunk_size = samples.max(1);
+        self
+    }
+
+    fn is_included(&self, name: &str) -> bool {
+        match &self.bodies {
+            Some(bodies) => bodies.iter().any(|body| body == name),
+            None => true
+        }
+    }
+
+    /// Write a sample if the step is on the cadence
+    pub fn record(&mut self, step: u64, time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) {
+        if step.is_multiple_of(self.every) {
+            self.sample(time, celestials);
+        }
+    }
+
+    /// Write a sample of the bodies.
+    /// A failing file does not stop the simulation, the error is reported on the standard error.
+    pub fn sample(&mut self, time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) {
+        let result: std::io::Result<()> = match self.format {
+            TrajectoryFormat::Csv => self.write_csv_rows(time, celestials),
+            TrajectoryFormat::Columnar => self.buffer_columns(time, celestials)
+        };
+        if let Err(error) = result {
+            eprintln!("Could not write the trajectory: {error}");
+        }
+    }
+
+    fn write_csv_rows(&mut self, time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) -> std::io::Result<()> {
+        if self.columns.is_none() {
+            writeln!(self.file, "time,name,x,y,z,vx,vy,vz,mass")?;
+            self.columns = Some(Vec::new());
+        }
+
+        for celestial in celestials.iter() {
+            let name: String = celestial.get_name();
+            if !self.is_included(&name) { continue; }
+
+            let body: &PointBody = celestial.point_body();
+            writeln!(
+                self.file,
+                "{},{},{},{},{},{},{},{},{}",
+                time.to_f64(),
+                name,
+                body.coordinates.x.to_f64(),
+                body.coordinates.y.to_f64(),
+                body.coordinates.z.to_f64(),
+                body.velocity.point.x.to_f64(),
+                body.velocity.point.y.to_f64(),
+                body.velocity.point.z.to_f64(),
+                body.mass.to_f64()
+            )?;
+        }
+        Ok(())
+    }
+
+    fn buffer_columns(&mut self, time: Scalar, celestials: &[Box<dyn Celestial + Sync>]) -> std::io::Result<()> {
+        // The bodies of the file are fixed by the first sample
+        if self.columns.is_none() {
+            let columns: Vec<usize> = (0..celestials.len())
+                .filter(|i| self.is_included(&celestials[*i].get_name()))
+                .collect();
+
+            self.file.write_all(&MAGIC)?;
+            self.file.write_all(&TRAJECTORY_VERSION.to_le_bytes())?;
+            self.file.write_all(&(columns.len() as u32).to_le_bytes())?;
+            for i in columns.iter() {
+                let name: String = celestials[*i].get_name();
+                self.file.write_all(&(name.len() as u32).to_le_bytes())?;
+                self.file.write_all(name.as_bytes())?;
+            }
+
+            self.values = vec![Vec::with_capacity(self.chunk_size); columns.len() * BODY_COLUMNS];
+            self.columns = Some(columns);
+        }
+
+        self.times.push(time.to_f64());
+        let columns: &[usize] = self.columns.as_deref().unwrap_or_default();
+        for (n, i) in columns.iter().enumerate() {
+            let body: &PointBody = celestials[*i].point_body();
+            let row: [Scalar; BODY_COLUMNS] = [
+                body.coordinates.x,
+                body.coordinates.y,
+                body.coordinates.z,
+                body.velocity.point.x,
+                body.velocity.point.y,
+                body.velocity.point.z,
+                body.mass.value
+            ];
+            for (column, value) in row.iter().enumerate() {
+                self.values[n * BODY_COLUMNS + column].push(value.to_f64());
+            }
+        }
+
+        if self.times.len() >= self.chunk_size {
+            self.write_chunk()?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffered samples as one chunk
+    fn write_chunk(&mut self) -> std::io::Result<()> {
+        if self.times.is_empty() { return Ok(()); }
+
+        self.file.write_all(&(self.times.len() as u32).to_le_bytes())?;
+        for column in std::iter::once(&mut self.times).chain(self.values.iter_mut()) {
+            for value in column.iter() {
+                self.file.write_all(&value.to_le_bytes())?;
+            }
+            column.clear();
+        }
+        Ok(())
+    }
+
+    /// Write the buffered samples into the file
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.write_chunk()?;
+        self.file.flush()
+    }
+}
+
+
+impl Drop for TrajectoryOutput {
+    fn drop(&mut self) {
+        if let Err(error) = self.flush() {
+            eprintln!("Could not write the trajectory: {error}");
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn bodies() -> Vec<Box<dyn Celestial + Sync>> {
+        vec![
+            Box::new(star!("Sun", 0, 0, 2e30, 7e8)),
+            Box::new(planet!("Earth", (0, 0, 29_780), (1.496e11, 0, 0), 6e24, 6.4e6))
+        ]
+    }
+
+    /// Write three samples, moving the Earth by a minute between them
+    fn write(format: TrajectoryFormat, name: &str) -> Vec<u8> {
+        let path: std::path::PathBuf = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+        let mut celestials: Vec<Box<dyn Celestial + Sync>> = bodies();
+        {
+            let mut output: TrajectoryOutput = TrajectoryOutput::create(&path, format).unwrap().chunk_size(2);
+            for step in 0..3 {
+                output.sample(scalar!(step * 60), &celestials);
+                celestials[1].point_body_mut().advance(Time::from(60));
+            }
+        }
+        let bytes: Vec<u8> = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        bytes
+    }
+
+    fn take<'a>(bytes: &mut &'a [u8], length: usize) -> &'a [u8] {
+        let (head, tail): (&[u8], &[u8]) = bytes.split_at(length);
+        *bytes = tail;
+        head
+    }
+
+    fn take_u32(bytes: &mut &[u8]) -> usize {
+        u32::from_le_bytes(take(bytes, 4).try_into().unwrap()) as usize
+    }
+
+    fn take_f64s(bytes: &mut &[u8], count: usize) -> Vec<f64> {
+        (0..count).map(|_| f64::from_le_bytes(take(bytes, 8).try_into().unwrap())).collect()
+    }
+
+    #[test]
+    fn columnar_chunks_are_written() {
+        let file: Vec<u8> = write(TrajectoryFormat::Columnar, "columnar.bin");
+        let mut bytes: &[u8] = &file;
+
+        assert_eq!(take(&mut bytes, 8), b"PSIMTRAJ");
+        assert_eq!(u16::from_le_bytes(take(&mut bytes, 2).try_into().unwrap()), TRAJECTORY_VERSION);
+        assert_eq!(take_u32(&mut bytes), 2);
+        for name in ["Sun", "Earth"] {
+            let length: usize = take_u32(&mut bytes);
+            assert_eq!(take(&mut bytes, length), name.as_bytes());
+        }
+
+        // A full chunk of two samples, then the rest written by the flush
+        assert_eq!(take_u32(&mut bytes), 2);
+        assert_eq!(take_f64s(&mut bytes, 2), vec![0.0, 60.0]);
+        let first: Vec<f64> = take_f64s(&mut bytes, 2 * 2 * 7);
+        assert_eq!(take_u32(&mut bytes), 1);
+        assert_eq!(take_f64s(&mut bytes, 1), vec![120.0]);
+        let second: Vec<f64> = take_f64s(&mut bytes, 2 * 7);
+        assert!(bytes.is_empty());
+
+        // The columns of the Earth (x, y, z, vx, vy, vz, mass) follow the ones of the Sun
+        assert_eq!(first[7 * 2..7 * 2 + 2], [1.496e11, 1.496e11]);
+        assert_eq!(first[9 * 2..9 * 2 + 2], [0.0, 60.0 * 29_780.0]);
+        assert_eq!(second[7..], [1.496e11, 0.0, 120.0 * 29_780.0, 0.0, 0.0, 29_780.0, 6e24]);
+    }
+
+    #[test]
+    fn csv_rows_are_written() {
+        let file: String = String::from_utf8(write(TrajectoryFormat::Csv, "rows.csv")).unwrap();
+        let lines: Vec<&str> = file.lines().collect();
+        assert_eq!(lines.len(), 1 + 3 * 2);
+        assert_eq!(lines[0], "time,name,x,y,z,vx,vy,vz,mass");
+        assert_eq!(lines[4], "60,Earth,149600000000,0,1786800,0,0,29780,6000000000000000000000000");
+    }
+}
@@ -0,0 +1,562 @@
+use crate::*;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+
+const DEFAULT_SCENARIO: &str = "simulations/sun_and_terrestrials.psa";
+
+const USAGE: &str = "\
+Usage: physics-simulator <command> [options]
+
+Commands:
+  view <input>                 Open the interactive viewer
+  run <input> --until <time>   Integrate without a window until the given simulation time,
+      [--output <file>]...     and export the states
+  convert <input> <output>     Write the state of a simulation in another format
+  info <input>                 Print the bodies, their orbital elements and the energy
+  help                         Print this message
+
+The input is a scenario (.psa), a checkpoint (.psc), a JPL Horizons vector table (.txt)
+or a CCSDS ephemeris (.oem), chosen by the extension or by --from. The bodies of the tables
+and the ephemerides are massless test particles, read at the --epoch (their first state by default).
+The outputs can be scenarios (.psa), checkpoints (.psc), CCSDS ephemerides (.oem)
+and trajectories (.csv, or .bin for the columnar format).
+Without a command, the input (or the Solar System scenario) is opened in the viewer.
+
+Options:
+  --dt <time>            The time step (e.g. `30 min`), negative to run backwards
+  --integrator <name>    `euler` (the default) or `leapfrog`
+  --threads <n>          The number of threads (1: single-threaded, 0: every core)
+  --from <format>        The format of the input: `psa`, `psc`, `horizons` or `oem`
+  --every <n>            Write a sample of the trajectories after every `n` steps
+  --epoch <julian day>   The date of the state of the input (the state read from the .txt and .oem inputs)
+  --center <name>        The central body of the orbital elements and the ephemerides";
+
+
+/// # CLI Error
+/// An error of the command line
+/// - `Usage` - The arguments are invalid
+/// - `File` - The input could not be read, or an output could not be written
+#[derive(Debug)]
+pub enum CliError {
+    Usage(String),
+    File { path: String, message: String }
+}
+
+
+impl CliError {
+    fn usage(message: impl ToString) -> Self { Self::Usage(message.to_string()) }
+
+    fn file(path: &str, message: impl ToString) -> Self {
+        Self::File { path: path.to_string(), message: message.to_string() }
+    }
+}
+
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Usage(message) => write!(f, "{message}\n\n{USAGE}"),
+            Self::File { path, message } => write!(f, "{path}: {message}")
+        }
+    }
+}
+
+
+impl std::error::Error for CliError {}
+
+
+/// # Command
+/// A subcommand of the command line
+/// - `View` - Open the interactive viewer\
+/// - `Run` - Integrate without a window until the time `until`, exporting the states into the `outputs`\
+/// - `Convert` - Write the state of the input into the output\
+/// - `Info` - Print the bodies, their orbital elements and the energy\
+/// - `Help` - Print the usage
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    View { input: String },
+    Run { input: String, until: Time, outputs: Vec<String> },
+    Convert { input: String, output: String },
+    Info { input: String },
+    Help
+}
+
+
+/// # Options
+/// The settings of the simulation given on the command line.
+/// The ones which are not given are taken from the input.
+/// ## Attributes
+/// `dt: Option<Time>` - The time step\
+/// `integrator: Option<Integrator>` - The integration scheme\
+/// `threads: Option<usize>` - The number of threads (`1` is single-threaded, `0` uses every core)\
+/// `from: Option<InputKind>` - The format of the input (chosen by the extension if `None`)\
+/// `every: u64` - The cadence of the trajectory outputs\
+/// `epoch: Option<f64>` - The Julian day of the state of the input\
+/// `center: Option<String>` - The central body of the orbital elements and the ephemerides
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Options {
+    pub dt: Option<Time>,
+    pub integrator: Option<Integrator>,
+    pub threads: Option<usize>,
+    pub from: Option<InputKind>,
+    pub every: u64,
+    pub epoch: Option<f64>,
+    pub center: Option<String>
+}
+
+
+/// # Input Kind
+/// The kinds of files a simulation can be read from, chosen by the extension or by `--from`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputKind {
+    Scenario,
+    Checkpoint,
+    Horizons,
+    Ephemeris
+}
+
+
+impl InputKind {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "psa" => Some(Self::Scenario),
+            "psc" => Some(Self::Checkpoint),
+            "horizons" | "txt" => Some(Self::Horizons),
+            "oem" => Some(Self::Ephemeris),
+            _ => None
+        }
+    }
+
+    fn from_path(path: &str) -> Result<Self, CliError> {
+        let extension: String = Path::new(path).extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self::parse(&extension).ok_or_else(|| CliError::usage(format!(
+            "{path}: unknown input format, expected .psa, .psc, .txt (Horizons) or .oem, or give it with --from"
+        )))
+    }
+}
+
+
+/// The states of one body of a Horizons table or an ephemeris: the Julian days, positions and velocities
+struct Track {
+    name: String,
+    center: String,
+    states: Vec<(f64, Point, Vector)>
+}
+
+
+/// The kinds of files the simulation can be written into, chosen by the extension
+enum OutputKind {
+    Scenario,
+    Checkpoint,
+    Ephemeris,
+    Trajectory(TrajectoryFormat)
+}
+
+
+impl OutputKind {
+    fn from_path(path: &str) -> Result<Self, CliError> {
+        let extension: String = Path::new(path).extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "psa" => Ok(Self::Scenario),
+            "psc" => Ok(Self::Checkpoint),
+            "oem" => Ok(Self::Ephemeris),
+            "csv" => Ok(Self::Trajectory(TrajectoryFormat::Csv)),
+            "bin" => Ok(Self::Trajectory(TrajectoryFormat::Columnar)),
+            _ => Err(CliError::usage(format!("{path}: unknown output format, expected .psa, .psc, .oem, .csv or .bin")))
+        }
+    }
+}
+
+
+/// # CLI
+/// The parsed command line
+/// ## Attributes
+/// `command: Command` - The subcommand\
+/// `options: Options` - The settings of the simulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub command: Command,
+    pub options: Options
+}
+
+
+impl Cli {
+    /// Parse the arguments (without the name of the program)
+    pub fn parse(arguments: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut options: Options = Options { every: 1, ..Options::default() };
+        let mut positional: Vec<String> = Vec::new();
+        let mut until: Option<Time> = None;
+        let mut outputs: Vec<String> = Vec::new();
+
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            if argument == "--help" || argument == "-h" {
+                return Ok(Self { command: Command::Help, options });
+            }
+            if !argument.starts_with("--") {
+                positional.push(argument);
+                continue;
+            }
+
+            let value: String = arguments.next()
+                .ok_or_else(|| CliError::usage(format!("{argument}: missing value")))?;
+            let invalid = |message: &dyn Display| CliError::usage(format!("{argument} {value}: {message}"));
+            match argument.as_str() {
+                "--dt" => options.dt = Some(parse_time(&value).map_err(|error| invalid(&error))?),
+                "--until" => until = Some(parse_time(&value).map_err(|error| invalid(&error))?),
+                "--integrator" => options.integrator = Some(
+                    Integrator::parse(&value).ok_or_else(|| invalid(&"expected `euler` or `leapfrog`"))?
+                ),
+                "--threads" => options.threads = Some(
+                    value.parse().map_err(|_| invalid(&"expected a number of threads"))?
+                ),
+                "--from" => options.from = Some(
+                    InputKind::parse(&value).ok_or_else(|| invalid(&"expected `psa`, `psc`, `horizons` or `oem`"))?
+                ),
+                "--every" => options.every = match value.parse() {
+                    Ok(every) if every > 0 => every,
+                    _ => return Err(invalid(&"expected a positive number of steps"))
+                },
+                "--epoch" => options.epoch = Some(value.parse().map_err(|_| invalid(&"expected a Julian day"))?),
+                "--center" => options.center = Some(value),
+                "--output" => outputs.push(value),
+                _ => return Err(CliError::usage(format!("unknown option {argument}")))
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let name: Option<String> = positional.next();
+        let mut input = |command: &str| positional.next()
+            .ok_or_else(|| CliError::usage(format!("{command}: missing input file")));
+        let command: Command = match name.as_deref() {
+            None => Command::View { input: String::from(DEFAULT_SCENARIO) },
+            Some("help") => Command::Help,
+            Some("view") => Command::View { input: input("view")? },
+            Some("info") => Command::Info { input: input("info")? },
+            Some("convert") => Command::Convert {
+                input: input("convert")?,
+                output: input("convert").map_err(|_| CliError::usage("convert: missing output file"))?
+            },
+            Some("run") => Command::Run {
+                input: input("run")?,
+                until: until.ok_or_else(|| CliError::usage("run: missing --until"))?,
+                outputs: std::mem::take(&mut outputs)
+            },
+            // A bare input file is opened in the viewer
+            Some(path) => Command::View { input: path.to_string() }
+        };
+
+        if let Some(extra) = positional.next() {
+            return Err(CliError::usage(format!("unexpected argument {extra}")));
+        }
+        if !matches!(command, Command::Run { .. }) && (until.is_some() || !outputs.is_empty()) {
+            return Err(CliError::usage("--until and --output are only used by run"));
+        }
+        Ok(Self { command, options })
+    }
+
+    /// Execute the command
+    pub fn execute(&self) -> Result<(), CliError> {
+        match &self.command {
+            Command::Help => {
+                println!("{USAGE}");
+                Ok(())
+            },
+            Command::View { input } => self.view(input),
+            Command::Run { input, until, outputs } => {
+                let mut simulation: AstronomicalSimulation = self.load(input)?;
+                let start: u64 = simulation.get_steps();
+                self.export(&mut simulation, outputs, Some(*until))?;
+                let steps: u64 = simulation.get_steps() - start;
+                println!(
+                    "{}: {steps} steps, t = {} s",
+                    simulation.get_name(), simulation.get_time()
+                );
+                Ok(())
+            },
+            Command::Convert { input, output } => {
+                let mut simulation: AstronomicalSimulation = self.load(input)?;
+                self.export(&mut simulation, std::slice::from_ref(output), None)
+            },
+            Command::Info { input } => {
+                let simulation: AstronomicalSimulation = self.load(input)?;
+                self.info(&simulation)
+            }
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    fn view(&self, input: &str) -> Result<(), CliError> {
+        let mut simulation: AstronomicalSimulation = self.load(input)?;
+        simulation.run();
+        Ok(())
+    }
+
+    #[cfg(not(feature = "graphics"))]
+    fn view(&self, _input: &str) -> Result<(), CliError> {
+        Err(CliError::usage("view: this build has no viewer, enable the `graphics` feature to open it"))
+    }
+
+    /// Read the input, and apply the options to it
+    fn load(&self, input: &str) -> Result<AstronomicalSimulation, CliError> {
+        let kind: InputKind = match self.options.from {
+            Some(kind) => kind,
+            None => InputKind::from_path(input)?
+        };
+        let mut simulation: AstronomicalSimulation = match kind {
+            InputKind::Scenario => load_simulation(input).map_err(|error| CliError::file(input, error))?,
+            InputKind::Checkpoint => AstronomicalSimulation::read_checkpoint(input)
+                .map_err(|error| CliError::file(input, error))?,
+            InputKind::Horizons => {
+                let table: HorizonsVectors = HorizonsVectors::load(input).map_err(|error| CliError::file(input, error))?;
+                self.load_tracks(input, vec![Track {
+                    name: table.target,
+                    center: table.center,
+                    states: table.states.iter().map(|state| (state.epoch, state.coordinates, state.velocity)).collect()
+                }])?
+            },
+            InputKind::Ephemeris => {
+                let ephemeris: OemEphemeris = OemEphemeris::load(input).map_err(|error| CliError::file(input, error))?;
+                self.load_tracks(input, ephemeris.objects.into_iter().map(|object| Track {
+                    name: object.name,
+                    center: object.center,
+                    states: object.states.iter().map(|state| (state.epoch, state.coordinates, state.velocity)).collect()
+                }).collect())?
+            }
+        };
+
+        if let Some(dt) = self.options.dt {
+            simulation.set_dt(dt.value);
+        }
+        if let Some(integrator) = self.options.integrator {
+            simulation.set_integrator(integrator);
+        }
+        if let Some(threads) = self.options.threads {
+            if threads > 1 {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build_global()
+                    .map_err(|error| CliError::usage(format!("--threads {threads}: {error}")))?;
+            }
+            simulation.set_multi_processor(threads != 1);
+        }
+
+        // The epoch option is the date of the state, which is at the current time of the simulation
+        if let Some(epoch) = self.options.epoch {
+            simulation.set_epoch(Some(epoch - (simulation.get_time() / DAY.value).to_f64()));
+        }
+        Ok(simulation)
+    }
+
+    /// Create a simulation of massless test particles from the states of the tracks at the `--epoch`
+    /// (the first state by default). The time step is the spacing of the states, unless `--dt` is given.
+    fn load_tracks(&self, input: &str, tracks: Vec<Track>) -> Result<AstronomicalSimulation, CliError> {
+        let epoch: f64 = match self.options.epoch {
+            Some(epoch) => epoch,
+            None => tracks.iter()
+                .find_map(|track| track.states.first())
+                .map(|(epoch, _, _)| *epoch)
+                .ok_or_else(|| CliError::file(input, "there are no states"))?
+        };
+
+        let mut dt: Option<Time> = self.options.dt;
+        let mut bodies: Vec<BodyDescription> = Vec::new();
+        for track in tracks.iter() {
+            if let Some(first) = tracks.first().filter(|first| first.center != track.center) {
+                return Err(CliError::file(input, format!(
+                    "the states of {} are relative to '{}', but the earlier ones to '{}'", track.name, track.center, first.center
+                )));
+            }
+            let index: usize = track.states.iter()
+                .position(|(time, _, _)| (time - epoch).abs() <= 1e-6)
+                .ok_or_else(|| CliError::file(input, format!("{} has no state at JD {epoch}", track.name)))?;
+            // The Julian days are only precise to about 40 µs, so the spacing is rounded to milliseconds
+            if let (None, Some((next, _, _))) = (dt, track.states.get(index + 1)) {
+                dt = Some(Time::new(Scalar::from(((next - epoch) * 86_400_000.0).round() / 1000.0)));
+            }
+            let (_, coordinates, velocity) = track.states[index];
+            bodies.push(BodyDescription {
+                name: track.name.clone(),
+                kind: BodyKind::Planet(PlanetType::Satellite),
+                mass: Mass::new(ZERO),
+                radius: Length::new(ZERO),
+                velocity,
+                coordinates,
+                tracer: false,
+                test_particle: true,
+                engine: None
+            });
+        }
+
+        let scenario: Scenario = Scenario {
+            name: Path::new(input).file_stem().map_or_else(|| input.to_string(), |stem| stem.to_string_lossy().to_string()),
+            dt: dt.ok_or_else(|| CliError::usage(format!("{input}: the time step is not known, give it with --dt")))?,
+            time: Time::new(ZERO),
+            epoch: Some(epoch),
+            bodies
+        };
+        Ok(scenario.build())
+    }
+
+    /// Take `steps` steps, streaming the trajectories and the ephemerides into the outputs,
+    /// then write the scenarios and the checkpoints of the final state
+    fn export(&self,
+              simulation: &mut AstronomicalSimulation,
+              outputs: &[String],
+              until: Option<Time>) -> Result<(), CliError> {
+        let steps: u64 = match until {
+            Some(until) => steps_until(simulation, until)?,
+            None => 0
+        };
+        let kinds: Vec<OutputKind> = outputs.iter()
+            .map(|output| OutputKind::from_path(output))
+            .collect::<Result<_, _>>()?;
+
+        for (output, kind) in outputs.iter().zip(kinds.iter()) {
+            match kind {
+                OutputKind::Trajectory(format) => {
+                    let trajectory: TrajectoryOutput = TrajectoryOutput::create(output, *format)
+                        .map_err(|error| CliError::file(output, error))?;
+                    simulation.add_trajectory_output(trajectory.every(self.options.every));
+                },
+                OutputKind::Ephemeris => {
+                    let epoch: f64 = simulation.get_epoch().ok_or_else(|| CliError::usage(format!(
+                        "{output}: the date of the input is not known, give it with --epoch"
+                    )))?;
+                    let mut ephemeris: OemOutput = OemOutput::new(output, epoch).every(self.options.every);
+                    if let Some(center) = &self.options.center {
+                        ephemeris = ephemeris.center(center);
+                    }
+                    simulation.add_oem_output(ephemeris);
+                },
+                OutputKind::Scenario | OutputKind::Checkpoint => ()
+            }
+        }
+
+        simulation.run_for(steps);
+        if let Some(until) = until {
+            // The last step is shortened to stop at `until`
+            let dt: Scalar = simulation.get_dt();
+            let remaining: Scalar = until.value - simulation.get_time();
+            if remaining.abs() > dt.abs() * Scalar::from(1e-9) {
+                simulation.set_dt(remaining);
+                simulation.step();
+                simulation.set_dt(dt);
+            }
+        }
+        simulation.flush_trajectories().map_err(|error| CliError::file(&outputs.join(", "), error))?;
+
+        for (output, kind) in outputs.iter().zip(kinds.iter()) {
+            match kind {
+                OutputKind::Scenario => {
+                    let skipped: Vec<String> = save_simulation(simulation, output)
+                        .map_err(|error| CliError::file(output, error))?;
+                    if !skipped.is_empty() {
+                        eprintln!("{output}: saved without {}", skipped.join(", "));
+                    }
+                },
+                OutputKind::Checkpoint => simulation.write_checkpoint(output)
+                    .map_err(|error| CliError::file(output, error))?,
+                OutputKind::Ephemeris | OutputKind::Trajectory(_) => ()
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the bodies, their orbital elements around the central body and the energy
+    fn info(&self, simulation: &AstronomicalSimulation) -> Result<(), CliError> {
+        let celestials: &[Box<dyn Celestial + Sync>] = simulation.get_celestials();
+        println!("{} ({} bodies)", simulation.get_name(), celestials.len());
+        println!(
+            "time: {} s, dt: {} s, integrator: {}",
+            simulation.get_time(), simulation.get_dt(), simulation.get_integrator()
+        );
+
+        println!();
+        println!("{:<24} {:<12} {:>14} {:>14}", "Body", "Type", "Mass (kg)", "Radius (m)");
+        for celestial in celestials.iter() {
+            let kind: String = if celestial.is_star() { String::from("Star") } else {
+                celestial.planet_type().map_or_else(|| String::from("-"), |planet_type| planet_type.to_string())
+            };
+            println!(
+                "{:<24} {:<12} {:>14.6e} {:>14.6e}",
+                celestial.get_name(), kind,
+                celestial.point_body().mass.to_f64(), celestial.get_radius().to_f64()
+            );
+        }
+
+        // The elements are measured around the given body, or the most massive one
+        let center: Option<&(dyn Celestial + Sync)> = match &self.options.center {
+            Some(name) => Some(simulation.get_celestial_from_name(name)
+                .ok_or_else(|| CliError::usage(format!("--center {name}: there is no body called {name}")))?),
+            None => celestials.iter()
+                .max_by(|a, b| a.point_body().mass.partial_cmp(&b.point_body().mass).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|celestial| celestial.as_ref())
+        };
+        if let Some(center) = center {
+            println!();
+            println!("Orbital elements around {}:", center.get_name());
+            println!(
+                "{:<24} {:>14} {:>12} {:>10} {:>10} {:>10} {:>10} {:>14}",
+                "Body", "a (AU)", "e", "i (°)", "Ω (°)", "ω (°)", "M (°)", "Period (d)"
+            );
+            let center_name: String = center.get_name();
+            for celestial in celestials.iter().filter(|celestial| celestial.get_name() != center_name) {
+                let body: &PointBody = celestial.point_body();
+                let elements: OrbitalElements = OrbitalElements::from_bodies(body, center.point_body());
+                let period: String = if elements.eccentricity < 1.0 {
+                    let gravitational_parameter: Scalar = constants::G * (body.mass + center.point_body().mass).value;
+                    format!("{:.4}", (elements.period(gravitational_parameter) / DAY.value).to_f64())
+                } else { String::from("-") };
+                println!(
+                    "{:<24} {:>14.6} {:>12.6} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>14}",
+                    celestial.get_name(),
+                    (elements.semi_major_axis / AU.value).to_f64(),
+                    elements.eccentricity,
+                    elements.inclination.to_degrees(),
+                    elements.longitude_of_ascending_node.to_degrees(),
+                    elements.argument_of_periapsis.to_degrees(),
+                    elements.mean_anomaly.to_degrees(),
+                    period
+                );
+            }
+        }
+
+        let (kinetic, potential): (Energy, Energy) = simulation.energy();
+        println!();
+        println!("Energy (without the test particles):");
+        println!("kinetic:   {:.12e} J", kinetic.value.to_f64());
+        println!("potential: {:.12e} J", potential.value.to_f64());
+        println!("total:     {:.12e} J", (kinetic.value + potential.value).to_f64());
+        Ok(())
+    }
+}
+
+
+/// Parse a time with its unit (e.g. `1.5 yr`, `30 min`)
+fn parse_time(value: &str) -> Result<Time, UnitError> {
+    parse_quantity(value).and_then(|quantity| quantity.time())
+}
+
+
+/// The number of whole steps before the time `until` (the rest is covered by a shorter step)
+fn steps_until(simulation: &AstronomicalSimulation, until: Time) -> Result<u64, CliError> {
+    let dt: Scalar = simulation.get_dt();
+    if dt == ZERO {
+        return Err(CliError::usage("run: the time step is 0"));
+    }
+    let steps: f64 = ((until.value - simulation.get_time()) / dt).to_f64();
+    if steps < 0.0 {
+        return Err(CliError::usage(format!(
+            "run: --until is {} the current time, use a {} --dt",
+            if dt > ZERO { "before" } else { "after" },
+            if dt > ZERO { "negative" } else { "positive" }
+        )));
+    }
+    Ok(steps.floor() as u64)
+}
@@ -26,24 +26,35 @@ pub const G: Scalar = Scalar::new(6.6743e-11);
 pub const g: Vector = Vector::new(ZERO, Scalar::new(-9.81), ZERO);
 
 
+/// # Standard Gravity
+/// The standard acceleration due to gravity, used for specific impulse\
+/// Value: `9.80665 ms^-2`
+pub const STANDARD_GRAVITY: Acceleration = Acceleration::new(Scalar::new(9.80665));
+
+
 /// # PI
 pub const PI: Scalar = Scalar::new(std::f64::consts::PI);
 
 
 /// # Astronomical Unit
 /// The average distance between the Earth and the Sun
-pub const AU: Scalar = Scalar::new(149_597_870_700.0);
+pub const AU: Length = Length::new(Scalar::new(149_597_870_700.0));
 
 
 /// # Light Year
 /// The distance light travels in 365.25 days
-pub const LIGHTYEAR: Scalar = Scalar::new(9.4605284e15);
+pub const LIGHTYEAR: Length = Length::new(Scalar::new(9.4605284e15));
+
+
+/// # Parsec
+/// The distance at which one astronomical unit subtends one arcsecond
+pub const PARSEC: Length = Length::new(Scalar::new(3.085_677_581_491_367e16));
 
 
 /// # Earth's Mass
 /// The mass of Earth\
 /// Value: `5.97219 * 10^24 kg`
-pub const EARTH_MASS: Scalar = Scalar::new(5.97219e24);
+pub const EARTH_MASS: Mass = Mass::new(Scalar::new(5.97219e24));
 
 
 /// # Earth's Velocity
@@ -55,13 +66,25 @@ pub const EARTH_VELOCITY: Vector = Vector::new(Scalar::new(-29_780.0), ZERO, ZER
 /// # Solar Mass
 /// The mass of the Sun.\
 /// Value: `2 * 10^30 kg`
-pub const SOLAR_MASS: Scalar = Scalar::new(2e30);
+pub const SOLAR_MASS: Mass = Mass::new(Scalar::new(2e30));
+
+
+/// # Solar Radius
+/// The radius of the Sun.\
+/// Value: `695 700 km`
+pub const SOLAR_RADIUS: Length = Length::new(Scalar::new(6.957e8));
 
 
 /// # Solar Luminosity
 /// The power emitted by the Sun.\
 /// Value: `3.828 * 10^26 W`
-pub const SOLAR_LUMINOSITY: Scalar = Scalar::new(3.828e26);
+pub const SOLAR_LUMINOSITY: Power = Power::new(Scalar::new(3.828e26));
+
+
+/// # Solar Temperature
+/// The effective surface temperature of the Sun.\
+/// Value: `5772 K`
+pub const SOLAR_TEMPERATURE: Temperature = Temperature::new(Scalar::new(5772.0));
 
 
 /// # Stefan-Boltzmann Constant
@@ -71,34 +94,34 @@ pub const STEFAN_BOLTZMANN_CONSTANT: Scalar = Scalar::new(5.670367e-8);
 
 
 /// # One Second
-pub const SECOND: Scalar = Scalar::new(1.0);
+pub const SECOND: Time = Time::new(Scalar::new(1.0));
 
 
 /// # One Minute
-pub const MINUTE: Scalar = Scalar::new(60.0);
+pub const MINUTE: Time = Time::new(Scalar::new(60.0));
 
 
 /// # One Hour
-pub const HOUR: Scalar = Scalar::new(3600.0);
+pub const HOUR: Time = Time::new(Scalar::new(3600.0));
 
 
 /// # One Day
-pub const DAY: Scalar = Scalar::new(86_400.0);
+pub const DAY: Time = Time::new(Scalar::new(86_400.0));
 
 
 /// # One Week
-pub const WEEK: Scalar = Scalar::new(604_800.0);
+pub const WEEK: Time = Time::new(Scalar::new(604_800.0));
 
 
 /// # One Month
-pub const MONTH: Scalar = Scalar::new(2_419_200.0);
+pub const MONTH: Time = Time::new(Scalar::new(2_419_200.0));
 
 
 /// # One Year
-pub const YEAR: Scalar = Scalar::new(31_556_926.0);
+pub const YEAR: Time = Time::new(Scalar::new(31_556_926.0));
 
 
 /// # Lightspeed
 /// The speed of light moving in a vacuum\
 /// Value: `299 792 458 m/s`
-pub const c: Scalar = Scalar::new(299_792_458.0);
+pub const c: Velocity = Velocity::new(Scalar::new(299_792_458.0));
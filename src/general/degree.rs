@@ -77,11 +77,7 @@ impl std::fmt::Binary for Degree {
 
 impl Clone for Degree {
     fn clone(&self) -> Self {
-        Self {
-            degree: self.degree,
-            minute: self.minute,
-            second: self.second
-        }
+        *self
     }
 }
 
@@ -90,9 +86,9 @@ impl Copy for Degree { }
 
 #[macro_export]
 macro_rules! degree {
-    () => { Degree::new(0, 0, 0) };
-    ($x: expr) => { Degree::new($x) };
-    ($x: expr, $y: expr) => { Degree::new($x, $y, 0) };
-    ($x: expr, $y: expr, $z: expr) => { Degree::new($x, $y, $z) };
-    (($x: expr, $y: expr, $z: expr)) => { Degree::new($x, $y, $z) };
+    () => { $crate::general::Degree::new(0, 0, 0) };
+    ($x: expr) => { $crate::general::Degree::new($x, 0, 0) };
+    ($x: expr, $y: expr) => { $crate::general::Degree::new($x, $y, 0) };
+    ($x: expr, $y: expr, $z: expr) => { $crate::general::Degree::new($x, $y, $z) };
+    (($x: expr, $y: expr, $z: expr)) => { $crate::general::Degree::new($x, $y, $z) };
 }
\ No newline at end of file
@@ -0,0 +1,227 @@
+use std::fmt::{Display, Formatter, LowerExp, UpperExp};
+use std::ops::{Add, Sub, Mul, Div, Neg};
+
+
+/// # Float
+/// The numeric type behind `Scalar`, `Point` and `Vector`.\
+/// By default this is `f64`.
+/// With the `double-double` feature it is `DoubleDouble`, which has about 106 bits of precision.
+#[cfg(not(feature = "double-double"))]
+pub type Float = f64;
+
+/// # Float
+/// The numeric type behind `Scalar`, `Point` and `Vector`.\
+/// By default this is `f64`.
+/// With the `double-double` feature it is `DoubleDouble`, which has about 106 bits of precision.
+#[cfg(feature = "double-double")]
+pub type Float = DoubleDouble;
+
+
+/// # Double-Double
+/// An unevaluated sum of two `f64` values, `hi + lo`, where `|lo| <= ulp(hi) / 2`.
+/// This gives about 106 bits of mantissa (~32 decimal digits),
+/// which keeps sub-millimetre resolution even at interstellar distances.
+/// ## Attributes
+/// `hi: f64` - The leading part of the value\
+/// `lo: f64` - The rounding error of `hi`
+/// ## Precision
+/// The arithmetic operations and the square root are accurate to ~106 bits,
+/// the transcendental functions (`ln`, non-integer powers) are evaluated in `f64` precision.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64
+}
+
+
+impl DoubleDouble {
+    /// Create a new value from its two parts (the parts have to be normalised already)
+    pub const fn new(hi: f64, lo: f64) -> Self {
+        Self { hi, lo }
+    }
+
+    /// Create a new value from an `f64`
+    pub const fn from_f64(value: f64) -> Self {
+        Self { hi: value, lo: 0.0 }
+    }
+
+    /// The value rounded to the nearest `f64`
+    pub fn to_f64(&self) -> f64 { self.hi + self.lo }
+
+    pub fn is_finite(&self) -> bool { self.hi.is_finite() }
+
+    pub fn abs(&self) -> Self { if self.hi < 0.0 { -*self } else { *self } }
+    pub fn min(&self, other: Self) -> Self { if other < *self { other } else { *self } }
+    pub fn max(&self, other: Self) -> Self { if other > *self { other } else { *self } }
+    pub fn ln(&self) -> Self { Self::from_f64(self.to_f64().ln()) }
+
+    /// The square root, refined with one Newton iteration
+    pub fn sqrt(&self) -> Self {
+        if self.hi <= 0.0 || !self.is_finite() {
+            return Self::from_f64(self.hi.sqrt());
+        }
+        let x: f64 = self.hi.sqrt();
+        let (square, error) = two_product(x, x);
+        let residual: Self = *self - Self::new(square, error);
+        let (hi, lo) = quick_two_sum(x, residual.hi / (2.0 * x));
+        Self::new(hi, lo)
+    }
+
+    /// Raise the value to an integer power, by repeated squaring
+    pub fn powi(&self, n: i32) -> Self {
+        let mut result: Self = Self::from_f64(1.0);
+        let mut base: Self = *self;
+        let mut exponent: u32 = n.unsigned_abs();
+        while exponent > 0 {
+            if exponent & 1 == 1 { result = result * base; }
+            base = base * base;
+            exponent >>= 1;
+        }
+        if n < 0 { Self::from_f64(1.0) / result } else { result }
+    }
+
+    /// Raise the value to a power.
+    /// Integer powers are exact to ~106 bits, other powers are evaluated in `f64` precision.
+    pub fn powf(&self, n: Self) -> Self {
+        if n.lo == 0.0 && n.hi.fract() == 0.0 && n.hi.abs() <= i32::MAX as f64 {
+            self.powi(n.hi as i32)
+        } else {
+            Self::from_f64(self.to_f64().powf(n.to_f64()))
+        }
+    }
+}
+
+
+/// The exact sum of two `f64` values: `a + b = s + e`
+pub fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s: f64 = a + b;
+    let b_virtual: f64 = s - a;
+    let error: f64 = (a - (s - b_virtual)) + (b - b_virtual);
+    (s, error)
+}
+
+
+/// The exact sum of two `f64` values, if `|a| >= |b|`
+pub fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s: f64 = a + b;
+    (s, b - (s - a))
+}
+
+
+/// The exact product of two `f64` values: `a * b = p + e`
+pub fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let p: f64 = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self { Self::from_f64(value) }
+}
+
+
+impl From<DoubleDouble> for f64 {
+    fn from(value: DoubleDouble) -> Self { value.to_f64() }
+}
+
+
+impl Add for DoubleDouble {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        let (s, e) = two_sum(self.hi, rhs.hi);
+        if !s.is_finite() { return Self::from_f64(s); }
+        let (t, f) = two_sum(self.lo, rhs.lo);
+        let (s, e) = quick_two_sum(s, e + t);
+        let (hi, lo) = quick_two_sum(s, e + f);
+        Self::new(hi, lo)
+    }
+}
+
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (p, e) = two_product(self.hi, rhs.hi);
+        if !p.is_finite() { return Self::from_f64(p); }
+        let (hi, lo) = quick_two_sum(p, e + (self.hi * rhs.lo + self.lo * rhs.hi));
+        Self::new(hi, lo)
+    }
+}
+
+
+impl Div for DoubleDouble {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        // Long division, one f64 digit at a time
+        let q1: f64 = self.hi / rhs.hi;
+        if !q1.is_finite() || q1 == 0.0 { return Self::from_f64(q1); }
+        let remainder: Self = self - rhs * Self::from_f64(q1);
+        let q2: f64 = remainder.hi / rhs.hi;
+        let remainder: Self = remainder - rhs * Self::from_f64(q2);
+        let q3: f64 = remainder.hi / rhs.hi;
+
+        let (hi, lo) = quick_two_sum(q1, q2);
+        Self::new(hi, lo) + Self::from_f64(q3)
+    }
+}
+
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self::new(-self.hi, -self.lo)
+    }
+}
+
+
+impl Display for DoubleDouble {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.to_f64(), f)
+    }
+}
+
+
+impl LowerExp for DoubleDouble {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        LowerExp::fmt(&self.to_f64(), f)
+    }
+}
+
+
+impl UpperExp for DoubleDouble {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        UpperExp::fmt(&self.to_f64(), f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn small_terms_are_kept() {
+        let big: DoubleDouble = DoubleDouble::from(1e16);
+        assert_eq!((big + DoubleDouble::from(1.0) - big).to_f64(), 1.0);
+        assert_eq!(1e16 + 1.0 - 1e16, 0.0);
+
+        let root: DoubleDouble = DoubleDouble::from(2.0).sqrt();
+        assert!((root * root - DoubleDouble::from(2.0)).abs().to_f64() < 1e-30);
+    }
+
+    #[test]
+    fn values_round_trip_through_f64() {
+        for value in [0.0, 0.1, -3.5, 1e300, f64::MIN_POSITIVE, std::f64::consts::PI] {
+            assert_eq!(DoubleDouble::from(value).to_f64(), value);
+            assert_eq!(f64::from(DoubleDouble::from_f64(value)), value);
+        }
+    }
+}
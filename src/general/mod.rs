@@ -1,15 +1,25 @@
 pub mod constants;
 pub mod degree;
+pub mod float;
 pub mod point;
 pub mod point_body;
+pub mod quantity;
 pub mod radiation;
+pub mod random;
 pub mod scalar;
+pub mod summation;
+pub mod units;
 pub mod vector;
 
 pub use constants::*;
 pub use degree::*;
+pub use float::*;
 pub use point::*;
 pub use point_body::*;
+pub use quantity::*;
 pub use radiation::*;
+pub use random::*;
 pub use scalar::*;
+pub use summation::*;
+pub use units::*;
 pub use vector::*;
\ No newline at end of file
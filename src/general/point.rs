@@ -20,9 +20,7 @@ impl Point {
         let y: Scalar = other.y - self.y;
         let z: Scalar = other.z - self.z;
 
-        Scalar::new(
-            (x.value.powi(2) + y.value.powi(2) + z.value.powi(2)).sqrt()
-        )
+        (x * x + y * y + z * z).sqrt()
     }
 }
 
@@ -57,17 +55,17 @@ impl std::fmt::Display for Point {
 
 #[macro_export]
 macro_rules! point {
-    () => { ORIGO };
-    (O) => { ORIGO };
-    (0) => { ORIGO };
+    () => { $crate::general::ORIGO };
+    (O) => { $crate::general::ORIGO };
+    (0) => { $crate::general::ORIGO };
     ($x: expr, $y: expr, $z: expr) => {
-        Point::new(
-            scalar!($x), scalar!($y), scalar!($z)
+        $crate::general::Point::new(
+            $crate::scalar!($x), $crate::scalar!($y), $crate::scalar!($z)
         )
     };
     (($x: expr, $y: expr, $z: expr)) => {
-        Point::new(
-            scalar!($x), scalar!($y), scalar!($z)
+        $crate::general::Point::new(
+            $crate::scalar!($x), $crate::scalar!($y), $crate::scalar!($z)
         )
     }
 }
\ No newline at end of file
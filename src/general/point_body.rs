@@ -5,74 +5,124 @@ use crate::*;
 /// # Point Body
 /// A body, with no volume, surface, ...
 /// ## Attributes
-/// `mass: Scalar`\
+/// `mass: Mass`\
 /// `velocity: Vector`\
 /// `coordinates: Point`\
-/// `charge: Scalar`
+/// `charge: Scalar`\
+/// `test_particle: bool` - Test particles feel gravity, but do not attract other bodies\
+/// `position_compensation: Vector`, `velocity_compensation: Vector` -
+/// The rounding errors carried between the compensated updates of `advance` and `accelerate`
 pub struct PointBody {
-    pub mass: Scalar,
+    pub mass: Mass,
     pub velocity: Vector,
     pub coordinates: Point,
-    pub charge: Scalar
+    pub charge: Scalar,
+    pub test_particle: bool,
+    pub position_compensation: Vector,
+    pub velocity_compensation: Vector
 }
 
 
 impl PointBody {
     /// Create a new `PointBody` object
-    pub fn new(mass: Scalar,
+    pub fn new(mass: Mass,
                velocity: Vector,
                coordinates: Point, 
                charge: Scalar) -> Self {
-        Self { mass, velocity, coordinates, charge }
+        Self {
+            mass,
+            velocity,
+            coordinates,
+            charge,
+            test_particle: false,
+            position_compensation: NULL_VECTOR,
+            velocity_compensation: NULL_VECTOR
+        }
     }
     
     /// Calculate the momentum of the body
     pub fn momentum(&self) -> Vector {
-        self.velocity.clone() * self.mass
+        self.velocity * self.mass.value
     }
     
+    /// Get the mass of the body
+    pub fn get_mass(&self) -> Mass { self.mass }
+
+    /// Calculate the speed of the body
+    pub fn speed(&self) -> Velocity { Velocity::new(self.velocity.magnitude()) }
+    
     /// Calculate the kinetic energy of the body
-    pub fn kinetic_energy(&self) -> Scalar {
-       scalar!(0.5) * self.mass * self.velocity.magnitude().pow(scalar!(2))
+    pub fn kinetic_energy(&self) -> Energy {
+       Energy::new(scalar!(0.5) * self.mass.value * self.velocity.magnitude().pow(scalar!(2)))
     }
     
     /// Calculate the potential energy of the body
-    pub fn potential_energy(&self, height: Scalar, gravity: Vector) -> Scalar {
-        self.mass * height * gravity.magnitude()
+    pub fn potential_energy(&self, height: Length, gravity: Acceleration) -> Energy {
+        self.mass * gravity * height
     }
     
-    /// Calculate the acceleration of the body based on the force applied to it
+    /// Calculate the acceleration of the body based on the force (in newtons) applied to it
     pub fn acceleration(&self, force: Vector) -> Vector {
-        force / self.mass
+        force / self.mass.value
     }
     
-    /// Calculate the force applied based on the body's acceleration
+    /// Calculate the force (in newtons) applied based on the body's acceleration
     pub fn force(&self, acceleration: Vector) -> Vector {
-        acceleration * self.mass
+        acceleration * self.mass.value
+    }
+    
+    /// Calculate the magnitude of the acceleration caused by a force of the given magnitude
+    pub fn acceleration_magnitude(&self, force: Force) -> Acceleration {
+        force / self.mass
+    }
+    
+    /// Calculate the magnitude of the force needed for an acceleration of the given magnitude
+    pub fn force_magnitude(&self, acceleration: Acceleration) -> Force {
+        self.mass * acceleration
     }
     
     /// Calculate the distance between the body and a given point
-    pub fn distance(&self, point: &Point) -> Scalar {
-        self.coordinates.distance(point)
+    pub fn distance(&self, point: &Point) -> Length {
+        Length::new(self.coordinates.distance(point))
     }
     
     /// Calculate the gravitational force between two bodies
     pub fn gravitational_force(&self, other: &Self) -> Vector {
         // Get the distance between the two bodies
-        let distance: Scalar = self.distance(&other.coordinates);
+        let distance: Scalar = self.coordinates.distance(&other.coordinates);
         
         // Calculate the gravitational force between the two bodies
-        let force: Scalar = constants::G * self.mass * other.mass / distance.powi(2);
+        let force: Scalar = constants::G * self.mass.value * other.mass.value / distance.powi(2);
         
         // Calculate the vector from the magnitude and the direction
         Vector::from_magnitude(force, &other.coordinates, &self.coordinates)
     }
     
-    /// Advance the body by `dt` seconds
-    pub fn advance(&mut self, dt: Scalar) {
-        self.coordinates.x += self.velocity.point.x * dt;
-        self.coordinates.y += self.velocity.point.y * dt;
-        self.coordinates.z += self.velocity.point.z * dt;
+    /// Calculate the acceleration caused by the gravity of another body.
+    /// Unlike the force, this does not depend on the mass of the body, so it works for massless bodies too.
+    pub fn gravitational_acceleration(&self, other: &Self) -> Vector {
+        let distance: Scalar = self.coordinates.distance(&other.coordinates);
+        let acceleration: Scalar = constants::G * other.mass.value / distance.powi(2);
+        Vector::from_magnitude(acceleration, &other.coordinates, &self.coordinates)
+    }
+    
+    /// Advance the body by `dt` seconds.
+    /// The position is updated with Kahan summation, so the small steps do not get lost in rounding.
+    pub fn advance(&mut self, dt: Time) {
+        let step: Vector = self.velocity * dt.value;
+        let compensation: &mut Point = &mut self.position_compensation.point;
+        kahan_add(&mut self.coordinates.x, &mut compensation.x, step.point.x);
+        kahan_add(&mut self.coordinates.y, &mut compensation.y, step.point.y);
+        kahan_add(&mut self.coordinates.z, &mut compensation.z, step.point.z);
+    }
+
+    /// Accelerate the body by `acceleration` for `dt` seconds, with Kahan summation
+    pub fn accelerate(&mut self, acceleration: Vector, dt: Time) {
+        let step: Vector = acceleration * dt.value;
+        let compensation: &mut Point = &mut self.velocity_compensation.point;
+        kahan_add(&mut self.velocity.point.x, &mut compensation.x, step.point.x);
+        kahan_add(&mut self.velocity.point.y, &mut compensation.y, step.point.y);
+        kahan_add(&mut self.velocity.point.z, &mut compensation.z, step.point.z);
     }
     
     pub fn __repr__(&self) -> String { format!("{self:?}") }
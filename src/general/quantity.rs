@@ -0,0 +1,181 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Add, AddAssign, Sub, SubAssign, Mul, Div, Neg};
+use crate::scalar::Scalar;
+
+
+/// Define a quantity with a physical dimension, wrapping a `Scalar` (in SI units).\
+/// Quantities of the same dimension can be added, subtracted and compared,
+/// they can be scaled by a dimensionless `Scalar`, and the ratio of two of them is a `Scalar`.
+macro_rules! quantity {
+    ($(#[$doc: meta])* $name: ident, $unit: literal) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, PartialEq, PartialOrd)]
+        pub struct $name {
+            pub value: Scalar
+        }
+
+        impl $name {
+            pub const fn new(value: Scalar) -> Self {
+                Self { value }
+            }
+
+            /// The value in SI units, rounded to an `f64`
+            pub fn to_f64(&self) -> f64 { self.value.to_f64() }
+            pub fn abs(&self) -> Self { Self::new(self.value.abs()) }
+            pub fn min(&self, other: Self) -> Self { Self::new(self.value.min(other.value)) }
+            pub fn max(&self, other: Self) -> Self { Self::new(self.value.max(other.value)) }
+        }
+
+        impl<P> From<P> for $name where f64: From<P> {
+            fn from(value: P) -> Self {
+                Self::new(Scalar::new(f64::from(value)))
+            }
+        }
+
+        impl From<Scalar> for $name {
+            fn from(value: Scalar) -> Self { Self::new(value) }
+        }
+
+        impl From<$name> for Scalar {
+            fn from(value: $name) -> Self { value.value }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self::Output { Self::new(self.value + rhs.value) }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) { self.value += rhs.value }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self::Output { Self::new(self.value - rhs.value) }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) { self.value -= rhs.value }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self::Output { Self::new(-self.value) }
+        }
+
+        impl Mul<Scalar> for $name {
+            type Output = Self;
+            fn mul(self, rhs: Scalar) -> Self::Output { Self::new(self.value * rhs) }
+        }
+
+        impl Div<Scalar> for $name {
+            type Output = Self;
+            fn div(self, rhs: Scalar) -> Self::Output { Self::new(self.value / rhs) }
+        }
+
+        impl Div for $name {
+            type Output = Scalar;
+            fn div(self, rhs: Self) -> Self::Output { self.value / rhs.value }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{} {}", self.value, $unit)
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{self}")
+            }
+        }
+    };
+}
+
+
+/// Define the product of two quantities: `$a * $b = $c`, together with the matching divisions
+macro_rules! product {
+    ($a: ident * $b: ident = $c: ident) => {
+        impl Mul<$b> for $a {
+            type Output = $c;
+            fn mul(self, rhs: $b) -> Self::Output { $c::new(self.value * rhs.value) }
+        }
+
+        impl Mul<$a> for $b {
+            type Output = $c;
+            fn mul(self, rhs: $a) -> Self::Output { $c::new(self.value * rhs.value) }
+        }
+
+        impl Div<$a> for $c {
+            type Output = $b;
+            fn div(self, rhs: $a) -> Self::Output { $b::new(self.value / rhs.value) }
+        }
+
+        impl Div<$b> for $c {
+            type Output = $a;
+            fn div(self, rhs: $b) -> Self::Output { $a::new(self.value / rhs.value) }
+        }
+    };
+}
+
+
+quantity!(
+    /// # Length
+    /// A distance, in metres
+    Length, "m"
+);
+
+quantity!(
+    /// # Mass
+    /// A mass, in kilograms
+    Mass, "kg"
+);
+
+quantity!(
+    /// # Time
+    /// A duration, in seconds
+    Time, "s"
+);
+
+quantity!(
+    /// # Velocity
+    /// The magnitude of a velocity, in metres per second
+    Velocity, "m/s"
+);
+
+quantity!(
+    /// # Acceleration
+    /// The magnitude of an acceleration, in metres per second squared
+    Acceleration, "m/s^2"
+);
+
+quantity!(
+    /// # Force
+    /// The magnitude of a force, in newtons
+    Force, "N"
+);
+
+quantity!(
+    /// # Energy
+    /// An energy, in joules
+    Energy, "J"
+);
+
+quantity!(
+    /// # Power
+    /// A power (e.g. the luminosity of a star), in watts
+    Power, "W"
+);
+
+quantity!(
+    /// # Temperature
+    /// A thermodynamic temperature, in kelvins
+    Temperature, "K"
+);
+
+
+product!(Velocity * Time = Length);
+product!(Acceleration * Time = Velocity);
+product!(Mass * Acceleration = Force);
+product!(Force * Length = Energy);
+product!(Power * Time = Energy);
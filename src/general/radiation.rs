@@ -40,7 +40,7 @@ impl Radiation {
     }
     
     pub fn color_from_wavelength(wavelength: Scalar) -> [f64; 4] {
-        let wavelength: f64 = wavelength.value;
+        let wavelength: f64 = wavelength.to_f64();
         match wavelength {
             ..380e-9 => {
                 [1.0, 0.0, 1.0, 0.0]
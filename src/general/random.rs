@@ -0,0 +1,68 @@
+/// # Random
+/// A seedable pseudo-random number generator (xoshiro256**).\
+/// The same seed always produces the same sequence, so runs using it are reproducible.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: [u64; 4]
+}
+
+
+impl Random {
+    /// Create a new generator from a seed
+    pub fn new(seed: u64) -> Self {
+        // Expand the seed into the state with SplitMix64
+        let mut x: u64 = seed;
+        let mut state: [u64; 4] = [0; 4];
+        for value in state.iter_mut() {
+            x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z: u64 = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            *value = z ^ (z >> 31);
+        }
+        Self { state }
+    }
+
+    /// Generate the next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        let result: u64 = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t: u64 = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+
+    /// Generate a uniformly distributed number in the `[0; 1)` range
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Generate a uniformly distributed number in the `[min; max)` range
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.uniform()
+    }
+
+    /// Generate a normally distributed number with a mean of `0` and a standard deviation of `1`
+    /// (Box-Muller transform)
+    pub fn gaussian(&mut self) -> f64 {
+        let u: f64 = 1.0 - self.uniform();  // (0; 1], so the logarithm is finite
+        let v: f64 = self.uniform();
+        (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+    }
+
+    /// Generate a normally distributed number with the given mean and standard deviation
+    pub fn normal(&mut self, mean: f64, standard_deviation: f64) -> f64 {
+        mean + standard_deviation * self.gaussian()
+    }
+
+    /// Generate a Rayleigh distributed number with the given scale
+    pub fn rayleigh(&mut self, scale: f64) -> f64 {
+        scale * (-2.0 * (1.0 - self.uniform()).ln()).sqrt()
+    }
+}
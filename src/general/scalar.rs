@@ -1,21 +1,72 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Div, DivAssign, Neg};
+use crate::float::*;
 
 
 /// # Scalar
-/// A value with only magnitude and no direction
+/// A value with only magnitude and no direction.\
+/// The type of `value` is `Float`, which is selected by the `double-double` feature.
 #[derive(Clone, Copy, PartialEq, PartialOrd)]
 pub struct Scalar {
-    pub value: f64
+    pub value: Float
 }
 
 impl Scalar {
+    #[cfg(not(feature = "double-double"))]
     pub const fn new(value: f64) -> Self {
         Self { value }
     }
+
+    #[cfg(feature = "double-double")]
+    pub const fn new(value: f64) -> Self {
+        Self { value: DoubleDouble::from_f64(value) }
+    }
+
+    /// The value rounded to an `f64`
+    #[cfg(not(feature = "double-double"))]
+    pub fn to_f64(&self) -> f64 { self.value }
+
+    /// The value rounded to an `f64`
+    #[cfg(feature = "double-double")]
+    pub fn to_f64(&self) -> f64 { self.value.to_f64() }
     
-    pub fn pow(&self, n: Self) -> Self { Self::new(self.value.powf(n.value)) }
-    pub fn powi(&self, n: i32) -> Self { Self::new(self.value.powi(n)) }
+    /// Format the value so that parsing it back (with `parse_quantity`) gives exactly the same value.
+    /// Unlike `Display`, this never rounds.
+    #[cfg(not(feature = "double-double"))]
+    pub fn format_exact(&self) -> String { format_f64(self.value) }
+
+    /// Format the value so that parsing it back (with `parse_quantity`) gives exactly the same value.
+    /// Unlike `Display`, this never rounds: the two parts of the double-double are written as a sum.
+    #[cfg(feature = "double-double")]
+    pub fn format_exact(&self) -> String {
+        let (hi, lo): (f64, f64) = (self.value.hi, self.value.lo);
+        if lo == 0.0 {
+            format_f64(hi)
+        } else if lo > 0.0 {
+            format!("{} + {}", format_f64(hi), format_f64(lo))
+        } else {
+            format!("{} - {}", format_f64(hi), format_f64(-lo))
+        }
+    }
+    
+    pub fn pow(&self, n: Self) -> Self { Self { value: self.value.powf(n.value) } }
+    pub fn powi(&self, n: i32) -> Self { Self { value: self.value.powi(n) } }
+    pub fn sqrt(&self) -> Self { Self { value: self.value.sqrt() } }
+    pub fn ln(&self) -> Self { Self { value: self.value.ln() } }
+    pub fn abs(&self) -> Self { Self { value: self.value.abs() } }
+    pub fn min(&self, other: Self) -> Self { Self { value: self.value.min(other.value) } }
+    pub fn max(&self, other: Self) -> Self { Self { value: self.value.max(other.value) } }
+}
+
+
+/// The shortest representation of an `f64` that reads back as the same value,
+/// using the scientific notation for very large and very small values
+fn format_f64(value: f64) -> String {
+    if value == 0.0 || (1e-3..1e7).contains(&value.abs()) {
+        format!("{value}")
+    } else {
+        format!("{value:e}")
+    }
 }
 
 
@@ -96,15 +147,15 @@ impl DivAssign for Scalar {
 impl Neg for Scalar {
     type Output = Self;
     fn neg(self) -> Self::Output {
-        Self::new(
-            -self.value
-        )
+        Self {
+            value: -self.value
+        }
     }
 }
 
 impl Display for Scalar {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.value >= 1e4 {
+        if self.to_f64() >= 1e4 {
             write!(f, "{:e}", self.value)
         } else {
             write!(f, "{}", self.value)
@@ -121,6 +172,6 @@ impl Debug for Scalar {
 
 #[macro_export]
 macro_rules! scalar {
-    () => { Scalar::new(0.0) };
-    ($x: expr) => { Scalar::from($x) };
+    () => { $crate::general::Scalar::new(0.0) };
+    ($x: expr) => { $crate::general::Scalar::from($x) };
 }
\ No newline at end of file
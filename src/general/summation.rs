@@ -0,0 +1,105 @@
+use crate::*;
+
+
+/// # Summation
+/// Compensated (Neumaier) summation of scalars.
+/// The rounding error of every addition is collected in a separate term,
+/// so the error of the total does not grow with the number of terms.
+/// ## Attributes
+/// `sum: Scalar` - The naive sum\
+/// `compensation: Scalar` - The accumulated rounding error of `sum`
+#[derive(Debug, Clone, Copy)]
+pub struct Summation {
+    pub sum: Scalar,
+    pub compensation: Scalar
+}
+
+
+impl Summation {
+    pub const fn new() -> Self {
+        Self { sum: ZERO, compensation: ZERO }
+    }
+
+    /// Add a value to the sum
+    pub fn add(&mut self, value: Scalar) {
+        let total: Scalar = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - total) + value;
+        } else {
+            self.compensation += (value - total) + self.sum;
+        }
+        self.sum = total;
+    }
+
+    /// The compensated total
+    pub fn total(&self) -> Scalar { self.sum + self.compensation }
+}
+
+
+impl Default for Summation {
+    fn default() -> Self { Self::new() }
+}
+
+
+/// # Vector Summation
+/// Compensated (Neumaier) summation of vectors, component by component
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VectorSummation {
+    pub x: Summation,
+    pub y: Summation,
+    pub z: Summation
+}
+
+
+impl VectorSummation {
+    pub const fn new() -> Self {
+        Self { x: Summation::new(), y: Summation::new(), z: Summation::new() }
+    }
+
+    /// Add a vector to the sum
+    pub fn add(&mut self, vector: Vector) {
+        self.x.add(vector.point.x);
+        self.y.add(vector.point.y);
+        self.z.add(vector.point.z);
+    }
+
+    /// The compensated total
+    pub fn total(&self) -> Vector {
+        Vector::new(self.x.total(), self.y.total(), self.z.total())
+    }
+}
+
+
+/// Add `value` to a running total with Kahan summation.\
+/// `compensation` carries the lost low-order bits from one call to the next,
+/// so it has to be kept together with `total` (e.g. between time steps).
+pub fn kahan_add(total: &mut Scalar, compensation: &mut Scalar, value: Scalar) {
+    let corrected: Scalar = value - *compensation;
+    let sum: Scalar = *total + corrected;
+    *compensation = (sum - *total) - corrected;
+    *total = sum;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn neumaier_keeps_the_small_terms() {
+        let values: [f64; 4] = [1.0, 1e100, 1.0, -1e100];
+        assert_eq!(values.iter().sum::<f64>(), 0.0);
+
+        let mut sum: Summation = Summation::new();
+        for value in values.iter() {
+            sum.add(scalar!(*value));
+        }
+        assert_eq!(sum.total(), scalar!(2));
+
+        let mut vectors: VectorSummation = VectorSummation::new();
+        for value in values.iter() {
+            vectors.add(vector!(*value, -*value, 0));
+        }
+        assert_eq!(vectors.total(), vector!(2, -2, 0));
+    }
+}
@@ -0,0 +1,684 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Mul, Div};
+use crate::*;
+
+
+/// # Dimension
+/// The physical dimension of a quantity, as the exponents of the SI base units.
+/// ## Attributes
+/// `mass: i8` - kg\
+/// `length: i8` - m\
+/// `time: i8` - s\
+/// `current: i8` - A\
+/// `temperature: i8` - K
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dimension {
+    pub mass: i8,
+    pub length: i8,
+    pub time: i8,
+    pub current: i8,
+    pub temperature: i8
+}
+
+
+impl Dimension {
+    pub const fn new(mass: i8, length: i8, time: i8, current: i8, temperature: i8) -> Self {
+        Self { mass, length, time, current, temperature }
+    }
+
+    pub const DIMENSIONLESS: Self = Self::new(0, 0, 0, 0, 0);
+    pub const MASS: Self = Self::new(1, 0, 0, 0, 0);
+    pub const LENGTH: Self = Self::new(0, 1, 0, 0, 0);
+    pub const TIME: Self = Self::new(0, 0, 1, 0, 0);
+    pub const CURRENT: Self = Self::new(0, 0, 0, 1, 0);
+    pub const TEMPERATURE: Self = Self::new(0, 0, 0, 0, 1);
+    pub const VELOCITY: Self = Self::new(0, 1, -1, 0, 0);
+    pub const ACCELERATION: Self = Self::new(0, 1, -2, 0, 0);
+    pub const FORCE: Self = Self::new(1, 1, -2, 0, 0);
+    pub const ENERGY: Self = Self::new(1, 2, -2, 0, 0);
+    pub const POWER: Self = Self::new(1, 2, -3, 0, 0);
+    pub const PRESSURE: Self = Self::new(1, -1, -2, 0, 0);
+    pub const FREQUENCY: Self = Self::new(0, 0, -1, 0, 0);
+    pub const CHARGE: Self = Self::new(0, 0, 1, 1, 0);
+
+    /// Raise the dimension to an integer power
+    pub fn powi(&self, n: i8) -> Self {
+        self.checked_powi(n).expect("the exponent of the dimension is out of range")
+    }
+
+    /// Raise the dimension to an integer power, or `None` if an exponent does not fit in an `i8`
+    pub fn checked_powi(&self, n: i8) -> Option<Self> {
+        Some(Self::new(
+            self.mass.checked_mul(n)?,
+            self.length.checked_mul(n)?,
+            self.time.checked_mul(n)?,
+            self.current.checked_mul(n)?,
+            self.temperature.checked_mul(n)?
+        ))
+    }
+
+    /// Multiply two dimensions, or `None` if an exponent does not fit in an `i8`
+    pub fn checked_mul(&self, rhs: Self) -> Option<Self> {
+        Some(Self::new(
+            self.mass.checked_add(rhs.mass)?,
+            self.length.checked_add(rhs.length)?,
+            self.time.checked_add(rhs.time)?,
+            self.current.checked_add(rhs.current)?,
+            self.temperature.checked_add(rhs.temperature)?
+        ))
+    }
+
+    /// Divide two dimensions, or `None` if an exponent does not fit in an `i8`
+    pub fn checked_div(&self, rhs: Self) -> Option<Self> {
+        self.checked_mul(rhs.checked_powi(-1)?)
+    }
+
+    /// The name of the dimension, if it has one
+    pub fn name(&self) -> Option<&'static str> {
+        let names: [(Dimension, &str); 14] = [
+            (Self::DIMENSIONLESS, "dimensionless"),
+            (Self::MASS, "mass"),
+            (Self::LENGTH, "length"),
+            (Self::TIME, "time"),
+            (Self::CURRENT, "current"),
+            (Self::TEMPERATURE, "temperature"),
+            (Self::VELOCITY, "velocity"),
+            (Self::ACCELERATION, "acceleration"),
+            (Self::FORCE, "force"),
+            (Self::ENERGY, "energy"),
+            (Self::POWER, "power"),
+            (Self::PRESSURE, "pressure"),
+            (Self::FREQUENCY, "frequency"),
+            (Self::CHARGE, "charge")
+        ];
+        names.iter().find(|(dimension, _)| dimension == self).map(|(_, name)| *name)
+    }
+}
+
+
+impl Mul for Dimension {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(rhs).expect("the exponent of the dimension is out of range")
+    }
+}
+
+
+impl Div for Dimension {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs).expect("the exponent of the dimension is out of range")
+    }
+}
+
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let units: [(i8, &str); 5] = [
+            (self.mass, "kg"),
+            (self.length, "m"),
+            (self.time, "s"),
+            (self.current, "A"),
+            (self.temperature, "K")
+        ];
+        let parts: Vec<String> = units.iter()
+            .filter(|(exponent, _)| *exponent != 0)
+            .map(|(exponent, unit)| if *exponent == 1 { unit.to_string() } else { format!("{unit}^{exponent}") })
+            .collect();
+
+        match self.name() {
+            Some(name) if parts.is_empty() => write!(f, "{name}"),
+            Some(name) => write!(f, "{name} ({})", parts.join(" ")),
+            None => write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+
+/// # Unit Error
+/// An error in a quantity string.
+/// ## Attributes
+/// `input: String` - The parsed string\
+/// `position: usize` - The index of the character where the error was found\
+/// `message: String` - The description of the error
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitError {
+    pub input: String,
+    pub position: usize,
+    pub message: String
+}
+
+
+impl UnitError {
+    fn new(input: &str, position: usize, message: impl ToString) -> Self {
+        Self { input: input.to_string(), position, message: message.to_string() }
+    }
+}
+
+
+impl Display for UnitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} (at column {})", self.message, self.position + 1)?;
+        writeln!(f, "    {}", self.input)?;
+        write!(f, "    {}^", " ".repeat(self.position))
+    }
+}
+
+
+impl std::error::Error for UnitError {}
+
+
+/// # Measurement
+/// A parsed quantity: its value in SI units and its dimension
+/// ## Attributes
+/// `value: Scalar` - The value, converted to SI units\
+/// `dimension: Dimension` - The dimension of the value\
+/// `input: String`, `position: usize` - Where the quantity was parsed from (for error messages)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub value: Scalar,
+    pub dimension: Dimension,
+    input: String,
+    position: usize
+}
+
+
+impl Measurement {
+    /// Return the value, if the quantity has the expected dimension
+    pub fn expect(&self, dimension: Dimension) -> Result<Scalar, UnitError> {
+        // A bare zero fits any dimension
+        if self.dimension == dimension || (self.dimension == Dimension::DIMENSIONLESS && self.value == ZERO) {
+            Ok(self.value)
+        } else {
+            Err(UnitError::new(
+                &self.input,
+                self.position,
+                format!("expected a quantity of {dimension}, found {}", self.dimension)
+            ))
+        }
+    }
+
+    pub fn length(&self) -> Result<Length, UnitError> { self.expect(Dimension::LENGTH).map(Length::new) }
+    pub fn mass(&self) -> Result<Mass, UnitError> { self.expect(Dimension::MASS).map(Mass::new) }
+    pub fn time(&self) -> Result<Time, UnitError> { self.expect(Dimension::TIME).map(Time::new) }
+    pub fn velocity(&self) -> Result<Velocity, UnitError> { self.expect(Dimension::VELOCITY).map(Velocity::new) }
+    pub fn acceleration(&self) -> Result<Acceleration, UnitError> { self.expect(Dimension::ACCELERATION).map(Acceleration::new) }
+    pub fn force(&self) -> Result<Force, UnitError> { self.expect(Dimension::FORCE).map(Force::new) }
+    pub fn energy(&self) -> Result<Energy, UnitError> { self.expect(Dimension::ENERGY).map(Energy::new) }
+}
+
+
+impl std::str::FromStr for Measurement {
+    type Err = UnitError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> { parse_quantity(s) }
+}
+
+
+/// # Vector Measurement
+/// A parsed vector: its value in SI units and its dimension
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorMeasurement {
+    pub value: Vector,
+    pub dimension: Dimension,
+    input: String
+}
+
+
+impl VectorMeasurement {
+    /// Return the value, if the vector has the expected dimension
+    pub fn expect(&self, dimension: Dimension) -> Result<Vector, UnitError> {
+        if self.dimension == dimension || (self.dimension == Dimension::DIMENSIONLESS && self.value == NULL_VECTOR) {
+            Ok(self.value)
+        } else {
+            Err(UnitError::new(
+                &self.input,
+                0,
+                format!("expected a vector of {dimension}, found {}", self.dimension)
+            ))
+        }
+    }
+}
+
+
+/// Parse a quantity, such as `696 340 km`, `1 SM`, `47.4 km/s`, `3.3 * 10^22 kg` or `1.5e11 m`.
+/// ## Syntax
+/// - Numbers can have a sign, a fraction and an exponent (`-1.5e-3`),
+///   thousands can be separated with spaces (`58 000 000`),
+///   they can be multiplied by powers (`3.3 * 10^22`, `5 × 10^3`), and added together (`1.5e11 + 2.5e-6`)
+/// - Units can have SI prefixes (`km`, `MJ`, `Gyr`), powers (`m^3`, `s^-2`, `m²`),
+///   and can be combined with `*`, `·`, `/` or spaces (`kg m/s^2`)
+/// - The astronomical units: `AU`, `ly`, `pc`, `SM` (solar mass), `EM` (Earth mass), `RS` (solar radius),
+///   `min`, `h`, `d` / `day`, `week`, `month`, `yr` / `year`
+pub fn parse_quantity(input: &str) -> Result<Measurement, UnitError> {
+    let mut parser: Parser = Parser::new(input);
+    let measurement: Measurement = parser.quantity()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(measurement),
+        Some(character) => Err(parser.error(format!("unexpected '{character}'")))
+    }
+}
+
+
+/// Parse a vector, such as `(0, 47.4 km/s, 0)` or `(58 000 000 km, 0, 0)`.
+/// A single `0` is the null vector.
+/// Every non-zero component has to have the same dimension.
+pub fn parse_vector(input: &str) -> Result<VectorMeasurement, UnitError> {
+    let mut parser: Parser = Parser::new(input);
+    parser.skip_whitespace();
+
+    if parser.peek() != Some('(') {
+        let zero: Measurement = parser.quantity()?;
+        parser.skip_whitespace();
+        if let Some(character) = parser.peek() {
+            return Err(parser.error(format!("unexpected '{character}'")));
+        }
+        if zero.value != ZERO {
+            return Err(UnitError::new(input, zero.position, "expected a vector in the form (x, y, z)"));
+        }
+        return Ok(VectorMeasurement { value: NULL_VECTOR, dimension: zero.dimension, input: input.to_string() });
+    }
+    parser.next();
+
+    let mut components: Vec<Measurement> = Vec::with_capacity(3);
+    loop {
+        components.push(parser.quantity()?);
+        parser.skip_whitespace();
+        match parser.next() {
+            Some(',') if components.len() < 3 => continue,
+            Some(')') if components.len() == 3 => break,
+            Some(')') => return Err(parser.error_at(parser.index - 1, "expected 3 components")),
+            Some(character) => return Err(parser.error_at(parser.index - 1, format!("unexpected '{character}'"))),
+            None => return Err(parser.error("missing ')'"))
+        }
+    }
+    parser.skip_whitespace();
+    if let Some(character) = parser.peek() {
+        return Err(parser.error(format!("unexpected '{character}'")));
+    }
+
+    // Every component has to have the dimension of the first non-zero one
+    let dimension: Dimension = components.iter()
+        .find(|component| component.value != ZERO || component.dimension != Dimension::DIMENSIONLESS)
+        .map(|component| component.dimension)
+        .unwrap_or(Dimension::DIMENSIONLESS);
+    for component in components.iter() {
+        component.expect(dimension)?;
+    }
+
+    Ok(VectorMeasurement {
+        value: Vector::new(components[0].value, components[1].value, components[2].value),
+        dimension,
+        input: input.to_string()
+    })
+}
+
+
+/* ----- UNITS ----- */
+/// The SI prefixes and their exponents (`da` is first, so it is tried before `d`)
+const PREFIXES: [(&str, i32); 21] = [
+    ("da", 1), ("Q", 30), ("R", 27), ("Y", 24), ("Z", 21), ("E", 18), ("P", 15), ("T", 12), ("G", 9), ("M", 6),
+    ("k", 3), ("h", 2), ("d", -1), ("c", -2), ("m", -3), ("µ", -6), ("u", -6), ("n", -9), ("p", -12),
+    ("f", -15), ("a", -18)
+];
+
+
+/// A unit: its symbol, its value in SI units, its dimension, and whether it can have an SI prefix
+struct Unit {
+    symbol: &'static str,
+    value: Scalar,
+    dimension: Dimension,
+    prefixable: bool
+}
+
+
+const fn unit(symbol: &'static str, value: Scalar, dimension: Dimension, prefixable: bool) -> Unit {
+    Unit { symbol, value, dimension, prefixable }
+}
+
+
+const UNITS: [Unit; 37] = [
+    // SI
+    unit("m", Scalar::new(1.0), Dimension::LENGTH, true),
+    unit("g", Scalar::new(1e-3), Dimension::MASS, true),
+    unit("s", Scalar::new(1.0), Dimension::TIME, true),
+    unit("A", Scalar::new(1.0), Dimension::CURRENT, true),
+    unit("K", Scalar::new(1.0), Dimension::TEMPERATURE, true),
+    unit("N", Scalar::new(1.0), Dimension::FORCE, true),
+    unit("J", Scalar::new(1.0), Dimension::ENERGY, true),
+    unit("W", Scalar::new(1.0), Dimension::POWER, true),
+    unit("Pa", Scalar::new(1.0), Dimension::PRESSURE, true),
+    unit("Hz", Scalar::new(1.0), Dimension::FREQUENCY, true),
+    unit("C", Scalar::new(1.0), Dimension::CHARGE, true),
+    unit("t", Scalar::new(1e3), Dimension::MASS, true),
+    unit("eV", Scalar::new(1.602_176_634e-19), Dimension::ENERGY, true),
+
+    // Time
+    unit("min", MINUTE.value, Dimension::TIME, false),
+    unit("h", HOUR.value, Dimension::TIME, false),
+    unit("d", DAY.value, Dimension::TIME, false),
+    unit("day", DAY.value, Dimension::TIME, false),
+    unit("days", DAY.value, Dimension::TIME, false),
+    unit("week", WEEK.value, Dimension::TIME, false),
+    unit("weeks", WEEK.value, Dimension::TIME, false),
+    unit("month", MONTH.value, Dimension::TIME, false),
+    unit("months", MONTH.value, Dimension::TIME, false),
+    unit("yr", YEAR.value, Dimension::TIME, true),
+    unit("y", YEAR.value, Dimension::TIME, false),
+    unit("year", YEAR.value, Dimension::TIME, false),
+    unit("years", YEAR.value, Dimension::TIME, false),
+
+    // Astronomy
+    unit("AU", AU.value, Dimension::LENGTH, false),
+    unit("au", AU.value, Dimension::LENGTH, false),
+    unit("ly", LIGHTYEAR.value, Dimension::LENGTH, true),
+    unit("pc", PARSEC.value, Dimension::LENGTH, true),
+    unit("SM", SOLAR_MASS.value, Dimension::MASS, false),
+    unit("M☉", SOLAR_MASS.value, Dimension::MASS, false),
+    unit("EM", EARTH_MASS.value, Dimension::MASS, false),
+    unit("M⊕", EARTH_MASS.value, Dimension::MASS, false),
+    unit("RS", SOLAR_RADIUS.value, Dimension::LENGTH, false),
+    unit("R☉", SOLAR_RADIUS.value, Dimension::LENGTH, false),
+
+    // Angles
+    unit("deg", Scalar::new(std::f64::consts::PI / 180.0), Dimension::DIMENSIONLESS, false)
+];
+
+
+/// Look up a unit symbol, with or without an SI prefix
+fn find_unit(symbol: &str) -> Option<(Scalar, Dimension)> {
+    if let Some(unit) = UNITS.iter().find(|unit| unit.symbol == symbol) {
+        return Some((unit.value, unit.dimension));
+    }
+
+    PREFIXES.iter().find_map(|(prefix, exponent)| {
+        let rest: &str = symbol.strip_prefix(prefix)?;
+        let unit: &Unit = UNITS.iter().find(|unit| unit.symbol == rest && unit.prefixable)?;
+        Some((unit.value * scalar!(10f64.powi(*exponent)), unit.dimension))
+    })
+}
+
+
+/* ----- PARSER ----- */
+/// A factor of a number
+enum Factor {
+    Literal(String, i32),
+    PowerOfTen(i32),
+    Value(Scalar)
+}
+
+
+/// The value of decimal digits multiplied by a power of ten, rounded once
+fn decimal(digits: &str, exponent: i32) -> f64 {
+    format!("{digits}e{exponent}").parse::<f64>().unwrap_or(f64::NAN)
+}
+
+
+struct Parser<'a> {
+    input: &'a str,
+    characters: Vec<char>,
+    index: usize
+}
+
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, characters: input.chars().collect(), index: 0 }
+    }
+
+    fn peek(&self) -> Option<char> { self.characters.get(self.index).copied() }
+    fn peek_at(&self, offset: usize) -> Option<char> { self.characters.get(self.index + offset).copied() }
+
+    fn next(&mut self) -> Option<char> {
+        let character: Option<char> = self.peek();
+        if character.is_some() { self.index += 1; }
+        character
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) { self.index += 1; }
+    }
+
+    fn error(&self, message: impl ToString) -> UnitError { self.error_at(self.index, message) }
+    fn error_at(&self, position: usize, message: impl ToString) -> UnitError { UnitError::new(self.input, position, message) }
+
+    fn is_unit_character(character: char) -> bool {
+        character.is_alphabetic() || matches!(character, 'µ' | '☉' | '⊕')
+    }
+
+    /// A number (or a sum of numbers), followed by optional units
+    fn quantity(&mut self) -> Result<Measurement, UnitError> {
+        self.skip_whitespace();
+        let position: usize = self.index;
+        let mut number: Scalar = self.number()?;
+
+        // Sums keep the precision of the extended-precision values: `1.5e11 + 2.5e-6`
+        loop {
+            let checkpoint: usize = self.index;
+            self.skip_whitespace();
+            let negative: bool = match self.peek() {
+                Some('+') => false,
+                Some('-') => true,
+                _ => { self.index = checkpoint; break; }
+            };
+            self.next();
+            self.skip_whitespace();
+            let term: Scalar = self.number()?;
+            number = if negative { number - term } else { number + term };
+        }
+
+        let (factor, dimension) = self.units()?;
+        Ok(Measurement { value: number * factor, dimension, input: self.input.to_string(), position })
+    }
+
+    /// A number, optionally multiplied by other numbers and powers: `3.3 * 10^22`.\
+    /// The powers of ten are added to the decimal exponent of the first literal,
+    /// so `3.3 * 10^22` is read exactly like `3.3e22`.
+    fn number(&mut self) -> Result<Scalar, UnitError> {
+        let mut mantissa: Option<String> = None;
+        let mut exponent: i32 = 0;
+        let mut multiplier: Scalar = scalar!(1);
+
+        loop {
+            match self.factor()? {
+                Factor::Literal(digits, power) if mantissa.is_none() => {
+                    mantissa = Some(digits);
+                    exponent += power;
+                },
+                Factor::Literal(digits, power) => multiplier *= scalar!(decimal(&digits, power)),
+                Factor::PowerOfTen(power) => exponent += power,
+                Factor::Value(value) => multiplier *= value
+            }
+
+            let checkpoint: usize = self.index;
+            self.skip_whitespace();
+            if !matches!(self.peek(), Some('*' | '×')) { self.index = checkpoint; break; }
+            self.next();
+            self.skip_whitespace();
+
+            // `*` can also join units: `5 * kg`
+            if !self.peek().is_some_and(|character| character.is_ascii_digit() || matches!(character, '.' | '-' | '+')) {
+                self.index = checkpoint;
+                break;
+            }
+        }
+
+        let base: f64 = decimal(mantissa.as_deref().unwrap_or("1"), exponent);
+        Ok(scalar!(base) * multiplier)
+    }
+
+    /// A literal, optionally raised to an integer power: `10^22`
+    fn factor(&mut self) -> Result<Factor, UnitError> {
+        let (digits, power) = self.literal()?;
+        if self.peek() != Some('^') { return Ok(Factor::Literal(digits, power)); }
+        self.next();
+        let exponent: i32 = self.integer()?;
+
+        if digits == "10" && power == 0 {
+            Ok(Factor::PowerOfTen(exponent))
+        } else {
+            Ok(Factor::Value(scalar!(decimal(&digits, power)).powi(exponent)))
+        }
+    }
+
+    /// A number literal, like `-1.5e-3` or `58 000 000`.
+    /// Returns the digits (with the sign and the decimal point) and the decimal exponent.
+    fn literal(&mut self) -> Result<(String, i32), UnitError> {
+        let start: usize = self.index;
+        let mut text: String = String::new();
+
+        if let Some(sign @ ('-' | '+')) = self.peek() {
+            text.push(sign);
+            self.next();
+        }
+
+        let mut digits: usize = 0;
+        while let Some(character) = self.peek() {
+            if character.is_ascii_digit() {
+                text.push(character);
+                digits += 1;
+                self.next();
+            } else if matches!(character, ' ' | '_' | '\u{2009}' | '\u{202f}') && digits > 0 && self.is_thousands_group() {
+                self.next();
+            } else {
+                break;
+            }
+        }
+
+        if self.peek() == Some('.') {
+            text.push('.');
+            self.next();
+            while let Some(character) = self.peek().filter(char::is_ascii_digit) {
+                text.push(character);
+                digits += 1;
+                self.next();
+            }
+        }
+
+        if digits == 0 {
+            return Err(self.error_at(start, "expected a number"));
+        }
+
+        // The exponent, if the `e` is followed by a number (otherwise it is a unit)
+        let mut exponent: i32 = 0;
+        if let Some('e' | 'E') = self.peek() {
+            let sign: bool = matches!(self.peek_at(1), Some('-' | '+'));
+            let digit: Option<char> = self.peek_at(if sign { 2 } else { 1 });
+            if digit.is_some_and(|character| character.is_ascii_digit()) {
+                self.next();
+                exponent = self.integer()?;
+            }
+        }
+
+        Ok((text, exponent))
+    }
+
+    /// Is the next character a thousands separator, followed by exactly three digits?
+    fn is_thousands_group(&self) -> bool {
+        (1..=3).all(|offset| self.peek_at(offset).is_some_and(|character| character.is_ascii_digit()))
+            && !self.peek_at(4).is_some_and(|character| character.is_ascii_digit())
+    }
+
+    /// An integer, like `22` or `-2`
+    fn integer(&mut self) -> Result<i32, UnitError> {
+        let start: usize = self.index;
+        let mut text: String = String::new();
+        if let Some(sign @ ('-' | '+')) = self.peek() {
+            text.push(sign);
+            self.next();
+        }
+        while let Some(character) = self.peek().filter(char::is_ascii_digit) {
+            text.push(character);
+            self.next();
+        }
+        text.parse::<i32>().map_err(|_| self.error_at(start, "expected an integer exponent"))
+    }
+
+    /// The units after a number: `km/s`, `kg m s^-2`, ...
+    fn units(&mut self) -> Result<(Scalar, Dimension), UnitError> {
+        let mut factor: Scalar = scalar!(1);
+        let mut dimension: Dimension = Dimension::DIMENSIONLESS;
+        let mut divide: bool;
+
+        loop {
+            let checkpoint: usize = self.index;
+            self.skip_whitespace();
+            match self.peek() {
+                Some('*' | '·' | '×') => { self.next(); divide = false; },
+                Some('/') => { self.next(); divide = true; },
+                Some(character) if Self::is_unit_character(character) => divide = false,
+                _ => { self.index = checkpoint; break; }
+            }
+            self.skip_whitespace();
+
+            let start: usize = self.index;
+            let (term_factor, term_dimension) = self.unit_term()?;
+            let product: Option<Dimension> = if divide {
+                factor /= term_factor;
+                dimension.checked_div(term_dimension)
+            } else {
+                factor *= term_factor;
+                dimension.checked_mul(term_dimension)
+            };
+            dimension = product.ok_or_else(|| self.error_at(start, "the exponent of the unit is too large"))?;
+        }
+
+        Ok((factor, dimension))
+    }
+
+    /// A unit symbol with an optional power: `km`, `s^-2`, `m²`
+    fn unit_term(&mut self) -> Result<(Scalar, Dimension), UnitError> {
+        let start: usize = self.index;
+        let mut symbol: String = String::new();
+        while let Some(character) = self.peek().filter(|character| Self::is_unit_character(*character)) {
+            symbol.push(character);
+            self.next();
+        }
+        if symbol.is_empty() {
+            return Err(match self.peek() {
+                Some(character) => self.error(format!("expected a unit, found '{character}'")),
+                None => self.error("expected a unit")
+            });
+        }
+
+        let (value, dimension) = find_unit(&symbol)
+            .ok_or_else(|| self.error_at(start, format!("unknown unit '{symbol}'")))?;
+
+        let exponent: i32 = match self.peek() {
+            Some('^') => { self.next(); self.integer()? },
+            Some('²') => { self.next(); 2 },
+            Some('³') => { self.next(); 3 },
+            _ => 1
+        };
+        let small: i8 = i8::try_from(exponent).map_err(|_| self.error_at(start, "the exponent is too large"))?;
+
+        let power: Dimension = dimension.checked_powi(small)
+            .ok_or_else(|| self.error_at(start, "the exponent is too large"))?;
+
+        Ok((value.powi(exponent), power))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn overflowing_exponents_are_errors() {
+        let error: UnitError = parse_quantity("1 m^100 m^100").unwrap_err();
+        assert_eq!(error.position, 8);
+
+        let error: UnitError = parse_quantity("1 N^100").unwrap_err();
+        assert_eq!(error.position, 2);
+
+        let error: UnitError = parse_quantity("1 m^100 / s^-100 / s^-100").unwrap_err();
+        assert_eq!(error.position, 19);
+    }
+
+    #[test]
+    fn large_exponents_in_range_are_parsed() {
+        let quantity: Measurement = parse_quantity("1 m^100 m^27").unwrap();
+        assert_eq!(quantity.dimension, Dimension::new(0, 127, 0, 0, 0));
+    }
+}
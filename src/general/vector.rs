@@ -66,12 +66,23 @@ impl Vector {
     /// Calculate the magnitude of the vector
     pub fn magnitude(&self) -> Scalar {
         // Square of the magnitude in the horizontal plane
-        let horizontal: f64 = self.point.x.value.powi(2) + self.point.z.value.powi(2);
+        let horizontal: Scalar = self.point.x * self.point.x + self.point.z * self.point.z;
 
         // Calculate the magnitude
-        let magnitude: f64 = (horizontal + self.point.y.value.powi(2)).sqrt();
+        (horizontal + self.point.y * self.point.y).sqrt()
+    }
+
+    /// Calculate the dot product of two vectors
+    pub fn dot(&self, other: &Vector) -> Scalar {
+        self.point.x * other.point.x + self.point.y * other.point.y + self.point.z * other.point.z
+    }
 
-        Scalar::new(magnitude)
+    /// Calculate the unit vector pointing in the same direction.
+    /// A null vector stays a null vector.
+    pub fn unit(&self) -> Vector {
+        let magnitude: Scalar = self.magnitude();
+        if magnitude == ZERO { return *self; }
+        *self / magnitude
     }
 }
 
@@ -98,7 +109,7 @@ impl std::ops::Sub for Vector {
 
 impl std::ops::SubAssign for Vector {
     fn sub_assign(&mut self, rhs: Self) {
-        *self = *self + -rhs
+        *self += -rhs
     }
 }
 
@@ -186,29 +197,29 @@ impl std::fmt::Debug for Vector {
 
 impl std::fmt::LowerExp for Vector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:e}", self.magnitude().value)
+        write!(f, "{:e}", self.magnitude().to_f64())
     }
 }
 
 impl std::fmt::UpperExp for Vector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:E}", self.magnitude().value)
+        write!(f, "{:E}", self.magnitude().to_f64())
     }
 }
 
 
 #[macro_export]
 macro_rules! vector {
-    () => { NULL_VECTOR };
-    (0) => { NULL_VECTOR };
+    () => { $crate::general::NULL_VECTOR };
+    (0) => { $crate::general::NULL_VECTOR };
     ($x: expr, $y: expr, $z: expr) => {
-        Vector::new(
-            scalar!($x), scalar!($y), scalar!($z)
+        $crate::general::Vector::new(
+            $crate::scalar!($x), $crate::scalar!($y), $crate::scalar!($z)
         )
     };
     (($x: expr, $y: expr, $z: expr)) => {
-        Vector::new(
-            scalar!($x), scalar!($y), scalar!($z)
+        $crate::general::Vector::new(
+            $crate::scalar!($x), $crate::scalar!($y), $crate::scalar!($z)
         )
     }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 mod window;
 mod text;
+mod viewer;
 
 pub use window::Window;
 pub use text::Font;
-pub use glfw::{WindowEvent, Key, Action, MouseButton, flush_messages};
+pub use viewer::Viewer;
+pub use glfw::{WindowEvent, Key, Action, MouseButton, flush_messages, InitError};
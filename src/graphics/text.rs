@@ -137,9 +137,6 @@ impl Font {
 #[macro_export]
 macro_rules! load_font {
     ($font: literal) => {
-        {
-            use crate::graphics::Font;
-            Font::new($font)
-        }
+        $crate::graphics::Font::new($font)
     };
 }
\ No newline at end of file
@@ -0,0 +1,321 @@
+use crate::*;
+use std::collections::HashMap;
+
+const RADIUS_MULTIPLIER: f64 = 100.0;
+const REDRAW_AMOUNT: u16 = 2500;
+const TRACER_SIZE: f32 = 2.0;
+
+
+/* ----- TEXTURES ----- */
+const STAR_TEXTURE: &str = "textures/star_grayscale.jpg";
+const EARTH_TEXTURE: &str = "textures/earth.jpeg";
+const TERRESTRIAL_TEXTURE: &str = "textures/terrestrial.jpg";
+
+
+/* ----- BACKGROUND IMAGE ----- */
+const BACKGROUND_IMAGE: &str = "images/astronomy_bg_small.jpg";
+
+
+/// # Viewer
+/// The interactive front end of an `AstronomicalSimulation`.
+/// It owns the window and everything needed for drawing, and only observes the simulation:
+/// it steps it, draws its celestials and forwards the controls (pause, reverse, save) to it.
+/// ## Attributes
+/// `window: Window` - The window the simulation is drawn into\
+/// `textures: HashMap<String, u32>` - The textures of the celestials\
+/// `font: Font` - The font of the labels\
+/// `move_speed: f32` - The speed of the camera\
+/// `rotation_sensitivity: f32` - The speed of the camera rotation when dragging with the mouse
+pub struct Viewer {
+    window: Window,
+    textures: HashMap<String, u32>,
+    font: Font,
+
+    move_speed: f32,
+    drag: [f64; 2],
+    button_held_down: bool,
+    mouse_pos: [f64; 2],
+    rotation_sensitivity: f32,
+
+    calculation_amount: u16
+}
+
+
+impl Viewer {
+    /// Open the window of the simulation
+    pub fn new(simulation: &AstronomicalSimulation) -> Result<Self, InitError> {
+        let window: Window = Window::new(
+            600,
+            600,
+            [0.0, 0.0, 0.0, 1.0],
+            "Astronomical Simulation",
+            45,
+            [0.0, 0.0, -5.0],
+            Some(BACKGROUND_IMAGE)
+        )?;
+
+        let mut s: Self = Self {
+            window,
+            textures: HashMap::new(),
+            font: load_font!("fonts\\arial.ttf"),
+            move_speed: simulation.get_move_speed(),
+            drag: [0.0; 2],
+            button_held_down: false,
+            mouse_pos: [0.0; 2],
+            rotation_sensitivity: 0.05,
+            calculation_amount: 0
+        };
+
+        s.load_textures();
+        Ok(s)
+    }
+
+    fn load_textures(&mut self) {
+        self.textures.insert(
+            String::from("star"), Window::load_texture(STAR_TEXTURE)
+        );
+        self.textures.insert(
+            String::from("terrestrial"), Window::load_texture(TERRESTRIAL_TEXTURE)
+        );
+        self.textures.insert(
+            String::from("earth"), Window::load_texture(EARTH_TEXTURE)
+        );
+    }
+
+    /// Convert the coordinates of a celestial to the <-1; +1> range
+    fn screen_coordinates(celestial: &(dyn Celestial + Sync), map_size: Scalar) -> [f32; 3] {
+        let coordinates: &Point = &celestial.point_body().coordinates;
+        [
+            (coordinates.x / map_size).to_f64() as f32,
+            (coordinates.y / map_size).to_f64() as f32,
+            (coordinates.z / map_size).to_f64() as f32
+        ]
+    }
+
+    /// Add a light source for each star.
+    /// If there are more stars than light slots, only the brightest ones light up the scene,
+    /// the rest of them are still drawn glowing.
+    fn add_light_sources(simulation: &AstronomicalSimulation) {
+        let mut stars: Vec<&(dyn Celestial + Sync)> = simulation.get_celestials().iter()
+            .filter(|celestial| celestial.is_star() && !celestial.is_tracer())
+            .map(|celestial| celestial.as_ref())
+            .collect();
+        stars.sort_by(|a, b| {
+            b.get_luminosity().partial_cmp(&a.get_luminosity()).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        stars.truncate(Window::max_light_sources());
+
+        let brightest: Power = stars.first().map(|star| star.get_luminosity()).unwrap_or(Power::new(ZERO));
+        for (index, star) in stars.iter().enumerate() {
+            let colors: [f64; 4] = star.get_color();
+            let intensity: f32 = if brightest.value > ZERO { (star.get_luminosity() / brightest).to_f64() as f32 } else { 1.0 };
+            Window::add_light_source(
+                index,
+                [colors[0] as f32, colors[1] as f32, colors[2] as f32],
+                Self::screen_coordinates(*star, simulation.get_map_size()),
+                intensity
+            );
+        }
+        Window::disable_light_sources(stars.len());
+    }
+
+    pub fn draw_objects(&self, simulation: &AstronomicalSimulation) {
+        let map_size: Scalar = simulation.get_map_size();
+        Self::add_light_sources(simulation);
+
+        for celestial in simulation.get_celestials().iter() {
+            let coordinates: [f32; 3] = Self::screen_coordinates(celestial.as_ref(), map_size);
+
+            let colors: [f64; 4] = celestial.get_color();
+            let color: [f32; 3] = [colors[0] as f32, colors[1] as f32, colors[2] as f32];
+
+            if celestial.is_tracer() {
+                Window::draw_point(coordinates, TRACER_SIZE, color);
+                continue;
+            }
+
+            self.window.draw_text(
+                coordinates[0],
+                coordinates[1],
+                coordinates[2],
+                celestial.get_name(),
+                self.font.clone(),
+                24.0,
+                [1.0, 1.0, 1.0]
+            );
+
+            if celestial.is_star() {
+                Window::emit_light(color);
+                Window::bind_texture(
+                    unsafe { *self.textures.get("star").unwrap_unchecked() }
+                )
+            } else if celestial.is_planet() {
+                Window::cancel_emission();
+                Window::react_to_light(color);
+                Window::bind_texture(
+                    unsafe {
+                        *self.textures.get(
+                            if celestial.get_name().to_lowercase().as_str() == "earth" { "earth" }
+                            else { "terrestrial" }
+                        ).unwrap_unchecked()
+                    }
+                )
+            } else {
+                Window::cancel_emission();
+                Window::react_to_light(color);
+                Window::bind_texture(0);
+            }
+
+            let radius: f64 = (celestial.get_radius() / map_size).to_f64() * RADIUS_MULTIPLIER;
+
+            Window::enable_light();
+            Window::enable_texture();
+
+            Window::draw_sphere(coordinates, radius, color, 64);
+        }
+    }
+
+    fn move_forward(&mut self, amount: f32) {
+        let rot_x: Degree = Degree::from_float(self.window.camera_rotation[1] as f64);
+        let rot_y: Degree = Degree::from_float(self.window.camera_rotation[0] as f64);
+        self.window.camera_location = [
+            self.window.camera_location[0] - amount * (rot_x.sin() * rot_y.cos()) as f32,
+            self.window.camera_location[1] + amount * rot_y.sin() as f32,
+            self.window.camera_location[2] + amount * (rot_x.cos() * rot_y.cos()) as f32
+        ];
+    }
+
+    fn move_backward(&mut self, amount: f32) {
+        self.move_forward(-amount);
+    }
+
+    fn move_left(&mut self, amount: f32) {
+        let rot_x: Degree = Degree::from_float(self.window.camera_rotation[1] as f64);
+        let rot_y: Degree = Degree::from_float(self.window.camera_rotation[0] as f64);
+        self.window.camera_location = [
+            self.window.camera_location[0] + amount * (rot_x.cos() * rot_y.cos()) as f32,
+            self.window.camera_location[1] + amount * rot_y.sin() as f32,
+            self.window.camera_location[2] + amount * (rot_x.sin() * rot_y.cos()) as f32
+        ];
+    }
+
+    fn move_right(&mut self, amount: f32) {
+        self.move_left(-amount);
+    }
+
+    fn move_up(&mut self, amount: f32) {
+        self.window.camera_location = [
+            self.window.camera_location[0],
+            self.window.camera_location[1] - amount,
+            self.window.camera_location[2]
+        ];
+    }
+
+    fn move_down(&mut self, amount: f32) {
+        self.move_up(-amount);
+    }
+
+    fn rotate_camera(&mut self, roll: f32, pitch: f32, yaw: f32) {
+        self.window.camera_rotation = [
+            self.window.camera_rotation[0] + roll,
+            self.window.camera_rotation[1] + pitch,
+            self.window.camera_rotation[2] + yaw
+        ]
+    }
+
+    fn handle_events(&mut self, simulation: &mut AstronomicalSimulation, event: WindowEvent) {
+        let (x, y) = self.window.get_cursor_pos();
+        if self.button_held_down {
+            let dx: f64 = x - self.mouse_pos[0];
+            let dy: f64 = y - self.mouse_pos[1];
+            self.drag = [dx, dy];
+            self.rotate_camera(self.drag[1] as f32 * self.rotation_sensitivity, self.drag[0] as f32 * self.rotation_sensitivity, 0.0);
+        }
+        self.mouse_pos = [x, y];
+
+        match event {
+            WindowEvent::Key(Key::W, _, Action::Press, _) | WindowEvent::Key(Key::W, _, Action::Repeat, _) => {
+                self.move_forward(self.move_speed);
+            },
+            WindowEvent::Key(Key::S, _, Action::Press, _) | WindowEvent::Key(Key::S, _, Action::Repeat, _) => {
+                self.move_backward(self.move_speed);
+            },
+            WindowEvent::Key(Key::D, _, Action::Press, _) | WindowEvent::Key(Key::D, _, Action::Repeat, _) => {
+                self.move_right(self.move_speed);
+            },
+            WindowEvent::Key(Key::A, _, Action::Press, _) | WindowEvent::Key(Key::A, _, Action::Repeat, _) => {
+                self.move_left(self.move_speed);
+            },
+            WindowEvent::Key(Key::E, _, Action::Press, _) | WindowEvent::Key(Key::E, _, Action::Repeat, _) => {
+                self.move_up(self.move_speed);
+            },
+            WindowEvent::Key(Key::Q, _, Action::Press, _) | WindowEvent::Key(Key::Q, _, Action::Repeat, _) => {
+                self.move_down(self.move_speed);
+            },
+            WindowEvent::Key(Key::LeftShift, _, Action::Press, _) => {
+                self.move_speed *= 5.0;
+            },
+            WindowEvent::Key(Key::LeftShift, _, Action::Release, _) => {
+                self.move_speed /= 5.0;
+            },
+            WindowEvent::Key(Key::Space, _, Action::Press, _) => {
+                simulation.toggle_time();
+            },
+            WindowEvent::Key(Key::R, _, Action::Press, _) => {
+                simulation.reverse_time();
+            },
+            WindowEvent::Key(Key::F5, _, Action::Press, _) => {
+                match simulation.save() {
+                    Ok(skipped) if skipped.is_empty() => println!("Saved to {}", simulation.get_save_path()),
+                    Ok(skipped) => println!("Saved to {} (without {})", simulation.get_save_path(), skipped.join(", ")),
+                    Err(error) => eprintln!("Could not save to {}: {error}", simulation.get_save_path())
+                }
+            },
+            WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                self.button_held_down = true;
+                self.drag = [0.0; 2];
+            },
+            WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+                self.button_held_down = false;
+                self.drag = [0.0; 2];
+            }
+            _ => ()
+        }
+    }
+
+    /// Step and draw the simulation until the window is closed
+    pub fn run(&mut self, simulation: &mut AstronomicalSimulation) {
+        let this: *mut Self = self as *mut Self;
+
+        self.window.start_render_loop(move |_w, e| {
+            unsafe {
+                if !simulation.is_time_stopped() {
+                    simulation.step();
+                }
+
+                if (*this).calculation_amount < REDRAW_AMOUNT {
+                    (*this).calculation_amount += 1;
+                    return;
+                }
+
+                (*this).calculation_amount = 0;
+
+                Window::load_identity_matrix();
+                Window::clear_screen();
+
+                for (_, event) in flush_messages(e) {
+                    (*this).handle_events(simulation, event);
+                }
+
+                (*this).window.move_camera();
+
+                (*this).window.load_background_image();
+                (*this).draw_objects(simulation);
+
+                (*this).window.swap_buffers();
+                (*this).window.poll_events();
+            }
+        }
+        );
+    }
+}
@@ -188,6 +188,21 @@ impl Window {
         }
     }
 
+    /// Draw a single point at the given coordinates with the given size (in pixels) and color.
+    /// Lighting and texturing are disabled for the point.
+    pub fn draw_point(coordinates: [f32; 3], size: f32, color: [f32; 3]) {
+        unsafe {
+            glDisable(GL_LIGHTING);
+            glDisable(GL_TEXTURE_2D);
+            glPointSize(size);
+            glColor4f(color[0], color[1], color[2], 1.0);
+
+            glBegin(GL_POINTS);
+            glVertex3f(coordinates[0], coordinates[1], coordinates[2]);
+            glEnd();
+        }
+    }
+
     /// Load a texture and return its ID
     /// ## Important
     /// This only loads the texture into memory.\
@@ -263,23 +278,48 @@ impl Window {
         bound_texture
     }
 
-    /// Add a light source
-    pub fn add_light_source(color: [f32; 3], coordinates: [f32; 3]) {
+    /// Get the number of light sources OpenGL can handle at the same time
+    pub fn max_light_sources() -> usize {
+        let mut lights: i32 = 0;
+        unsafe { glGetIntegerv(GL_MAX_LIGHTS, &mut lights) };
+        lights.max(0) as usize
+    }
+
+    /// Add a light source in the given slot (`GL_LIGHT0 + index`)
+    /// ## Params
+    /// `index: usize` - The slot of the light, below `Window::max_light_sources()`\
+    /// `color: [f32; 3]` - The RGB color of the light\
+    /// `coordinates: [f32; 3]` - The position of the light\
+    /// `intensity: f32` - The brightness of the light relative to the brightest one (`0.0` - `1.0`)
+    pub fn add_light_source(index: usize, color: [f32; 3], coordinates: [f32; 3], intensity: f32) {
         unsafe {
-            let light: u32 = GL_LIGHT0;
-            glMaterialfv(GL_FRONT, GL_EMISSION, [color[0], color[1], color[2], 1.0].as_ptr() as *const _);
+            let light: u32 = GL_LIGHT0 + index as u32;
 
             glEnable(light);
             glLightfv(light, GL_POSITION, [coordinates[0], coordinates[1], coordinates[2], 1.0].as_ptr() as *const _);
             glLightfv(light, GL_DIFFUSE, [
-                0.7 + 0.3 * color[0],
-                0.7 + 0.3 * color[1],
-                0.7 + 0.3 * color[2],
+                (0.7 + 0.3 * color[0]) * intensity,
+                (0.7 + 0.3 * color[1]) * intensity,
+                (0.7 + 0.3 * color[2]) * intensity,
                 1.0,
             ].as_ptr() as *const _);
         }
     }
 
+    /// Disable the light sources in the slots starting at `from`
+    pub fn disable_light_sources(from: usize) {
+        for index in from..Self::max_light_sources() {
+            unsafe { glDisable(GL_LIGHT0 + index as u32); }
+        }
+    }
+
+    /// Make the material glow with the given color
+    pub fn emit_light(color: [f32; 3]) {
+        unsafe {
+            glMaterialfv(GL_FRONT, GL_EMISSION, [color[0], color[1], color[2], 1.0].as_ptr() as *const _);
+        }
+    }
+
     /// Cancel emission\
     /// Wrapper for:
     /// ```rust
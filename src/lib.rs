@@ -0,0 +1,34 @@
+//! # Physics Simulator
+//! An N-body simulation of stars, planets and spacecraft, with an optional interactive viewer.
+//!
+//! The modules are organised by field:
+//! - `general` - The math types: scalars, vectors, points, quantities with units, constants
+//! - `astronomy` - The celestials, the simulation, and the scenario, ephemeris and trajectory formats
+//! - `graphics` - The window and the viewer (only with the default `graphics` feature)
+//!
+//! The `prelude` collects the types most programs need:
+//! ```no_run
+//! use physics_simulator::prelude::*;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut simulation: AstronomicalSimulation = load_simulation("simulations/sun_and_terrestrials.psa")?;
+//! simulation.run_for(1000);
+//! # Ok(())
+//! # }
+//! ```
+//! The macros (`scalar!`, `vector!`, `point!`, `degree!`, `star!`, `planet!`, `spacecraft!`,
+//! `astronomical_simulation!` and `load_font!`) are exported at the root of the crate.
+
+pub mod astronomy;
+pub mod general;
+#[cfg(feature = "graphics")]
+pub mod graphics;
+pub mod prelude;
+
+// The modules of the crate see each other's items through `use crate::*;`
+#[cfg(feature = "graphics")]
+use crate::graphics::*;
+use crate::{
+    astronomy::*,
+    general::*
+};
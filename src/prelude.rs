@@ -0,0 +1,28 @@
+//! The types, functions and macros most programs need, to be imported with `use physics_simulator::prelude::*;`.
+//! The constants `c` and `g` are left out, so they do not shadow local variables: use `constants::c` and `constants::g`.
+
+pub use crate::general::{
+    Scalar, Vector, Point, Degree, PointBody,
+    Length, Mass, Time, Velocity, Acceleration, Force, Energy, Power, Temperature,
+    UnitError, parse_quantity, parse_vector,
+    constants,
+    ZERO, NULL_VECTOR, ORIGO, G, PI, AU, LIGHTYEAR, PARSEC,
+    EARTH_MASS, SOLAR_MASS, SOLAR_RADIUS, SOLAR_LUMINOSITY,
+    SECOND, MINUTE, HOUR, DAY, WEEK, MONTH, YEAR
+};
+
+pub use crate::astronomy::{
+    Celestial, Star, StarType, Planet, PlanetType, Spacecraft, Burn, BurnDirection,
+    AstronomicalSimulation, Integrator, ReversibilityReport, EventKind, EventDetector, HaloPotential,
+    OrbitalElements, barycenter, energy,
+    Scenario, ScenarioError, load_simulation, save_simulation,
+    CheckpointError,
+    TrajectoryOutput, TrajectoryFormat, OemOutput,
+    HorizonsVectors, HorizonsError, SpkKernel, SpkError, Ephemeris, Rails,
+    Tle, TleError, MpcCatalogue, MpcOrbit, MpcError
+};
+
+#[cfg(feature = "graphics")]
+pub use crate::graphics::Viewer;
+
+pub use crate::{scalar, vector, point, degree, star, planet, spacecraft, astronomical_simulation};